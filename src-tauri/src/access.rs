@@ -0,0 +1,245 @@
+use crate::types::*;
+use anyhow::Result;
+
+// Arguments after the tunnel ID that select an entry for `access create` and
+// `access delete`
+pub fn subject_args(entry: &AccessControlEntry) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+
+    match entry.entry_type.as_str() {
+        "anonymous" => args.push("--anonymous".to_string()),
+        "organization" => {
+            let org_id = entry
+                .organization_id
+                .as_deref()
+                .filter(|id| !id.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("Organization access requires an organization ID"))?;
+            args.push("--org".to_string());
+            args.push(org_id.to_string());
+        }
+        "tenant" => args.push("--tenant".to_string()),
+        other => return Err(anyhow::anyhow!("Unsupported access entry type: {}", other)),
+    }
+
+    for port in entry.ports.iter().flatten() {
+        args.push("-p".to_string());
+        args.push(port.to_string());
+    }
+
+    if entry.deny.unwrap_or(false) {
+        args.push("--deny".to_string());
+    }
+
+    Ok(args)
+}
+
+pub fn create_args(entry: &AccessControlEntry) -> Result<Vec<String>> {
+    let mut args = subject_args(entry)?;
+    if let Some(expiration) = &entry.expiration {
+        args.push("--expiration".to_string());
+        args.push(expiration.clone());
+    }
+    Ok(args)
+}
+
+// The request that targets a listed entry; None when the CLI has no flags for
+// its subject (repositories, users) and only `access reset` can remove it
+pub fn request_for(entry: &AccessEntry) -> Option<AccessControlEntry> {
+    let (entry_type, organization_id) = match entry.entry_type {
+        AccessSubjectType::Anonymous => ("anonymous", None),
+        AccessSubjectType::Organization => ("organization", Some(entry.subject.clone()?)),
+        AccessSubjectType::Tenant => ("tenant", None),
+        _ => return None,
+    };
+
+    Some(AccessControlEntry {
+        entry_type: entry_type.to_string(),
+        scopes: None,
+        expiration: None,
+        ports: if entry.ports.is_empty() { None } else { Some(entry.ports.clone()) },
+        organization_id,
+        deny: if entry.is_deny { Some(true) } else { None },
+    })
+}
+
+// Scopes and expiration are left out: `access create` always grants connect,
+// and `access list` reports absolute expirations rather than durations
+fn entry_key(entry: &AccessControlEntry) -> (String, Option<String>, bool, Vec<u16>) {
+    let mut ports = entry.ports.clone().unwrap_or_default();
+    ports.sort_unstable();
+    ports.dedup();
+
+    let subject = match entry.entry_type.as_str() {
+        "organization" => entry.organization_id.as_ref().map(|id| id.to_lowercase()),
+        _ => None,
+    };
+
+    (entry.entry_type.to_lowercase(), subject, entry.deny.unwrap_or(false), ports)
+}
+
+pub fn same_entry(a: &AccessControlEntry, b: &AccessControlEntry) -> bool {
+    entry_key(a) == entry_key(b)
+}
+
+// Calls needed to turn `current` into `desired`. Inherited entries belong to
+// the tunnel and are left alone; when every entry has to go, or one can only be
+// removed by a reset, a single reset replaces the deletes.
+pub fn diff_access(
+    tunnel_id: &str,
+    current: Vec<AccessEntry>,
+    desired: &[AccessControlEntry],
+) -> Result<AccessPlan> {
+    for entry in desired {
+        subject_args(entry)?;
+    }
+
+    let own: Vec<(&AccessEntry, Option<AccessControlEntry>)> = current
+        .iter()
+        .filter(|e| !e.is_inherited)
+        .map(|e| (e, request_for(e)))
+        .collect();
+
+    let mut kept = vec![false; own.len()];
+    let mut missing = Vec::new();
+    for entry in desired {
+        let found = own.iter().enumerate().position(|(i, (_, req))| {
+            !kept[i] && req.as_ref().is_some_and(|req| same_entry(req, entry))
+        });
+        match found {
+            Some(i) => kept[i] = true,
+            None => missing.push(entry.clone()),
+        }
+    }
+
+    let stale: Vec<&(&AccessEntry, Option<AccessControlEntry>)> =
+        own.iter().zip(&kept).filter(|(_, k)| !**k).map(|(e, _)| e).collect();
+    let reset = !stale.is_empty()
+        && (stale.len() == own.len() || stale.iter().any(|(_, req)| req.is_none()));
+
+    let mut changes = Vec::new();
+    if reset {
+        changes.push(AccessChange::Reset);
+        // Kept entries are wiped by the reset too
+        changes.extend(desired.iter().cloned().map(|entry| AccessChange::Create { entry }));
+    } else {
+        changes.extend(
+            stale
+                .iter()
+                .filter_map(|(_, req)| req.clone())
+                .map(|entry| AccessChange::Delete { entry }),
+        );
+        changes.extend(missing.into_iter().map(|entry| AccessChange::Create { entry }));
+    }
+
+    let commands = changes
+        .iter()
+        .map(|change| change_command(tunnel_id, change))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(AccessPlan {
+        tunnel_id: tunnel_id.to_string(),
+        current,
+        changes,
+        commands,
+    })
+}
+
+fn change_command(tunnel_id: &str, change: &AccessChange) -> Result<String> {
+    let (verb, args) = match change {
+        AccessChange::Create { entry } => ("create", create_args(entry)?),
+        AccessChange::Delete { entry } => ("delete", subject_args(entry)?),
+        AccessChange::Reset => ("reset", Vec::new()),
+    };
+
+    let mut line = format!("devtunnel access {} {}", verb, tunnel_id);
+    for arg in args {
+        line.push(' ');
+        line.push_str(&arg);
+    }
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listed(entry_type: AccessSubjectType, subject: Option<&str>, ports: Vec<u16>) -> AccessEntry {
+        AccessEntry {
+            entry_type,
+            subject: subject.map(|s| s.to_string()),
+            provider: None,
+            is_deny: false,
+            is_inherited: false,
+            scopes: vec!["connect".to_string()],
+            ports,
+            expiration: None,
+        }
+    }
+
+    fn wanted(entry_type: &str, org: Option<&str>, ports: Option<Vec<u16>>) -> AccessControlEntry {
+        AccessControlEntry {
+            entry_type: entry_type.to_string(),
+            scopes: None,
+            expiration: None,
+            ports,
+            organization_id: org.map(|s| s.to_string()),
+            deny: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_access_creates_and_deletes() {
+        let current = vec![
+            listed(AccessSubjectType::Anonymous, None, vec![3000]),
+            listed(AccessSubjectType::Organization, Some("Contoso"), vec![]),
+        ];
+        let desired = vec![wanted("organization", Some("contoso"), None), wanted("tenant", None, None)];
+
+        let plan = diff_access("demo", current, &desired).unwrap();
+        assert_eq!(
+            plan.commands,
+            vec![
+                "devtunnel access delete demo --anonymous -p 3000",
+                "devtunnel access create demo --tenant",
+            ]
+        );
+
+        // Once applied there is nothing left to do
+        let applied = vec![
+            listed(AccessSubjectType::Organization, Some("Contoso"), vec![]),
+            listed(AccessSubjectType::Tenant, Some("tenant-id"), vec![]),
+        ];
+        assert!(diff_access("demo", applied, &desired).unwrap().changes.is_empty());
+    }
+
+    #[test]
+    fn test_diff_access_resets_when_needed() {
+        // Nothing to keep
+        let current = vec![listed(AccessSubjectType::Anonymous, None, vec![])];
+        let plan = diff_access("demo", current, &[]).unwrap();
+        assert_eq!(plan.changes, vec![AccessChange::Reset]);
+
+        // Repository entries have no delete flags, so kept entries are recreated after a reset
+        let current = vec![
+            listed(AccessSubjectType::Anonymous, None, vec![]),
+            listed(AccessSubjectType::Repository, Some("org/repo"), vec![]),
+        ];
+        let plan = diff_access("demo", current, &[wanted("anonymous", None, None)]).unwrap();
+        assert_eq!(
+            plan.commands,
+            vec!["devtunnel access reset demo", "devtunnel access create demo --anonymous"]
+        );
+
+        // Inherited entries are never touched
+        let mut inherited = listed(AccessSubjectType::Repository, Some("org/repo"), vec![]);
+        inherited.is_inherited = true;
+        let plan = diff_access("demo", vec![inherited], &[]).unwrap();
+        assert!(plan.changes.is_empty());
+    }
+
+    #[test]
+    fn test_diff_access_rejects_unsupported_entries() {
+        assert!(diff_access("demo", Vec::new(), &[wanted("organization", None, None)]).is_err());
+        assert!(diff_access("demo", Vec::new(), &[wanted("user", None, None)]).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::cache::{same_tunnel, StateCache};
+use crate::types::*;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::process::Command as TokioCommand;
+
+// Everything the app asks of the devtunnel CLI. DevTunnelClient implements it
+// by shelling out to the real binary; fake::FakeBackend keeps the same state
+// in memory so commands can be exercised without an account. Methods are async
+// so a stalled CLI never ties up a worker thread; see operations.rs for how
+// they are timed out and cancelled.
+#[async_trait]
+pub trait DevTunnelBackend: Send + Sync {
+    // Authentication
+    async fn login(&self, provider: &str, use_device_code: bool) -> Result<String>;
+    async fn logout(&self) -> Result<String>;
+    async fn get_user_info(&self) -> Result<UserInfo>;
+
+    // Tunnel Management
+    async fn create_tunnel(&self, req: CreateTunnelRequest) -> Result<String>;
+    async fn list_tunnels_light(&self, req: Option<ListTunnelsRequest>) -> Result<Vec<TunnelListItem>>;
+    async fn list_tunnels(&self, req: Option<ListTunnelsRequest>) -> Result<Vec<TunnelListItem>>;
+    async fn show_tunnel(&self, tunnel_id: Option<String>) -> Result<Tunnel>;
+    async fn update_tunnel(&self, req: UpdateTunnelRequest) -> Result<String>;
+    async fn delete_tunnel(&self, tunnel_id: String) -> Result<String>;
+    async fn delete_all_tunnels(&self) -> Result<String>;
+
+    // Port Management
+    async fn create_port(&self, req: CreatePortRequest) -> Result<String>;
+    async fn list_ports(&self, tunnel_id: String) -> Result<Vec<Port>>;
+    async fn show_port(&self, tunnel_id: String, port_number: u16) -> Result<Port>;
+    async fn update_port(&self, req: UpdatePortRequest) -> Result<String>;
+    async fn delete_port(&self, tunnel_id: String, port: u16) -> Result<String>;
+
+    // Access Control
+    async fn create_access(&self, req: CreateAccessRequest) -> Result<String>;
+    async fn list_access(&self, tunnel_id: String) -> Result<String>;
+    async fn list_access_entries(&self, tunnel_id: String) -> Result<Vec<AccessEntry>>;
+    async fn delete_access(&self, req: DeleteAccessRequest) -> Result<String>;
+    async fn reset_access(&self, tunnel_id: String) -> Result<String>;
+
+    // Tokens: returns the raw JWT; see token.rs for its claims
+    async fn issue_token(&self, tunnel_id: String, scopes: Vec<String>, port: Option<u16>) -> Result<String>;
+
+    // Clusters
+    async fn list_clusters(&self, ping: bool) -> Result<Vec<Cluster>>;
+
+    // Command line for `devtunnel host`; the supervisor spawns and owns the process
+    fn host_command(&self, req: &HostTunnelRequest) -> TokioCommand;
+
+    // Command line for `devtunnel connect`; owned by connect.rs the same way
+    fn connect_command(&self, req: &ConnectTunnelRequest) -> TokioCommand;
+}
+
+// 병렬 처리로 상세 정보 추가
+// Merges ports (and tags/description the light list lacks) from `show` into
+// `tunnels`. Details still fresh in the cache are reused unless force_refresh.
+// How many `show` processes actually run at once is up to the CLI executor.
+// A tunnel whose `show` fails stays in the list with `details_error` set.
+pub async fn enrich_tunnel_details(
+    backend: Arc<dyn DevTunnelBackend>,
+    cache: &StateCache,
+    mut tunnels: Vec<TunnelListItem>,
+    tunnel_ids: &[String],
+    force_refresh: bool,
+) -> Vec<TunnelListItem> {
+    use std::collections::HashMap;
+    use tokio::task::JoinSet;
+
+    let mut results = HashMap::new();
+    let mut set = JoinSet::new();
+    let mut tasks = HashMap::new();
+    let generation = cache.tunnels.generation();
+
+    for tunnel_id in tunnel_ids {
+        match cache.tunnels.get(tunnel_id).filter(|_| !force_refresh) {
+            Some(details) => {
+                results.insert(tunnel_id.clone(), Ok(details));
+            }
+            None => {
+                let backend = backend.clone();
+                let id = tunnel_id.clone();
+                let task = set.spawn(async move { backend.show_tunnel(Some(id)).await });
+                tasks.insert(task.id(), tunnel_id.clone());
+            }
+        }
+    }
+
+    while let Some(res) = set.join_next_with_id().await {
+        let (task, details) = match res {
+            Ok((task, details)) => (task, details),
+            Err(e) => (e.id(), Err(anyhow::anyhow!("Task join error: {}", e))),
+        };
+        let tunnel_id = tasks.remove(&task).unwrap_or_default();
+        if let Ok(details) = &details {
+            cache.tunnels.put_fetched(&tunnel_id, details.clone(), generation);
+        }
+        results.insert(tunnel_id, details);
+    }
+
+    for tunnel in &mut tunnels {
+        // Callers may pass the short ID ("demo" for "demo.usw2")
+        match results.iter().find(|(id, _)| same_tunnel(id, &tunnel.tunnel_id)).map(|(_, r)| r) {
+            Some(Ok(details)) => {
+                tunnel.ports = details.ports.iter().map(|p| p.port_number).collect();
+                if tunnel.tags.is_none() {
+                    tunnel.tags = details.tags.clone();
+                }
+                if tunnel.description.is_none() {
+                    tunnel.description = details.description.clone();
+                }
+                tunnel.details_error = None;
+            }
+            Some(Err(e)) => tunnel.details_error = Some(ItemError::from_error(e)),
+            None => {}
+        }
+    }
+
+    tunnels
+}
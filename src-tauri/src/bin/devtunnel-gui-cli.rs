@@ -0,0 +1,409 @@
+// Headless companion to the GUI. Talks to the running app over its control
+// socket when there is one, so tunnels stay under the app's supervisor;
+// otherwise runs the same backend and supervisor in this process.
+use devtunnel_gui_lib::commands::AppState;
+use devtunnel_gui_lib::control;
+use devtunnel_gui_lib::events::EventSink;
+use devtunnel_gui_lib::spec;
+use devtunnel_gui_lib::types::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: devtunnel-gui-cli [--json] [--standalone] <command>
+
+Commands:
+  list                            List tunnels
+  host <tunnel-id> [-p <port>]... Host a tunnel (in the app if it is running,
+                                  otherwise in the foreground until Ctrl-C)
+  stop <tunnel-id> [--grace <ms>] Stop a hosted tunnel
+  status                          Show hosted tunnel sessions
+  apply <spec-file> [--dry-run]   Apply a TOML/YAML spec file
+
+Options:
+  --json        Print the CommandResponse JSON the app would return
+  --standalone  Do not connect to a running app";
+
+const OUTPUT_POLL_MS: u64 = 200; // Host output polling interval in foreground mode
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    List,
+    Host { tunnel_id: String, ports: Vec<u16> },
+    Stop { tunnel_id: String, grace_ms: Option<u64> },
+    Status,
+    Apply { path: String, dry_run: bool },
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    json: bool,
+    standalone: bool,
+    command: Command,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut json = false;
+    let mut standalone = false;
+    let mut dry_run = false;
+    let mut grace_ms = None;
+    let mut ports = Vec::new();
+    let mut positional = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--standalone" => standalone = true,
+            "--dry-run" => dry_run = true,
+            "-p" | "--port" => {
+                let port = iter.next().ok_or("-p needs a port number")?;
+                ports.push(port.parse().map_err(|_| format!("Invalid port: {}", port))?);
+            }
+            "--grace" => {
+                let ms = iter.next().ok_or("--grace needs milliseconds")?;
+                grace_ms = Some(ms.parse().map_err(|_| format!("Invalid grace period: {}", ms))?);
+            }
+            other if other.starts_with('-') => return Err(format!("Unknown option: {}", other)),
+            other => positional.push(other.to_string()),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let name = positional.next().ok_or("Missing command")?;
+    let mut argument = |what: &str| positional.next().ok_or(format!("{} needs a {}", name, what));
+
+    let command = match name.as_str() {
+        "list" => Command::List,
+        "host" => Command::Host { tunnel_id: argument("tunnel ID")?, ports },
+        "stop" => Command::Stop { tunnel_id: argument("tunnel ID")?, grace_ms },
+        "status" => Command::Status,
+        "apply" => Command::Apply { path: argument("spec file")?, dry_run },
+        other => return Err(format!("Unknown command: {}", other)),
+    };
+    if let Some(extra) = positional.next() {
+        return Err(format!("Unexpected argument for {}: {}", name, extra));
+    }
+
+    Ok(Options { json, standalone, command })
+}
+
+// Backend events while running standalone
+struct ConsoleEvents;
+
+impl EventSink for ConsoleEvents {
+    fn emit_event(&self, event: &str, payload: serde_json::Value) {
+        eprintln!("[{}] {}", event, payload);
+    }
+
+    fn log(&self, level: LogLevel, source: &'static str, _tunnel_id: Option<&str>, message: &str) {
+        eprintln!("[{:?}] {}: {}", level, source, message);
+    }
+}
+
+enum Target {
+    #[cfg(unix)]
+    App(control::ControlClient),
+    Standalone(Box<AppState>),
+}
+
+impl Target {
+    fn open(standalone: bool) -> anyhow::Result<Self> {
+        #[cfg(unix)]
+        if !standalone {
+            if let Some(client) = control::ControlClient::connect(&control::socket_path())? {
+                return Ok(Target::App(client));
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = standalone;
+
+        let state = AppState::new(Arc::new(ConsoleEvents), &control::data_dir(), &control::log_dir());
+        // Pick up hosts started by the app or an earlier CLI run
+        state.supervisor.reconcile_sessions();
+        Ok(Target::Standalone(Box::new(state)))
+    }
+}
+
+fn respond<T>(result: anyhow::Result<T>) -> CommandResponse<T> {
+    match result {
+        Ok(data) => CommandResponse::success(data),
+        Err(e) => CommandResponse::failure(&e),
+    }
+}
+
+// Print a response and return the process exit code
+fn print<T: Serialize>(json: bool, response: CommandResponse<T>, human: impl FnOnce(&T)) -> i32 {
+    if json {
+        println!("{}", serde_json::to_string_pretty(&response).unwrap_or_default());
+    } else if let Some(data) = &response.data {
+        human(data);
+    }
+
+    if response.success {
+        return 0;
+    }
+    if !json {
+        eprintln!("Error: {}", response.error.unwrap_or_default());
+        if let Some(hint) = response.hint {
+            eprintln!("Hint: {}", hint);
+        }
+    }
+    1
+}
+
+fn print_tunnels(tunnels: &Vec<TunnelListItem>) {
+    if tunnels.is_empty() {
+        println!("No tunnels");
+        return;
+    }
+    println!("{:<32} {:<16} {:<16} DESCRIPTION", "TUNNEL ID", "PORTS", "TAGS");
+    for tunnel in tunnels {
+        let ports: Vec<String> = tunnel.ports.iter().map(|p| p.to_string()).collect();
+        let tags = tunnel.tags.clone().unwrap_or_default();
+        println!(
+            "{:<32} {:<16} {:<16} {}",
+            tunnel.tunnel_id,
+            if ports.is_empty() { "-".to_string() } else { ports.join(",") },
+            if tags.is_empty() { "-".to_string() } else { tags.join(",") },
+            tunnel.description.as_deref().unwrap_or("")
+        );
+    }
+}
+
+fn print_sessions(report: &HostSessionsReport) {
+    if report.sessions.is_empty() {
+        println!("No hosted tunnels");
+        return;
+    }
+    println!("{:<32} {:<8} {:<8} STARTED", "TUNNEL ID", "STATE", "PID");
+    for info in &report.sessions {
+        let state = match info.state {
+            HostSessionState::Running => "running",
+            HostSessionState::Adopted => "adopted",
+            HostSessionState::Stopped => "stopped",
+        };
+        println!(
+            "{:<32} {:<8} {:<8} {}",
+            info.session.tunnel_id, state, info.session.pid, info.session.started_at
+        );
+    }
+}
+
+fn print_stop(result: &StopResult) {
+    let how = match result.method {
+        StopMethod::Graceful => "terminated gracefully",
+        StopMethod::Forced => "killed after grace period",
+        StopMethod::AlreadyExited => "already exited",
+        StopMethod::NotRunning => "was not running",
+    };
+    println!("{}: {} ({} ms)", result.tunnel_id, how, result.elapsed_ms);
+}
+
+fn print_plan(plan: &SpecPlan) {
+    if plan.steps.is_empty() {
+        println!("Nothing to do");
+    }
+    for step in &plan.steps {
+        println!("  {}", step.summary);
+    }
+}
+
+fn print_report(report: &SpecApplyReport) {
+    for result in &report.results {
+        match result.status {
+            SpecStepStatus::Applied => println!("  done     {}", result.step.summary),
+            SpecStepStatus::Skipped => println!("  skipped  {}", result.step.summary),
+            SpecStepStatus::Failed => println!(
+                "  FAILED   {}: {}",
+                result.step.summary,
+                result.error.as_deref().unwrap_or("")
+            ),
+        }
+    }
+    if report.results.is_empty() {
+        println!("Nothing to do");
+    }
+}
+
+// Standalone hosting: the host lives as long as this process, so stream its
+// output and stop it on Ctrl-C
+async fn host_foreground(state: &AppState, req: HostTunnelRequest, json: bool) -> i32 {
+    let tunnel_id = req.tunnel_id.clone().unwrap_or_default();
+    if let Err(e) = state.supervisor.host(req).await {
+        return print(json, CommandResponse::<()>::failure(&e), |_| {});
+    }
+    eprintln!("Hosting {} in the foreground, press Ctrl-C to stop", tunnel_id);
+
+    let mut next_seq = 0;
+    loop {
+        if let Some(output) = state.supervisor.output(&tunnel_id, Some(next_seq)) {
+            for line in &output.lines {
+                if json {
+                    println!("{}", serde_json::to_string(line).unwrap_or_default());
+                } else {
+                    println!("{}", line.line);
+                }
+            }
+            next_seq = output.next_seq;
+            if !output.running {
+                eprintln!("Host exited: {}", output.exit_status.unwrap_or_default());
+                return 1;
+            }
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                let grace = Duration::from_millis(devtunnel_gui_lib::DEFAULT_STOP_GRACE_MS);
+                let stopped = state.supervisor.stop(&tunnel_id, grace).await;
+                return print(json, respond(stopped), print_stop);
+            }
+            _ = tokio::time::sleep(Duration::from_millis(OUTPUT_POLL_MS)) => {}
+        }
+    }
+}
+
+async fn run(options: Options) -> anyhow::Result<i32> {
+    let json = options.json;
+    let target = Target::open(options.standalone)?;
+
+    let code = match (target, options.command) {
+        #[cfg(unix)]
+        (Target::App(mut client), command) => match command {
+            Command::List => print(json, client.call("list_tunnels", json!({}))?, print_tunnels),
+            Command::Host { tunnel_id, ports } => {
+                let req = host_request(tunnel_id, ports);
+                print(json, client.call::<CommandResponse<String>>("host_tunnel", json!({ "req": req }))?, |msg| {
+                    println!("{} (managed by the running app)", msg)
+                })
+            }
+            Command::Stop { tunnel_id, grace_ms } => print(
+                json,
+                client.call("stop_tunnel", json!({ "tunnelId": tunnel_id, "gracePeriodMs": grace_ms }))?,
+                print_stop,
+            ),
+            Command::Status => print(json, client.call("get_host_sessions", json!({}))?, print_sessions),
+            Command::Apply { path, dry_run } => {
+                // The app resolves relative paths against its own working directory
+                let path = std::fs::canonicalize(&path).unwrap_or_else(|_| Path::new(&path).to_path_buf());
+                let params = json!({ "path": path.to_string_lossy() });
+                if dry_run {
+                    print(json, client.call("plan_spec", params)?, print_plan)
+                } else {
+                    print(json, client.call("apply_spec", params)?, print_report)
+                }
+            }
+        },
+        (Target::Standalone(state), command) => match command {
+            Command::List => {
+                let tunnels = state.operations.run("list_tunnels", None, state.backend.list_tunnels(None)).await;
+                print(json, respond(tunnels), print_tunnels)
+            }
+            Command::Host { tunnel_id, ports } => {
+                host_foreground(&state, host_request(tunnel_id, ports), json).await
+            }
+            Command::Stop { tunnel_id, grace_ms } => {
+                let grace = Duration::from_millis(
+                    grace_ms.unwrap_or(devtunnel_gui_lib::DEFAULT_STOP_GRACE_MS),
+                );
+                print(json, respond(state.supervisor.stop(&tunnel_id, grace).await), print_stop)
+            }
+            Command::Status => print(json, CommandResponse::success(state.supervisor.sessions_report()), print_sessions),
+            Command::Apply { path, dry_run } => {
+                let plan = state
+                    .operations
+                    .run("plan_spec", None, async {
+                        let file = spec::load_spec(Path::new(&path))?;
+                        spec::plan_spec(state.backend.as_ref(), &file).await
+                    })
+                    .await;
+                match plan {
+                    Ok(plan) if dry_run => print(json, CommandResponse::success(plan), print_plan),
+                    Ok(plan) => {
+                        let apply = spec::apply_plan(state.backend.as_ref(), plan, |_| {});
+                        let report = match state.operations.run("apply_spec", None, async { Ok(apply.await) }).await {
+                            Ok(report) => report,
+                            Err(e) => return Ok(print(json, CommandResponse::<()>::failure(&e), |_| {})),
+                        };
+                        let code = if report.success { 0 } else { 1 };
+                        print(json, CommandResponse::success(report), print_report).max(code)
+                    }
+                    Err(e) => print(json, CommandResponse::<()>::failure(&e), |_| {}),
+                }
+            }
+        },
+    };
+
+    Ok(code)
+}
+
+fn host_request(tunnel_id: String, ports: Vec<u16>) -> HostTunnelRequest {
+    HostTunnelRequest {
+        tunnel_id: Some(tunnel_id),
+        ports,
+        protocol: None,
+        allow_anonymous: None,
+        expiration: None,
+        restart_policy: None,
+        upstream: None,
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match run(options).await {
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse_args(&args("--json host demo -p 3000 -p 8080")).unwrap();
+        assert!(options.json);
+        assert_eq!(
+            options.command,
+            Command::Host { tunnel_id: "demo".to_string(), ports: vec![3000, 8080] }
+        );
+
+        let options = parse_args(&args("apply tunnels.toml --dry-run --standalone")).unwrap();
+        assert!(options.standalone);
+        assert_eq!(
+            options.command,
+            Command::Apply { path: "tunnels.toml".to_string(), dry_run: true }
+        );
+
+        assert!(parse_args(&args("stop")).is_err());
+        assert!(parse_args(&args("host demo -p http")).is_err());
+        assert!(parse_args(&args("frobnicate")).is_err());
+        assert!(parse_args(&args("stop demo other")).is_err());
+        assert!(parse_args(&args("list demo")).is_err());
+    }
+}
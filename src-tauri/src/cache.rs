@@ -0,0 +1,253 @@
+// Read-through cache for CLI results shared by every window and the control
+// socket. Our own mutating commands invalidate exactly the tunnel they touched,
+// whether or not the CLI call succeeded (a failed update_port may already have
+// deleted the old port); changes made elsewhere (another machine, the bare
+// CLI) show up once the TTL runs out or the caller asks for force_refresh.
+use crate::types::*;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const LIST_TTL: Duration = Duration::from_secs(15);
+const TUNNEL_TTL: Duration = Duration::from_secs(30);
+const PORTS_TTL: Duration = Duration::from_secs(30);
+const ACCESS_TTL: Duration = Duration::from_secs(60);
+
+// "demo" and "demo.usw2" name the same tunnel; IDs may omit the cluster suffix
+// the CLI adds, but "demo.usw2" and "demo.euw" are different tunnels
+pub(crate) fn same_tunnel(a: &str, b: &str) -> bool {
+    match (a.split_once('.'), b.split_once('.')) {
+        (Some(_), Some(_)) | (None, None) => a == b,
+        (Some((name, _)), None) => name == b,
+        (None, Some((name, _))) => a == name,
+    }
+}
+
+pub struct TtlMap<T> {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, T)>>,
+    // Bumped (with `entries` locked) by every invalidation, so a fetch that
+    // started before one can tell its result may already be stale
+    generation: AtomicU64,
+}
+
+impl<T: Clone> TtlMap<T> {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    // Read before starting a fetch whose result goes to put_fetched()
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    pub fn get(&self, key: &str) -> Option<T> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|(stored, _)| stored.elapsed() < self.ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    pub fn put(&self, key: &str, value: T) {
+        self.entries.lock().unwrap().insert(key.to_string(), (Instant::now(), value));
+    }
+
+    // Store a fetched value unless the map was invalidated since `generation`
+    // was read; the fetch may have seen the state from before the change
+    pub fn put_fetched(&self, key: &str, value: T, generation: u64) {
+        let mut entries = self.entries.lock().unwrap();
+        if self.generation() == generation {
+            entries.insert(key.to_string(), (Instant::now(), value));
+        }
+    }
+
+    fn remove_tunnel(&self, tunnel_id: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        entries.retain(|key, _| !same_tunnel(key, tunnel_id));
+    }
+
+    fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        entries.clear();
+    }
+
+    // Cached value unless `force_refresh`; otherwise run `fetch` and store it.
+    // Failures are never cached.
+    pub async fn get_or_fetch(
+        &self,
+        key: &str,
+        force_refresh: bool,
+        fetch: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        if !force_refresh {
+            if let Some(value) = self.get(key) {
+                return Ok(value);
+            }
+        }
+        let generation = self.generation();
+        let value = fetch.await?;
+        self.put_fetched(key, value.clone(), generation);
+        Ok(value)
+    }
+}
+
+pub struct StateCache {
+    // Keyed by the list request (tag filter), not by tunnel
+    pub lists: TtlMap<Vec<TunnelListItem>>,
+    pub tunnels: TtlMap<Tunnel>,
+    pub ports: TtlMap<Vec<Port>>,
+    pub access: TtlMap<Vec<AccessEntry>>,
+}
+
+impl Default for StateCache {
+    fn default() -> Self {
+        Self {
+            lists: TtlMap::new(LIST_TTL),
+            tunnels: TtlMap::new(TUNNEL_TTL),
+            ports: TtlMap::new(PORTS_TTL),
+            access: TtlMap::new(ACCESS_TTL),
+        }
+    }
+}
+
+impl StateCache {
+    pub fn list_key(req: &Option<ListTunnelsRequest>) -> String {
+        match req {
+            Some(ListTunnelsRequest { tags: Some(tags), all_tags }) if !tags.is_empty() => {
+                let mut tags = tags.clone();
+                tags.sort();
+                format!("{}:{}", if all_tags.unwrap_or(false) { "all" } else { "any" }, tags.join(","))
+            }
+            _ => String::new(),
+        }
+    }
+
+    // A tunnel was created: only the lists can be missing it
+    pub fn tunnel_created(&self) {
+        self.lists.clear();
+    }
+
+    // Description, tags, ports or host state of one tunnel changed
+    pub fn tunnel_changed(&self, tunnel_id: &str) {
+        self.lists.clear();
+        self.tunnels.remove_tunnel(tunnel_id);
+        self.ports.remove_tunnel(tunnel_id);
+    }
+
+    // Access entries show up in `show` output too, but not in lists
+    pub fn access_changed(&self, tunnel_id: &str) {
+        self.tunnels.remove_tunnel(tunnel_id);
+        self.access.remove_tunnel(tunnel_id);
+    }
+
+    pub fn tunnel_deleted(&self, tunnel_id: &str) {
+        self.tunnel_changed(tunnel_id);
+        self.access.remove_tunnel(tunnel_id);
+    }
+
+    // After a spec step ran (or failed part-way)
+    pub fn spec_step_ran(&self, action: &SpecAction) {
+        match action {
+            SpecAction::CreateTunnel { .. } => self.tunnel_created(),
+            SpecAction::UpdateTunnel { req } => self.tunnel_changed(&req.tunnel_id),
+            SpecAction::CreatePort { req } => self.tunnel_changed(&req.tunnel_id),
+            SpecAction::UpdatePort { req } => self.tunnel_changed(&req.tunnel_id),
+            SpecAction::DeletePort { tunnel_id, .. } => self.tunnel_changed(tunnel_id),
+            SpecAction::CreateAccess { req } => self.access_changed(&req.tunnel_id),
+            SpecAction::DeleteAccess { req } => self.access_changed(&req.tunnel_id),
+            SpecAction::ResetAccess { tunnel_id } => self.access_changed(tunnel_id),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.lists.clear();
+        self.tunnels.clear();
+        self.ports.clear();
+        self.access.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(port_number: u16) -> Port {
+        Port {
+            port_number,
+            protocol: Protocol::Http,
+            description: None,
+            port_forwarding_uris: None,
+            inspect_uri: None,
+        }
+    }
+
+    #[test]
+    fn test_same_tunnel() {
+        assert!(same_tunnel("demo.usw2", "demo"));
+        assert!(same_tunnel("demo", "demo.usw2"));
+        assert!(same_tunnel("demo.usw2", "demo.usw2"));
+        assert!(!same_tunnel("demo.usw2", "demo.euw"));
+        assert!(!same_tunnel("demo2.usw2", "demo"));
+    }
+
+    #[tokio::test]
+    async fn test_invalidation_is_per_tunnel() {
+        let cache = StateCache::default();
+        cache.ports.put("demo.usw2", vec![port(3000)]);
+        cache.ports.put("other.usw2", vec![port(8080)]);
+
+        // A fetch is not run while the entry is fresh
+        let ports = cache.ports.get_or_fetch("demo.usw2", false, async { panic!("not fetched") }).await;
+        assert_eq!(ports.unwrap()[0].port_number, 3000);
+
+        // Commands may use the short ID
+        cache.tunnel_changed("demo");
+        assert!(cache.ports.get("demo.usw2").is_none());
+        assert!(cache.ports.get("other.usw2").is_some());
+
+        let refreshed = cache.ports.get_or_fetch("other.usw2", true, async { Ok(vec![port(9090)]) }).await;
+        assert_eq!(refreshed.unwrap()[0].port_number, 9090);
+        assert!(cache.ports.get_or_fetch("x", false, async { Err(anyhow::anyhow!("boom")) }).await.is_err());
+        assert!(cache.ports.get("x").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_overlapping_invalidation_is_not_stored() {
+        let cache = std::sync::Arc::new(StateCache::default());
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+
+        // A slow read (say the poller) sees the ports from before a create_port
+        let reader = cache.clone();
+        let read = tokio::spawn(async move {
+            reader
+                .ports
+                .get_or_fetch("demo.usw2", false, async {
+                    let _ = started_tx.send(());
+                    let _ = release_rx.await;
+                    Ok(vec![port(3000)])
+                })
+                .await
+        });
+        started_rx.await.unwrap();
+        cache.tunnel_changed("demo");
+        release_tx.send(()).unwrap();
+
+        // The caller still gets its result, but the next read fetches again
+        assert_eq!(read.await.unwrap().unwrap().len(), 1);
+        assert!(cache.ports.get("demo.usw2").is_none());
+        let fresh = cache.ports.get_or_fetch("demo.usw2", false, async { Ok(vec![port(3000), port(8080)]) }).await;
+        assert_eq!(fresh.unwrap().len(), 2);
+        assert_eq!(cache.ports.get("demo.usw2").unwrap().len(), 2);
+    }
+}
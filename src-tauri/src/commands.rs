@@ -1,11 +1,70 @@
+use crate::access;
+use crate::backend::{self, DevTunnelBackend};
+use crate::cache::StateCache;
+use crate::connect::ConnectManager;
 use crate::devtunnel::DevTunnelClient;
+use crate::discovery;
+use crate::error::DevTunnelError;
+use crate::events::EventSink;
+use crate::executor::{CliExecutor, LIMITS_FILE_NAME};
+use crate::health;
+use crate::logfiles::{self, LogFiles, LOG_FILES_FILE_NAME};
+use crate::logging::LogStore;
+use crate::monitor::{HealthMonitor, HISTORY_DIR_NAME, MONITOR_FILE_NAME};
+use crate::operations::{Operations, TIMEOUTS_FILE_NAME};
+use crate::poller::{self, StatePoller, POLLER_FILE_NAME};
+use crate::redact::{Redactor, REDACTION_FILE_NAME};
+use crate::sessions::{SessionStore, SESSION_FILE_NAME};
+use crate::token;
+use crate::spec;
+use crate::supervisor::{HostSupervisor, DEFAULT_STOP_GRACE_MS};
 use crate::types::*;
-use std::sync::Mutex;
-use tauri::Emitter;
+use crate::upstream::{self, UpstreamWatch, UPSTREAM_CHECK_INTERVAL_MS};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+// Log lines for one command or background task, tagged with its name and the
+// tunnel it acts on. They are kept in AppState::logs for get_logs and sent to
+// the Logs view as "log-entry" events.
+pub(crate) struct Logger<'a, R: tauri::Runtime> {
+    app: &'a tauri::AppHandle<R>,
+    source: &'static str,
+    tunnel_id: Option<String>,
+}
+
+impl<'a, R: tauri::Runtime> Logger<'a, R> {
+    pub(crate) fn new(app: &'a tauri::AppHandle<R>, source: &'static str, tunnel_id: Option<String>) -> Self {
+        Self { app, source, tunnel_id }
+    }
+
+    // Same source, about one of several tunnels it handles
+    pub(crate) fn tunnel(&self, tunnel_id: &str) -> Self {
+        Self::new(self.app, self.source, Some(tunnel_id.to_string()))
+    }
 
-// Helper function to emit log events
-fn emit_log(app: &tauri::AppHandle, message: &str) {
-    let _ = app.emit("devtunnel-log", message);
+    pub(crate) fn info(&self, message: &str) {
+        self.log(LogLevel::Info, message);
+    }
+
+    pub(crate) fn warn(&self, message: &str) {
+        self.log(LogLevel::Warn, message);
+    }
+
+    pub(crate) fn error(&self, message: &str) {
+        self.log(LogLevel::Error, message);
+    }
+
+    pub(crate) fn log(&self, level: LogLevel, message: &str) {
+        let Some(state) = self.app.try_state::<AppState>() else {
+            return;
+        };
+        let message = state.redactor.redact(message);
+        let entry = state.logs.push(level, Some(self.source), self.tunnel_id.as_deref(), &message);
+        state.log_files.record_entry(&entry);
+        let _ = self.app.emit("log-entry", &entry);
+    }
 }
 
 // Helper function to get devtunnel binary path
@@ -19,16 +78,193 @@ fn get_devtunnel_path() -> String {
         .unwrap_or_else(|_| "devtunnel".to_string())
 }
 
-#[allow(dead_code)]
 pub struct AppState {
-    pub client: Mutex<DevTunnelClient>,
+    pub backend: Arc<dyn DevTunnelBackend>,
+    pub supervisor: HostSupervisor,
+    pub connections: ConnectManager,
+    pub operations: Operations,
+    pub cache: StateCache,
+    pub executor: Arc<CliExecutor>,
+    pub poller: StatePoller,
+    pub monitor: HealthMonitor,
+    pub upstream: UpstreamWatch,
+    pub logs: LogStore,
+    pub log_files: Arc<LogFiles>,
+    pub redactor: Arc<Redactor>,
 }
 
-#[allow(dead_code)]
 impl AppState {
-    pub fn new() -> Self {
+    pub fn new(events: Arc<dyn EventSink>, data_dir: &Path, log_dir: &Path) -> Self {
+        let executor = Arc::new(CliExecutor::load(Some(data_dir.join(LIMITS_FILE_NAME))));
+        let backend = Arc::new(DevTunnelClient::with_executor(get_devtunnel_path(), executor.clone()));
+        Self::build(backend, executor, events, data_dir, log_dir)
+    }
+
+    // Tests pass a fake::FakeBackend here instead of the real CLI. The limits
+    // are still loaded and saved but only DevTunnelClient enforces them. Log
+    // files go to a "logs" directory inside `data_dir`.
+    pub fn with_backend(backend: Arc<dyn DevTunnelBackend>, events: Arc<dyn EventSink>, data_dir: &Path) -> Self {
+        let executor = Arc::new(CliExecutor::load(Some(data_dir.join(LIMITS_FILE_NAME))));
+        Self::build(backend, executor, events, data_dir, &data_dir.join("logs"))
+    }
+
+    fn build(
+        backend: Arc<dyn DevTunnelBackend>,
+        executor: Arc<CliExecutor>,
+        events: Arc<dyn EventSink>,
+        data_dir: &Path,
+        log_dir: &Path,
+    ) -> Self {
+        let sessions = Arc::new(SessionStore::load(data_dir.join(SESSION_FILE_NAME)));
+        let log_files = Arc::new(LogFiles::load(log_dir.to_path_buf(), Some(data_dir.join(LOG_FILES_FILE_NAME))));
+        let redactor = Arc::new(Redactor::load(Some(data_dir.join(REDACTION_FILE_NAME))));
+
         Self {
-            client: Mutex::new(DevTunnelClient::new(get_devtunnel_path())),
+            backend: backend.clone(),
+            supervisor: HostSupervisor::new(backend.clone(), events.clone(), sessions, log_files.clone(), redactor.clone()),
+            connections: ConnectManager::new(backend, events, redactor.clone()),
+            operations: Operations::load(Some(data_dir.join(TIMEOUTS_FILE_NAME))),
+            cache: StateCache::default(),
+            executor,
+            poller: StatePoller::load(Some(data_dir.join(POLLER_FILE_NAME))),
+            monitor: HealthMonitor::load(Some(data_dir.join(MONITOR_FILE_NAME)), Some(data_dir.join(HISTORY_DIR_NAME))),
+            upstream: UpstreamWatch::default(),
+            logs: LogStore::default(),
+            log_files,
+            redactor,
+        }
+    }
+}
+
+// Startup task: adopt host processes left running by a previous app session and,
+// if enabled, re-host the ones that were running when the app was last closed
+pub async fn reconcile_host_sessions<R: tauri::Runtime>(app: tauri::AppHandle<R>) {
+    let log = Logger::new(&app, "startup", None);
+    let state = app.state::<AppState>();
+
+    for tunnel_id in state.supervisor.reconcile_sessions() {
+        log.tunnel(&tunnel_id).info(&format!("Adopted running host process for tunnel: {}", tunnel_id));
+    }
+
+    if state.supervisor.restore_on_startup() {
+        for (tunnel_id, result) in state.supervisor.restore_sessions(None).await {
+            match result {
+                Ok(pid) => log.tunnel(&tunnel_id).info(&format!("Restored tunnel host: {} (pid {})", tunnel_id, pid)),
+                Err(e) => log.tunnel(&tunnel_id).error(&format!("Failed to restore tunnel host {}: {}", tunnel_id, e)),
+            }
+        }
+    }
+}
+
+// Background task: reconcile tunnel state on the poller's interval and emit
+// tunnel-added / tunnel-removed / tunnel-updated / port-changed
+pub async fn poll_tunnel_state<R: tauri::Runtime>(app: tauri::AppHandle<R>) {
+    let log = Logger::new(&app, "poller", None);
+    let state = app.state::<AppState>();
+
+    loop {
+        state.poller.wait_next().await;
+
+        let pass = poller::snapshot(state.backend.clone(), &state.cache);
+        match state.operations.run("poll_state", None, pass).await {
+            Ok(snapshot) => {
+                state.poller.record_error(None);
+                for (event, payload) in state.poller.update(snapshot) {
+                    let _ = app.emit(event, payload);
+                }
+            },
+            Err(e) => {
+                if state.poller.record_error(Some(e.to_string())) {
+                    log.error(&format!("Background refresh failed: {}", e));
+                }
+            },
+        }
+    }
+}
+
+// Background task: run the monitor's health checks as they come due and emit
+// port-health-changed when a port goes up or down
+pub async fn monitor_ports<R: tauri::Runtime>(app: tauri::AppHandle<R>) {
+    let state = app.state::<AppState>();
+
+    loop {
+        state.monitor.wait_next().await;
+
+        // Checks run side by side so a slow port doesn't delay the others
+        for monitor in state.monitor.take_due() {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let state = app.state::<AppState>();
+                let result = health::check(&monitor.url, &monitor.check).await.unwrap_or_else(|e| PingResult {
+                    success: false,
+                    status_code: None,
+                    response_time_ms: 0,
+                    error: Some(e.to_string()),
+                    timings: PingTimings::default(),
+                    remote_address: None,
+                    body_matched: None,
+                });
+
+                let (event, written) = state.monitor.record(&monitor, &result);
+                let log = Logger::new(&app, "monitor", Some(monitor.tunnel_id.clone()));
+                if let Err(e) = written {
+                    log.error(&format!("{:#}", e));
+                }
+                if let Some(event) = event {
+                    let port = format!("Port {} of {}", monitor.port_number, monitor.tunnel_id);
+                    match &event.sample.error {
+                        None => log.info(&format!("{} is up again", port)),
+                        Some(error) => log.warn(&format!("{} is down: {}", port, error)),
+                    }
+                    let _ = app.emit("port-health-changed", &event);
+                }
+            });
+        }
+    }
+}
+
+// Background task: keep checking the local services behind hosted tunnels
+// and emit upstream-changed when a port becomes ready or stops being ready
+pub async fn watch_upstreams<R: tauri::Runtime>(app: tauri::AppHandle<R>) {
+    let state = app.state::<AppState>();
+
+    loop {
+        tokio::time::sleep(Duration::from_millis(UPSTREAM_CHECK_INTERVAL_MS)).await;
+
+        let hosted: Vec<HostSession> = state
+            .supervisor
+            .sessions_report()
+            .sessions
+            .into_iter()
+            .filter(|info| info.state != HostSessionState::Stopped)
+            .map(|info| info.session)
+            .collect();
+        state.upstream.retain(&hosted.iter().map(|s| s.tunnel_id.clone()).collect::<Vec<_>>());
+
+        for session in hosted {
+            let log = Logger::new(&app, "upstream", Some(session.tunnel_id.clone()));
+            let ports = match host_ports(&state, &session.tunnel_id, &session.request).await {
+                Ok(ports) => ports,
+                Err(e) => {
+                    log.warn(&format!("Could not list ports of {} for the upstream check: {}", session.tunnel_id, e));
+                    continue;
+                },
+            };
+            let probe = session.request.upstream.as_ref().and_then(|u| u.probe.as_ref());
+            let statuses = upstream::check_ports(&ports, probe).await;
+
+            if let Some(report) = state.upstream.update(&session.tunnel_id, statuses) {
+                if report.ready {
+                    log.info(&format!("Local service ready on all ports of {}", session.tunnel_id));
+                } else {
+                    log.warn(&format!(
+                        "Local service not ready behind {}: {}",
+                        session.tunnel_id,
+                        upstream::describe_not_ready(&report.ports)
+                    ));
+                }
+                let _ = app.emit("upstream-changed", &report);
+            }
         }
     }
 }
@@ -36,60 +272,69 @@ impl AppState {
 // Authentication Commands
 
 #[tauri::command]
-pub fn login_devtunnel(
-    app: tauri::AppHandle,
+pub async fn login_devtunnel<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
     provider: String,
     use_device_code: bool,
+    op_id: Option<String>,
 ) -> CommandResponse<String> {
-    emit_log(&app, &format!("Attempting login with provider: {}", provider));
+    let log = Logger::new(&app, "login_devtunnel", None);
+    log.info(&format!("Attempting login with provider: {}", provider));
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let state = app.state::<AppState>();
 
-    match client.login(&provider, use_device_code) {
+    match state.operations.run("login", op_id, state.backend.login(&provider, use_device_code)).await {
         Ok(result) => {
-            emit_log(&app, "Login successful");
+            // Another account sees other tunnels
+            state.cache.clear();
+            log.info("Login successful");
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Login failed: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Login failed: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn logout_devtunnel(app: tauri::AppHandle) -> CommandResponse<String> {
-    emit_log(&app, "Logging out...");
+pub async fn logout_devtunnel<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<String> {
+    let log = Logger::new(&app, "logout_devtunnel", None);
+    log.info("Logging out...");
+
+    let state = app.state::<AppState>();
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let result = state.operations.run("logout", None, state.backend.logout()).await;
+    state.cache.clear();
 
-    match client.logout() {
+    match result {
         Ok(result) => {
-            emit_log(&app, "Logout successful");
+            log.info("Logout successful");
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Logout failed: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Logout failed: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn get_user_info(app: tauri::AppHandle) -> CommandResponse<UserInfo> {
-    emit_log(&app, "Checking user authentication status...");
+pub async fn get_user_info<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<UserInfo> {
+    let log = Logger::new(&app, "get_user_info", None);
+    log.info("Checking user authentication status...");
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let state = app.state::<AppState>();
 
-    match client.get_user_info() {
+    match state.operations.run("get_user_info", None, state.backend.get_user_info()).await {
         Ok(info) => {
             let username = info.user_name.as_deref().unwrap_or(&info.user_id);
-            emit_log(&app, &format!("User authenticated: {}", username));
+            log.info(&format!("User authenticated: {}", username));
             CommandResponse::success(info)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to get user info: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to get user info: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
@@ -97,233 +342,567 @@ pub fn get_user_info(app: tauri::AppHandle) -> CommandResponse<UserInfo> {
 // Tunnel Management Commands
 
 #[tauri::command]
-pub fn create_tunnel(app: tauri::AppHandle, req: CreateTunnelRequest) -> CommandResponse<String> {
+pub async fn create_tunnel<R: tauri::Runtime>(app: tauri::AppHandle<R>, req: CreateTunnelRequest) -> CommandResponse<String> {
+    let log = Logger::new(&app, "create_tunnel", req.tunnel_id.clone());
     let tunnel_id = req.tunnel_id.as_deref().unwrap_or("auto-generated");
-    emit_log(&app, &format!("Creating tunnel: {}", tunnel_id));
+    log.info(&format!("Creating tunnel: {}", tunnel_id));
+
+    let state = app.state::<AppState>();
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let result = state.operations.run("create_tunnel", None, state.backend.create_tunnel(req.clone())).await;
+    state.cache.tunnel_created();
 
-    match client.create_tunnel(req.clone()) {
+    match result {
         Ok(result) => {
-            emit_log(&app, "Tunnel created successfully");
+            log.info("Tunnel created successfully");
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to create tunnel: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to create tunnel: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
-#[tauri::command]
-pub fn list_tunnels(app: tauri::AppHandle, req: Option<ListTunnelsRequest>) -> CommandResponse<Vec<TunnelListItem>> {
-    emit_log(&app, "Loading tunnel list...");
-
-    let client = DevTunnelClient::new(get_devtunnel_path());
+// Light list through the cache, keyed by the tag filter
+async fn cached_light_list(state: &AppState, req: Option<ListTunnelsRequest>, force_refresh: bool) -> anyhow::Result<Vec<TunnelListItem>> {
+    let key = StateCache::list_key(&req);
+    state.cache.lists.get_or_fetch(&key, force_refresh, state.backend.list_tunnels_light(req)).await
+}
 
-    match client.list_tunnels(req) {
+#[tauri::command]
+pub async fn list_tunnels<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    req: Option<ListTunnelsRequest>,
+    op_id: Option<String>,
+    force_refresh: Option<bool>,
+) -> CommandResponse<Vec<TunnelListItem>> {
+    let log = Logger::new(&app, "list_tunnels", None);
+    log.info("Loading tunnel list...");
+
+    let state = app.state::<AppState>();
+    let force_refresh = force_refresh.unwrap_or(false);
+
+    // Light list plus cached `show` per tunnel instead of N fresh `show` calls
+    let list = async {
+        let tunnels = cached_light_list(&state, req, force_refresh).await?;
+        let tunnel_ids: Vec<String> = tunnels.iter().map(|t| t.tunnel_id.clone()).collect();
+        Ok(backend::enrich_tunnel_details(state.backend.clone(), &state.cache, tunnels, &tunnel_ids, force_refresh).await)
+    };
+
+    match state.operations.run("list_tunnels", op_id, list).await {
         Ok(tunnels) => {
-            emit_log(&app, &format!("Loaded {} tunnel(s)", tunnels.len()));
+            log.info(&format!("Loaded {} tunnel(s)", tunnels.len()));
             CommandResponse::success(tunnels)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to list tunnels: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to list tunnels: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 // 경량 목록: ports 없이 빠르게 반환
 #[tauri::command]
-pub fn list_tunnels_light(app: tauri::AppHandle, req: Option<ListTunnelsRequest>) -> CommandResponse<Vec<TunnelListItem>> {
-    emit_log(&app, "Loading tunnel list (light mode)...");
-
-    let client = DevTunnelClient::new(get_devtunnel_path());
-
-    match client.list_tunnels_light(req) {
+pub async fn list_tunnels_light<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    req: Option<ListTunnelsRequest>,
+    op_id: Option<String>,
+    force_refresh: Option<bool>,
+) -> CommandResponse<Vec<TunnelListItem>> {
+    let log = Logger::new(&app, "list_tunnels_light", None);
+    log.info("Loading tunnel list (light mode)...");
+
+    let state = app.state::<AppState>();
+    let list = cached_light_list(&state, req, force_refresh.unwrap_or(false));
+
+    match state.operations.run("list_tunnels_light", op_id, list).await {
         Ok(tunnels) => {
-            emit_log(&app, &format!("Loaded {} tunnel(s) in light mode", tunnels.len()));
+            log.info(&format!("Loaded {} tunnel(s) in light mode", tunnels.len()));
             CommandResponse::success(tunnels)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to list tunnels: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to list tunnels: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 // 병렬 처리로 상세 정보 추가
+// The light list the UI just loaded is reused from the cache; force_refresh
+// only re-fetches the per-tunnel details.
 #[tauri::command]
-pub async fn enrich_tunnel_details(app: tauri::AppHandle, tunnel_ids: Vec<String>) -> CommandResponse<Vec<TunnelListItem>> {
-    emit_log(&app, &format!("Enriching details for {} tunnel(s)...", tunnel_ids.len()));
-
-    let client = DevTunnelClient::new(get_devtunnel_path());
-
-    match client.enrich_tunnel_details(tunnel_ids).await {
+pub async fn enrich_tunnel_details<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    tunnel_ids: Vec<String>,
+    op_id: Option<String>,
+    force_refresh: Option<bool>,
+) -> CommandResponse<Vec<TunnelListItem>> {
+    let log = Logger::new(&app, "enrich_tunnel_details", None);
+    log.info(&format!("Enriching details for {} tunnel(s)...", tunnel_ids.len()));
+
+    let state = app.state::<AppState>();
+    let enrich = async {
+        let tunnels = cached_light_list(&state, None, false).await?;
+        Ok(backend::enrich_tunnel_details(
+            state.backend.clone(),
+            &state.cache,
+            tunnels,
+            &tunnel_ids,
+            force_refresh.unwrap_or(false),
+        )
+        .await)
+    };
+
+    match state.operations.run("enrich_tunnel_details", op_id, enrich).await {
         Ok(tunnels) => {
-            emit_log(&app, "Tunnel details enriched successfully");
+            log.info("Tunnel details enriched successfully");
             CommandResponse::success(tunnels)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to enrich tunnel details: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to enrich tunnel details: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn show_tunnel(app: tauri::AppHandle, tunnel_id: Option<String>) -> CommandResponse<String> {
+pub async fn show_tunnel<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    tunnel_id: Option<String>,
+    op_id: Option<String>,
+    force_refresh: Option<bool>,
+) -> CommandResponse<Tunnel> {
+    let log = Logger::new(&app, "show_tunnel", tunnel_id.clone());
     let id_str = tunnel_id.as_deref().unwrap_or("current").to_string();
-    emit_log(&app, &format!("Fetching details for tunnel: {}", id_str));
-
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    log.info(&format!("Fetching details for tunnel: {}", id_str));
+
+    let state = app.state::<AppState>();
+    // "current" depends on the CLI's context, so only explicit IDs are cached
+    let show = async {
+        match tunnel_id.clone() {
+            Some(id) => {
+                let fetch = state.backend.show_tunnel(Some(id.clone()));
+                state.cache.tunnels.get_or_fetch(&id, force_refresh.unwrap_or(false), fetch).await
+            }
+            None => state.backend.show_tunnel(None).await,
+        }
+    };
 
-    match client.show_tunnel(tunnel_id) {
+    match state.operations.run("show_tunnel", op_id, show).await {
         Ok(result) => {
-            emit_log(&app, &format!("Retrieved tunnel details for: {}", id_str));
+            log.info(&format!("Retrieved tunnel details for: {}", id_str));
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to show tunnel: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to show tunnel: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn update_tunnel(app: tauri::AppHandle, req: UpdateTunnelRequest) -> CommandResponse<String> {
-    emit_log(&app, &format!("Updating tunnel: {}", req.tunnel_id));
+pub async fn update_tunnel<R: tauri::Runtime>(app: tauri::AppHandle<R>, req: UpdateTunnelRequest) -> CommandResponse<String> {
+    let log = Logger::new(&app, "update_tunnel", Some(req.tunnel_id.clone()));
+    log.info(&format!("Updating tunnel: {}", req.tunnel_id));
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let state = app.state::<AppState>();
 
-    match client.update_tunnel(req.clone()) {
+    let result = state.operations.run("update_tunnel", None, state.backend.update_tunnel(req.clone())).await;
+    state.cache.tunnel_changed(&req.tunnel_id);
+
+    match result {
         Ok(result) => {
-            emit_log(&app, &format!("Tunnel updated: {}", req.tunnel_id));
+            log.info(&format!("Tunnel updated: {}", req.tunnel_id));
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to update tunnel: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to update tunnel: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn delete_tunnel(app: tauri::AppHandle, tunnel_id: String) -> CommandResponse<String> {
-    emit_log(&app, &format!("Deleting tunnel: {}", tunnel_id));
+pub async fn delete_tunnel<R: tauri::Runtime>(app: tauri::AppHandle<R>, tunnel_id: String) -> CommandResponse<String> {
+    let log = Logger::new(&app, "delete_tunnel", Some(tunnel_id.clone()));
+    log.info(&format!("Deleting tunnel: {}", tunnel_id));
+
+    let state = app.state::<AppState>();
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let result = state.operations.run("delete_tunnel", None, state.backend.delete_tunnel(tunnel_id.clone())).await;
+    state.cache.tunnel_deleted(&tunnel_id);
 
-    match client.delete_tunnel(tunnel_id.clone()) {
+    match result {
         Ok(result) => {
-            emit_log(&app, &format!("Tunnel deleted: {}", tunnel_id));
+            log.info(&format!("Tunnel deleted: {}", tunnel_id));
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to delete tunnel: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to delete tunnel: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn delete_all_tunnels(app: tauri::AppHandle) -> CommandResponse<String> {
-    emit_log(&app, "Deleting all tunnels...");
+pub async fn delete_all_tunnels<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<String> {
+    let log = Logger::new(&app, "delete_all_tunnels", None);
+    log.info("Deleting all tunnels...");
+
+    let state = app.state::<AppState>();
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let result = state.operations.run("delete_all_tunnels", None, state.backend.delete_all_tunnels()).await;
+    state.cache.clear();
 
-    match client.delete_all_tunnels() {
+    match result {
         Ok(result) => {
-            emit_log(&app, "All tunnels deleted");
+            log.info("All tunnels deleted");
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to delete all tunnels: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to delete all tunnels: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+// Ports a host request forwards: the listed ones, or else all of the tunnel's ports
+async fn host_ports(state: &AppState, tunnel_id: &str, req: &HostTunnelRequest) -> anyhow::Result<Vec<u16>> {
+    if !req.ports.is_empty() {
+        return Ok(req.ports.clone());
+    }
+    let list = state.cache.ports.get_or_fetch(tunnel_id, false, state.backend.list_ports(tunnel_id.to_string()));
+    let ports = state.operations.run("list_ports", None, list).await?;
+    Ok(ports.iter().map(|p| p.port_number).collect())
+}
+
+// Check (or wait for) the local services behind a host request's ports.
+// Only fails when the request waits and they don't become ready in time.
+async fn check_upstream_before_host<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    log: &Logger<'_, R>,
+    req: &HostTunnelRequest,
+) -> anyhow::Result<()> {
+    let state = app.state::<AppState>();
+    let Some(tunnel_id) = req.tunnel_id.as_deref() else {
+        return Ok(());
+    };
+    let ports = match host_ports(&state, tunnel_id, req).await {
+        Ok(ports) => ports,
+        Err(e) => {
+            log.warn(&format!("Could not list ports of {}, skipping the upstream check: {}", tunnel_id, e));
+            return Ok(());
         },
+    };
+
+    let options = req.upstream.clone().unwrap_or_default();
+    let statuses = match options.wait_ms {
+        Some(wait_ms) => {
+            log.info(&format!("Waiting up to {}ms for the local service behind {}", wait_ms, tunnel_id));
+            upstream::wait_ready(&ports, options.probe.as_ref(), Duration::from_millis(wait_ms)).await
+        },
+        None => upstream::check_ports(&ports, options.probe.as_ref()).await,
+    };
+    let not_ready = upstream::describe_not_ready(&statuses);
+    if let Some(report) = state.upstream.update(tunnel_id, statuses) {
+        let _ = app.emit("upstream-changed", &report);
     }
+
+    if not_ready.is_empty() {
+        return Ok(());
+    }
+    if let Some(wait_ms) = options.wait_ms {
+        return Err(DevTunnelError::new(
+            ErrorCode::Timeout,
+            format!("Local service not ready after {}ms on port(s): {}", wait_ms, not_ready),
+        )
+        .into());
+    }
+    log.warn(&format!("Hosting {} although the local service is not ready on port(s): {}", tunnel_id, not_ready));
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn host_tunnel(app: tauri::AppHandle, req: HostTunnelRequest) -> CommandResponse<String> {
+pub async fn host_tunnel<R: tauri::Runtime>(app: tauri::AppHandle<R>, req: HostTunnelRequest) -> CommandResponse<String> {
+    let log = Logger::new(&app, "host_tunnel", req.tunnel_id.clone());
     let tunnel_id = req.tunnel_id.as_deref().unwrap_or("unknown");
-    emit_log(&app, &format!("Starting tunnel host: {}", tunnel_id));
+    log.info(&format!("Starting tunnel host: {}", tunnel_id));
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let state = app.state::<AppState>();
 
-    match client.host_tunnel(req.clone()).await {
-        Ok(_) => {
-            emit_log(&app, &format!("Tunnel host started: {}", tunnel_id));
+    if let Err(e) = check_upstream_before_host(&app, &log, &req).await {
+        log.error(&format!("Failed to host tunnel: {}", e));
+        return CommandResponse::failure(&e);
+    }
+
+    let result = state.supervisor.host(req.clone()).await;
+    state.cache.tunnel_changed(tunnel_id);
+
+    match result {
+        Ok(pid) => {
+            log.info(&format!("Tunnel host started: {} (pid {})", tunnel_id, pid));
             CommandResponse::success("Tunnel hosting started".to_string())
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to host tunnel: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to host tunnel: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn stop_tunnel(app: tauri::AppHandle, tunnel_id: String) -> CommandResponse<String> {
-    emit_log(&app, &format!("Stopping tunnel: {}", tunnel_id));
+pub async fn stop_tunnel<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    tunnel_id: String,
+    grace_period_ms: Option<u64>,
+) -> CommandResponse<StopResult> {
+    let log = Logger::new(&app, "stop_tunnel", Some(tunnel_id.clone()));
+    log.info(&format!("Stopping tunnel: {}", tunnel_id));
+
+    let state = app.state::<AppState>();
+    let grace = Duration::from_millis(grace_period_ms.unwrap_or(DEFAULT_STOP_GRACE_MS));
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let result = state.supervisor.stop(&tunnel_id, grace).await;
+    state.cache.tunnel_changed(&tunnel_id);
 
-    match client.stop_tunnel(tunnel_id.clone()) {
+    match result {
         Ok(result) => {
-            emit_log(&app, &format!("Tunnel stopped: {}", tunnel_id));
+            let how = match result.method {
+                StopMethod::Graceful => "terminated gracefully",
+                StopMethod::Forced => "killed after grace period",
+                StopMethod::AlreadyExited => "already exited",
+                StopMethod::NotRunning => "no tracked host process",
+            };
+            log.info(&format!("Tunnel stopped: {} ({})", tunnel_id, how));
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to stop tunnel: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to stop tunnel: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub async fn restart_tunnel(app: tauri::AppHandle, req: HostTunnelRequest) -> CommandResponse<String> {
+pub async fn restart_tunnel<R: tauri::Runtime>(app: tauri::AppHandle<R>, req: HostTunnelRequest) -> CommandResponse<String> {
+    let log = Logger::new(&app, "restart_tunnel", req.tunnel_id.clone());
     let tunnel_id = req.tunnel_id.clone().unwrap_or_else(|| "unknown".to_string());
-    emit_log(&app, &format!("Restarting tunnel: {}", tunnel_id));
+    log.info(&format!("Restarting tunnel: {}", tunnel_id));
+
+    let state = app.state::<AppState>();
+
+    if let Err(e) = check_upstream_before_host(&app, &log, &req).await {
+        log.error(&format!("Failed to restart tunnel: {}", e));
+        return CommandResponse::failure(&e);
+    }
+
+    let result = state.supervisor.restart(req).await;
+    state.cache.tunnel_changed(&tunnel_id);
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    match result {
+        Ok(_) => {
+            log.info(&format!("Tunnel restarted: {}", tunnel_id));
+            CommandResponse::success(format!("Tunnel {} restarted successfully", tunnel_id))
+        },
+        Err(e) => {
+            log.error(&format!("Failed to restart tunnel: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+// Check the local services behind `ports` without hosting anything
+#[tauri::command]
+pub async fn check_upstream(ports: Vec<u16>, probe: Option<HealthCheckOptions>) -> CommandResponse<Vec<UpstreamStatus>> {
+    CommandResponse::success(upstream::check_ports(&ports, probe.as_ref()).await)
+}
+
+// Latest upstream check of every hosted tunnel
+#[tauri::command]
+pub fn get_upstream_status<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<Vec<UpstreamReport>> {
+    let state = app.state::<AppState>();
+    CommandResponse::success(state.upstream.reports())
+}
 
-    match client.restart_tunnel(req).await {
+#[tauri::command]
+// Only hosts the supervisor tracks (including ones adopted at startup) are
+// known; a host started from the bare CLI has no start time here
+pub fn get_tunnel_start_time<R: tauri::Runtime>(app: tauri::AppHandle<R>, tunnel_id: String) -> CommandResponse<String> {
+    let state = app.state::<AppState>();
+
+    match state.supervisor.start_time(&tunnel_id) {
+        Some(start_time) => CommandResponse::success(start_time),
+        None => CommandResponse::coded(ErrorCode::NotFound, format!("No tracked host process for tunnel {}", tunnel_id)),
+    }
+}
+
+#[tauri::command]
+pub fn get_host_output<R: tauri::Runtime>(app: tauri::AppHandle<R>, tunnel_id: String, since: Option<u64>) -> CommandResponse<HostOutput> {
+    let state = app.state::<AppState>();
+
+    match state.supervisor.output(&tunnel_id, since) {
+        Some(output) => CommandResponse::success(output),
+        None => CommandResponse::coded(ErrorCode::NotFound, format!("No host process has been started for tunnel {}", tunnel_id)),
+    }
+}
+
+// HTTP health check of a forwarded port; see health.rs. Without `check` any
+// response counts as reachable. An unhealthy port is still a successful
+// command, with the reason in the result's `error`.
+#[tauri::command]
+pub async fn ping_port<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    url: String,
+    check: Option<HealthCheckOptions>,
+) -> CommandResponse<PingResult> {
+    let log = Logger::new(&app, "ping_port", None);
+    log.info(&format!("Pinging port: {}", url));
+
+    let state = app.state::<AppState>();
+    let check = check.unwrap_or_default();
+
+    match state.operations.run("ping_port", None, health::check(&url, &check)).await {
         Ok(result) => {
-            emit_log(&app, &format!("Tunnel restarted: {}", tunnel_id));
+            match &result.error {
+                None => log.info(&format!("Ping successful: {} ({}ms)", url, result.response_time_ms)),
+                Some(e) => log.warn(&format!("Ping failed: {}: {}", url, e)),
+            }
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to restart tunnel: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Ping failed: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn get_tunnel_start_time(tunnel_id: String) -> CommandResponse<String> {
-    let client = DevTunnelClient::new(get_devtunnel_path());
+pub fn get_host_sessions<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<HostSessionsReport> {
+    let state = app.state::<AppState>();
+    CommandResponse::success(state.supervisor.sessions_report())
+}
+
+#[tauri::command]
+pub async fn restore_host_sessions<R: tauri::Runtime>(app: tauri::AppHandle<R>, tunnel_ids: Option<Vec<String>>) -> CommandResponse<Vec<String>> {
+    let log = Logger::new(&app, "restore_host_sessions", None);
+    log.info("Restoring hosted tunnels from the previous session...");
+
+    let state = app.state::<AppState>();
+
+    let mut restored = Vec::new();
+    let mut failures = Vec::new();
+
+    for (tunnel_id, result) in state.supervisor.restore_sessions(tunnel_ids).await {
+        state.cache.tunnel_changed(&tunnel_id);
+        match result {
+            Ok(pid) => {
+                log.tunnel(&tunnel_id).info(&format!("Restored tunnel host: {} (pid {})", tunnel_id, pid));
+                restored.push(tunnel_id);
+            },
+            Err(e) => {
+                log.tunnel(&tunnel_id).error(&format!("Failed to restore tunnel host {}: {}", tunnel_id, e));
+                failures.push(format!("{}: {}", tunnel_id, e));
+            },
+        }
+    }
+
+    if restored.is_empty() && !failures.is_empty() {
+        CommandResponse::error(failures.join("\n"))
+    } else {
+        CommandResponse::success(restored)
+    }
+}
+
+#[tauri::command]
+pub fn forget_host_session<R: tauri::Runtime>(app: tauri::AppHandle<R>, tunnel_id: String) -> CommandResponse<String> {
+    let state = app.state::<AppState>();
+    match state.supervisor.forget_session(&tunnel_id) {
+        Ok(()) => CommandResponse::success(format!("Session for tunnel {} forgotten", tunnel_id)),
+        Err(e) => CommandResponse::failure(&e),
+    }
+}
 
-    match client.get_tunnel_start_time(tunnel_id) {
-        Ok(start_time) => CommandResponse::success(start_time),
-        Err(e) => CommandResponse::error(e.to_string()),
+#[tauri::command]
+pub fn set_restore_sessions_on_startup<R: tauri::Runtime>(app: tauri::AppHandle<R>, enabled: bool) -> CommandResponse<bool> {
+    let state = app.state::<AppState>();
+    match state.supervisor.set_restore_on_startup(enabled) {
+        Ok(()) => CommandResponse::success(enabled),
+        Err(e) => CommandResponse::failure(&e),
     }
 }
 
+// Connect Commands
+
+// Forward a remote tunnel's ports to localhost with `devtunnel connect`. The
+// tunnel's ports are checked for local conflicts first; the port list may not
+// be readable with only a connect token, in which case the check is skipped.
 #[tauri::command]
-pub fn ping_port(app: tauri::AppHandle, url: String) -> CommandResponse<crate::types::PingResult> {
-    emit_log(&app, &format!("Pinging port: {}", url));
+pub async fn connect_tunnel<R: tauri::Runtime>(app: tauri::AppHandle<R>, req: ConnectTunnelRequest) -> CommandResponse<ConnectSession> {
+    let log = Logger::new(&app, "connect_tunnel", Some(req.tunnel_id.clone()));
+    log.info(&format!("Connecting to tunnel: {}", req.tunnel_id));
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let state = app.state::<AppState>();
 
-    match client.ping_port(url.clone()) {
+    let list = state.cache.ports.get_or_fetch(&req.tunnel_id, false, state.backend.list_ports(req.tunnel_id.clone()));
+    let ports: Vec<u16> = match state.operations.run("list_ports", None, list).await {
+        Ok(ports) => ports.iter().map(|p| p.port_number).collect(),
+        Err(e) => {
+            log.warn(&format!("Could not list ports of {}, skipping the local port check: {}", req.tunnel_id, e));
+            Vec::new()
+        },
+    };
+
+    match state.connections.connect(req.clone(), &ports).await {
+        Ok(session) => {
+            let mappings: Vec<String> = session
+                .mappings
+                .iter()
+                .map(|m| format!("{}:{} -> {}", m.local_address, m.local_port, m.remote_port))
+                .collect();
+            let mappings = if mappings.is_empty() { "no ports reported yet".to_string() } else { mappings.join(", ") };
+            log.info(&format!("Connected to tunnel: {} ({})", req.tunnel_id, mappings));
+            CommandResponse::success(session)
+        },
+        Err(e) => {
+            log.error(&format!("Failed to connect to tunnel: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+#[tauri::command]
+pub fn list_connections<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<Vec<ConnectSession>> {
+    let state = app.state::<AppState>();
+    CommandResponse::success(state.connections.list())
+}
+
+#[tauri::command]
+pub async fn stop_connection<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    tunnel_id: String,
+    grace_period_ms: Option<u64>,
+) -> CommandResponse<StopResult> {
+    let log = Logger::new(&app, "stop_connection", Some(tunnel_id.clone()));
+    log.info(&format!("Disconnecting from tunnel: {}", tunnel_id));
+
+    let state = app.state::<AppState>();
+    let grace = Duration::from_millis(grace_period_ms.unwrap_or(DEFAULT_STOP_GRACE_MS));
+
+    match state.connections.stop(&tunnel_id, grace).await {
         Ok(result) => {
-            emit_log(&app, &format!("Ping successful: {}", url));
+            let how = match result.method {
+                StopMethod::Graceful => "terminated gracefully",
+                StopMethod::Forced => "killed after grace period",
+                StopMethod::AlreadyExited => "already exited",
+                StopMethod::NotRunning => "no connect process",
+            };
+            log.info(&format!("Disconnected from tunnel: {} ({})", tunnel_id, how));
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Ping failed: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to disconnect from tunnel: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
@@ -331,90 +910,147 @@ pub fn ping_port(app: tauri::AppHandle, url: String) -> CommandResponse<crate::t
 // Port Management Commands
 
 #[tauri::command]
-pub fn create_port(app: tauri::AppHandle, req: CreatePortRequest) -> CommandResponse<String> {
-    emit_log(&app, &format!("Creating port {} on tunnel {}", req.port_number, req.tunnel_id));
+pub async fn create_port<R: tauri::Runtime>(app: tauri::AppHandle<R>, req: CreatePortRequest) -> CommandResponse<String> {
+    let log = Logger::new(&app, "create_port", Some(req.tunnel_id.clone()));
+    log.info(&format!("Creating port {} on tunnel {}", req.port_number, req.tunnel_id));
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let state = app.state::<AppState>();
 
-    match client.create_port(req.clone()) {
+    let result = state.operations.run("create_port", None, state.backend.create_port(req.clone())).await;
+    state.cache.tunnel_changed(&req.tunnel_id);
+
+    match result {
         Ok(result) => {
-            emit_log(&app, &format!("Port {} created successfully", req.port_number));
+            log.info(&format!("Port {} created successfully", req.port_number));
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to create port: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to create port: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn list_ports(app: tauri::AppHandle, tunnel_id: String) -> CommandResponse<Vec<Port>> {
-    emit_log(&app, &format!("Listing ports for tunnel: {}", tunnel_id));
+pub async fn list_ports<R: tauri::Runtime>(app: tauri::AppHandle<R>, tunnel_id: String, force_refresh: Option<bool>) -> CommandResponse<Vec<Port>> {
+    let log = Logger::new(&app, "list_ports", Some(tunnel_id.clone()));
+    log.info(&format!("Listing ports for tunnel: {}", tunnel_id));
+
+    let state = app.state::<AppState>();
+    let list = state.cache.ports.get_or_fetch(
+        &tunnel_id,
+        force_refresh.unwrap_or(false),
+        state.backend.list_ports(tunnel_id.clone()),
+    );
+
+    match state.operations.run("list_ports", None, list).await {
+        Ok(ports) => {
+            log.info(&format!("Found {} port(s) for tunnel: {}", ports.len(), tunnel_id));
+            CommandResponse::success(ports)
+        },
+        Err(e) => {
+            log.error(&format!("Failed to list ports: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+// Local services to suggest as ports. With `tunnel_id` its existing ports are
+// left out; `probe` (default true) guesses HTTP vs HTTPS for each one.
+#[tauri::command]
+pub async fn discover_ports<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    tunnel_id: Option<String>,
+    probe: Option<bool>,
+) -> CommandResponse<Vec<DiscoveredPort>> {
+    let log = Logger::new(&app, "discover_ports", tunnel_id.clone());
+    log.info("Discovering local listening ports");
+
+    let state = app.state::<AppState>();
+    let existing = match &tunnel_id {
+        Some(tunnel_id) => {
+            let list = state.cache.ports.get_or_fetch(tunnel_id, false, state.backend.list_ports(tunnel_id.clone()));
+            match state.operations.run("list_ports", None, list).await {
+                Ok(ports) => ports.iter().map(|p| p.port_number).collect(),
+                Err(e) => {
+                    log.error(&format!("Failed to list ports: {}", e));
+                    return CommandResponse::failure(&e);
+                },
+            }
+        },
+        None => Vec::new(),
+    };
 
-    match client.list_ports(tunnel_id.clone()) {
+    match discovery::discover(&existing, probe.unwrap_or(true)).await {
         Ok(ports) => {
-            emit_log(&app, &format!("Found {} port(s) for tunnel: {}", ports.len(), tunnel_id));
+            log.info(&format!("Found {} listening port(s)", ports.len()));
             CommandResponse::success(ports)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to list ports: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to discover ports: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn show_port(app: tauri::AppHandle, tunnel_id: String, port_number: u16) -> CommandResponse<Port> {
-    emit_log(&app, &format!("Fetching port {} details for tunnel: {}", port_number, tunnel_id));
+pub async fn show_port<R: tauri::Runtime>(app: tauri::AppHandle<R>, tunnel_id: String, port_number: u16) -> CommandResponse<Port> {
+    let log = Logger::new(&app, "show_port", Some(tunnel_id.clone()));
+    log.info(&format!("Fetching port {} details for tunnel: {}", port_number, tunnel_id));
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let state = app.state::<AppState>();
 
-    match client.show_port(tunnel_id.clone(), port_number) {
+    match state.operations.run("show_port", None, state.backend.show_port(tunnel_id.clone(), port_number)).await {
         Ok(port) => {
-            emit_log(&app, &format!("Retrieved port {} details", port_number));
+            log.info(&format!("Retrieved port {} details", port_number));
             CommandResponse::success(port)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to show port: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to show port: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn update_port(app: tauri::AppHandle, req: UpdatePortRequest) -> CommandResponse<String> {
-    emit_log(&app, &format!("Updating port {} on tunnel {}", req.port_number, req.tunnel_id));
-    let client = DevTunnelClient::new(get_devtunnel_path());
+pub async fn update_port<R: tauri::Runtime>(app: tauri::AppHandle<R>, req: UpdatePortRequest) -> CommandResponse<String> {
+    let log = Logger::new(&app, "update_port", Some(req.tunnel_id.clone()));
+    log.info(&format!("Updating port {} on tunnel {}", req.port_number, req.tunnel_id));
+    let state = app.state::<AppState>();
+
+    let result = state.operations.run("update_port", None, state.backend.update_port(req.clone())).await;
+    state.cache.tunnel_changed(&req.tunnel_id);
 
-    match client.update_port(req.clone()) {
+    match result {
         Ok(result) => {
-            emit_log(&app, &format!("Port {} updated successfully", req.port_number));
+            log.info(&format!("Port {} updated successfully", req.port_number));
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to update port: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to update port: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn delete_port(app: tauri::AppHandle, tunnel_id: String, port: u16) -> CommandResponse<String> {
-    emit_log(&app, &format!("Deleting port {} from tunnel {}", port, tunnel_id));
+pub async fn delete_port<R: tauri::Runtime>(app: tauri::AppHandle<R>, tunnel_id: String, port: u16) -> CommandResponse<String> {
+    let log = Logger::new(&app, "delete_port", Some(tunnel_id.clone()));
+    log.info(&format!("Deleting port {} from tunnel {}", port, tunnel_id));
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let state = app.state::<AppState>();
 
-    match client.delete_port(tunnel_id.clone(), port) {
+    let result = state.operations.run("delete_port", None, state.backend.delete_port(tunnel_id.clone(), port)).await;
+    state.cache.tunnel_changed(&tunnel_id);
+
+    match result {
         Ok(result) => {
-            emit_log(&app, &format!("Port {} deleted successfully", port));
+            log.info(&format!("Port {} deleted successfully", port));
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to delete port: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to delete port: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
@@ -422,79 +1058,549 @@ pub fn delete_port(app: tauri::AppHandle, tunnel_id: String, port: u16) -> Comma
 // Access Control Commands
 
 #[tauri::command]
-pub fn create_access(app: tauri::AppHandle, req: CreateAccessRequest) -> CommandResponse<String> {
-    emit_log(&app, &format!("Creating access for tunnel: {}", req.tunnel_id));
+pub async fn create_access<R: tauri::Runtime>(app: tauri::AppHandle<R>, req: CreateAccessRequest) -> CommandResponse<String> {
+    let log = Logger::new(&app, "create_access", Some(req.tunnel_id.clone()));
+    log.info(&format!("Creating access for tunnel: {}", req.tunnel_id));
+
+    let state = app.state::<AppState>();
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let result = state.operations.run("create_access", None, state.backend.create_access(req.clone())).await;
+    state.cache.access_changed(&req.tunnel_id);
 
-    match client.create_access(req.clone()) {
+    match result {
         Ok(result) => {
-            emit_log(&app, &format!("Access created for tunnel: {}", req.tunnel_id));
+            log.info(&format!("Access created for tunnel: {}", req.tunnel_id));
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to create access: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to create access: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn list_access(app: tauri::AppHandle, tunnel_id: String) -> CommandResponse<String> {
-    emit_log(&app, &format!("Listing access for tunnel: {}", tunnel_id));
+pub async fn list_access<R: tauri::Runtime>(app: tauri::AppHandle<R>, tunnel_id: String) -> CommandResponse<String> {
+    let log = Logger::new(&app, "list_access", Some(tunnel_id.clone()));
+    log.info(&format!("Listing access for tunnel: {}", tunnel_id));
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let state = app.state::<AppState>();
 
-    match client.list_access(tunnel_id.clone()) {
+    match state.operations.run("list_access", None, state.backend.list_access(tunnel_id.clone())).await {
         Ok(result) => {
-            emit_log(&app, &format!("Retrieved access list for tunnel: {}", tunnel_id));
+            log.info(&format!("Retrieved access list for tunnel: {}", tunnel_id));
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to list access: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to list access: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
 #[tauri::command]
-pub fn reset_access(app: tauri::AppHandle, tunnel_id: String) -> CommandResponse<String> {
-    emit_log(&app, &format!("Resetting access for tunnel: {}", tunnel_id));
+pub async fn list_access_entries<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    tunnel_id: String,
+    force_refresh: Option<bool>,
+) -> CommandResponse<Vec<AccessEntry>> {
+    let log = Logger::new(&app, "list_access_entries", Some(tunnel_id.clone()));
+    log.info(&format!("Listing access entries for tunnel: {}", tunnel_id));
+
+    let state = app.state::<AppState>();
+    let list = state.cache.access.get_or_fetch(
+        &tunnel_id,
+        force_refresh.unwrap_or(false),
+        state.backend.list_access_entries(tunnel_id.clone()),
+    );
+
+    match state.operations.run("list_access_entries", None, list).await {
+        Ok(entries) => {
+            log.info(&format!("Retrieved {} access entries for tunnel: {}", entries.len(), tunnel_id));
+            CommandResponse::success(entries)
+        },
+        Err(e) => {
+            log.error(&format!("Failed to list access: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+#[tauri::command]
+pub async fn delete_access<R: tauri::Runtime>(app: tauri::AppHandle<R>, req: DeleteAccessRequest) -> CommandResponse<String> {
+    let log = Logger::new(&app, "delete_access", Some(req.tunnel_id.clone()));
+    log.info(&format!("Deleting access for tunnel: {}", req.tunnel_id));
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let state = app.state::<AppState>();
 
-    match client.reset_access(tunnel_id.clone()) {
+    let result = state.operations.run("delete_access", None, state.backend.delete_access(req.clone())).await;
+    state.cache.access_changed(&req.tunnel_id);
+
+    match result {
         Ok(result) => {
-            emit_log(&app, &format!("Access reset for tunnel: {}", tunnel_id));
+            log.info(&format!("Access deleted for tunnel: {}", req.tunnel_id));
             CommandResponse::success(result)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to reset access: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to delete access: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+#[tauri::command]
+pub async fn reset_access<R: tauri::Runtime>(app: tauri::AppHandle<R>, tunnel_id: String) -> CommandResponse<String> {
+    let log = Logger::new(&app, "reset_access", Some(tunnel_id.clone()));
+    log.info(&format!("Resetting access for tunnel: {}", tunnel_id));
+
+    let state = app.state::<AppState>();
+
+    let result = state.operations.run("reset_access", None, state.backend.reset_access(tunnel_id.clone())).await;
+    state.cache.access_changed(&tunnel_id);
+
+    match result {
+        Ok(result) => {
+            log.info(&format!("Access reset for tunnel: {}", tunnel_id));
+            CommandResponse::success(result)
+        },
+        Err(e) => {
+            log.error(&format!("Failed to reset access: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+// Changes needed to bring a tunnel's access list to `desired`; nothing is applied
+#[tauri::command]
+pub async fn diff_access<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    tunnel_id: String,
+    desired: Vec<AccessControlEntry>,
+) -> CommandResponse<AccessPlan> {
+    let log = Logger::new(&app, "diff_access", Some(tunnel_id.clone()));
+    log.info(&format!("Comparing access for tunnel: {}", tunnel_id));
+
+    let state = app.state::<AppState>();
+
+    // Always diff against fresh entries; the result refreshes the cache too
+    let current = state.cache.access.get_or_fetch(&tunnel_id, true, state.backend.list_access_entries(tunnel_id.clone()));
+    let plan = state
+        .operations
+        .run("list_access_entries", None, current)
+        .await
+        .and_then(|current| access::diff_access(&tunnel_id, current, &desired));
+
+    match plan {
+        Ok(plan) => {
+            log.info(&format!("{} access change(s) needed for tunnel: {}", plan.changes.len(), tunnel_id));
+            CommandResponse::success(plan)
+        },
+        Err(e) => {
+            log.error(&format!("Failed to compare access: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+// Token Commands
+
+// Issue a token for sharing a private tunnel, e.g. with a CI job. A warning is
+// logged (and "token-expiring" emitted) once it gets within
+// token::EXPIRY_WARNING of running out.
+#[tauri::command]
+pub async fn issue_token<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    tunnel_id: String,
+    scopes: Vec<String>,
+    port: Option<u16>,
+) -> CommandResponse<TunnelToken> {
+    let log = Logger::new(&app, "issue_token", Some(tunnel_id.clone()));
+    log.info(&format!("Issuing {} token for tunnel: {}", scopes.join(", "), tunnel_id));
+
+    if let Err(e) = token::validate_scopes(&scopes) {
+        log.error(&format!("Failed to issue token: {}", e));
+        return CommandResponse::failure(&e);
+    }
+
+    let state = app.state::<AppState>();
+
+    let result = state
+        .operations
+        .run("issue_token", None, state.backend.issue_token(tunnel_id.clone(), scopes.clone(), port))
+        .await
+        .and_then(|raw| token::decode(&raw, &tunnel_id, &scopes, port));
+
+    match result {
+        Ok(issued) => {
+            let expires_at = issued.expires_at.clone().unwrap_or_else(|| "unknown".to_string());
+            log.info(&format!("Token issued for tunnel {} (expires {})", tunnel_id, expires_at));
+            if issued.expiring_soon {
+                log.warn(&format!("Token for tunnel {} expires soon: {}", tunnel_id, expires_at));
+            } else if issued.expires_at.is_some() {
+                warn_before_expiry(app.clone(), TokenExpiringEvent {
+                    tunnel_id: tunnel_id.clone(),
+                    scopes: issued.scopes.clone(),
+                    expires_at,
+                });
+            }
+            CommandResponse::success(issued)
+        },
+        Err(e) => {
+            log.error(&format!("Failed to issue token: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+fn warn_before_expiry<R: tauri::Runtime>(app: tauri::AppHandle<R>, event: TokenExpiringEvent) {
+    let Some(left) = token::remaining(&event.expires_at) else {
+        return;
+    };
+    let delay = left.saturating_sub(token::EXPIRY_WARNING);
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        Logger::new(&app, "issue_token", Some(event.tunnel_id.clone())).warn(&format!(
+            "Token for tunnel {} ({}) expires at {}",
+            event.tunnel_id,
+            event.scopes.join(", "),
+            event.expires_at
+        ));
+        let _ = app.emit("token-expiring", &event);
+    });
+}
+
+// Spec File Commands
+
+#[tauri::command]
+pub async fn plan_spec<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    path: String,
+    op_id: Option<String>,
+) -> CommandResponse<SpecPlan> {
+    let log = Logger::new(&app, "plan_spec", None);
+    log.info(&format!("Planning spec: {}", path));
+
+    let state = app.state::<AppState>();
+
+    let plan = state
+        .operations
+        .run("plan_spec", op_id, async {
+            let spec = spec::load_spec(Path::new(&path))?;
+            spec::plan_spec(state.backend.as_ref(), &spec).await
+        })
+        .await;
+
+    match plan {
+        Ok(plan) => {
+            log.info(&format!("{} step(s) needed for spec: {}", plan.steps.len(), path));
+            CommandResponse::success(plan)
+        },
+        Err(e) => {
+            log.error(&format!("Failed to plan spec: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
+// Re-plans against current state, then runs the steps. A failed step still
+// returns the report so the UI can show what was applied; a timeout or
+// cancellation stops between steps and the log shows how far it got.
+#[tauri::command]
+pub async fn apply_spec<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    path: String,
+    op_id: Option<String>,
+) -> CommandResponse<SpecApplyReport> {
+    let log = Logger::new(&app, "apply_spec", None);
+    log.info(&format!("Applying spec: {}", path));
+
+    let state = app.state::<AppState>();
+
+    let apply = async {
+        let spec = spec::load_spec(Path::new(&path))?;
+        let plan = spec::plan_spec(state.backend.as_ref(), &spec).await?;
+        Ok(spec::apply_plan(state.backend.as_ref(), plan, |result| match result.status {
+            SpecStepStatus::Applied => {
+                state.cache.spec_step_ran(&result.step.action);
+                log.info(&format!("Applied: {}", result.step.summary))
+            },
+            SpecStepStatus::Failed => {
+                state.cache.spec_step_ran(&result.step.action);
+                log.error(&format!(
+                    "Failed: {}: {}",
+                    result.step.summary,
+                    result.error.as_deref().unwrap_or("")
+                ))
+            },
+            SpecStepStatus::Skipped => log.warn(&format!("Skipped: {}", result.step.summary)),
+        })
+        .await)
+    };
+
+    let report = match state.operations.run("apply_spec", op_id, apply).await {
+        Ok(report) => report,
+        Err(e) => {
+            log.error(&format!("Failed to apply spec: {}", e));
+            return CommandResponse::failure(&e);
+        }
+    };
+
+    if report.success {
+        log.info(&format!("Spec applied: {} step(s)", report.results.len()));
+    }
+    CommandResponse::success(report)
+}
+
 // Cluster Commands
 
 #[tauri::command]
-pub fn list_clusters(app: tauri::AppHandle, ping: bool) -> CommandResponse<Vec<Cluster>> {
-    emit_log(&app, "Fetching available clusters...");
+pub async fn list_clusters<R: tauri::Runtime>(app: tauri::AppHandle<R>, ping: bool, op_id: Option<String>) -> CommandResponse<Vec<Cluster>> {
+    let log = Logger::new(&app, "list_clusters", None);
+    log.info("Fetching available clusters...");
 
-    let client = DevTunnelClient::new(get_devtunnel_path());
+    let state = app.state::<AppState>();
 
-    match client.list_clusters(ping) {
+    match state.operations.run("list_clusters", op_id, state.backend.list_clusters(ping)).await {
         Ok(clusters) => {
-            emit_log(&app, &format!("Found {} cluster(s)", clusters.len()));
+            log.info(&format!("Found {} cluster(s)", clusters.len()));
             CommandResponse::success(clusters)
         },
         Err(e) => {
-            emit_log(&app, &format!("ERROR: Failed to list clusters: {}", e));
-            CommandResponse::error(e.to_string())
+            log.error(&format!("Failed to list clusters: {}", e));
+            CommandResponse::failure(&e)
         },
     }
 }
 
+// Operation Commands
+
+// Abort an in-flight command started with the same `opId`. Only the slow
+// read commands (login, list/show/enrich, clusters, spec plan/apply) take one;
+// create/update/delete and list_ports run until done or their CLI timeout,
+// and a hosted tunnel is ended with stop_tunnel instead.
+#[tauri::command]
+pub fn cancel_operation<R: tauri::Runtime>(app: tauri::AppHandle<R>, op_id: String) -> CommandResponse<bool> {
+    let log = Logger::new(&app, "cancel_operation", None);
+    let state = app.state::<AppState>();
+    let cancelled = state.operations.cancel(&op_id);
+    if cancelled {
+        log.info(&format!("Cancelled operation: {}", op_id));
+    }
+    CommandResponse::success(cancelled)
+}
+
+#[tauri::command]
+pub fn get_cli_timeouts<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<CliTimeouts> {
+    let state = app.state::<AppState>();
+    CommandResponse::success(state.operations.timeouts())
+}
+
+#[tauri::command]
+pub fn set_cli_timeouts<R: tauri::Runtime>(app: tauri::AppHandle<R>, timeouts: CliTimeouts) -> CommandResponse<CliTimeouts> {
+    let log = Logger::new(&app, "set_cli_timeouts", None);
+    let state = app.state::<AppState>();
+    match state.operations.set_timeouts(timeouts.clone()) {
+        Ok(()) => CommandResponse::success(timeouts),
+        Err(e) => {
+            log.error(&format!("Failed to save CLI timeouts: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+#[tauri::command]
+pub fn get_cli_limits<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<CliLimits> {
+    let state = app.state::<AppState>();
+    CommandResponse::success(state.executor.limits())
+}
+
+#[tauri::command]
+pub fn set_cli_limits<R: tauri::Runtime>(app: tauri::AppHandle<R>, limits: CliLimits) -> CommandResponse<CliLimits> {
+    let log = Logger::new(&app, "set_cli_limits", None);
+    let state = app.state::<AppState>();
+    match state.executor.set_limits(limits.clone()) {
+        Ok(()) => CommandResponse::success(limits),
+        Err(e) => {
+            log.error(&format!("Failed to save CLI limits: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+// Background Refresh
+
+#[tauri::command]
+pub fn get_poller_settings<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<PollerSettings> {
+    let state = app.state::<AppState>();
+    CommandResponse::success(state.poller.settings())
+}
+
+#[tauri::command]
+pub fn set_poller_settings<R: tauri::Runtime>(app: tauri::AppHandle<R>, settings: PollerSettings) -> CommandResponse<PollerSettings> {
+    let log = Logger::new(&app, "set_poller_settings", None);
+    let state = app.state::<AppState>();
+    match state.poller.set_settings(settings.clone()) {
+        Ok(()) => CommandResponse::success(settings),
+        Err(e) => {
+            log.error(&format!("Failed to save refresh settings: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+// The frontend pauses polling while the window is hidden
+#[tauri::command]
+pub fn set_poller_paused<R: tauri::Runtime>(app: tauri::AppHandle<R>, paused: bool) -> CommandResponse<bool> {
+    let state = app.state::<AppState>();
+    state.poller.set_paused(paused);
+    CommandResponse::success(paused)
+}
+
+// Port Monitoring
+
+#[tauri::command]
+pub fn get_monitor_settings<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<MonitorSettings> {
+    let state = app.state::<AppState>();
+    CommandResponse::success(state.monitor.settings())
+}
+
+#[tauri::command]
+pub fn set_monitor_settings<R: tauri::Runtime>(app: tauri::AppHandle<R>, settings: MonitorSettings) -> CommandResponse<MonitorSettings> {
+    let log = Logger::new(&app, "set_monitor_settings", None);
+    let state = app.state::<AppState>();
+    match state.monitor.set_settings(settings.clone()) {
+        Ok(()) => CommandResponse::success(settings),
+        Err(e) => {
+            log.error(&format!("Failed to save monitor settings: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+// Uptime and latency of every monitored port over the last `window_hours` (default 24)
+#[tauri::command]
+pub fn get_port_health<R: tauri::Runtime>(app: tauri::AppHandle<R>, window_hours: Option<u32>) -> CommandResponse<Vec<PortHealth>> {
+    let state = app.state::<AppState>();
+    let window = Duration::from_secs(u64::from(window_hours.unwrap_or(24)) * 60 * 60);
+    CommandResponse::success(state.monitor.health(window))
+}
+
+// `since` is an RFC 3339 timestamp; all kept samples are returned without it
+#[tauri::command]
+pub fn get_port_health_history<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    tunnel_id: String,
+    port_number: u16,
+    since: Option<String>,
+) -> CommandResponse<Vec<HealthSample>> {
+    let state = app.state::<AppState>();
+    let since = match since.map(|s| chrono::DateTime::parse_from_rfc3339(&s)).transpose() {
+        Ok(since) => since.map(|s| s.with_timezone(&chrono::Utc)),
+        Err(e) => return CommandResponse::coded(ErrorCode::InvalidArgument, format!("Invalid since timestamp: {}", e)),
+    };
+    CommandResponse::success(state.monitor.history(&tunnel_id, port_number, since))
+}
+
+// Log Commands
+
+#[tauri::command]
+pub fn get_logs<R: tauri::Runtime>(app: tauri::AppHandle<R>, filter: Option<LogFilter>) -> CommandResponse<Vec<LogEntry>> {
+    let state = app.state::<AppState>();
+    CommandResponse::success(state.logs.query(&filter.unwrap_or_default()))
+}
+
+#[tauri::command]
+pub fn get_log_file_settings<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<LogFileSettings> {
+    let state = app.state::<AppState>();
+    CommandResponse::success(state.log_files.settings())
+}
+
+#[tauri::command]
+pub fn set_log_file_settings<R: tauri::Runtime>(app: tauri::AppHandle<R>, settings: LogFileSettings) -> CommandResponse<LogFileSettings> {
+    let log = Logger::new(&app, "set_log_file_settings", None);
+    let state = app.state::<AppState>();
+    match state.log_files.set_settings(settings.clone()) {
+        Ok(()) => CommandResponse::success(settings),
+        Err(e) => {
+            log.error(&format!("Failed to save log file settings: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+#[tauri::command]
+pub fn get_redaction_settings<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> CommandResponse<RedactionSettings> {
+    let state = app.state::<AppState>();
+    CommandResponse::success(state.redactor.settings())
+}
+
+#[tauri::command]
+pub fn set_redaction_settings<R: tauri::Runtime>(app: tauri::AppHandle<R>, settings: RedactionSettings) -> CommandResponse<RedactionSettings> {
+    let log = Logger::new(&app, "set_redaction_settings", None);
+    let state = app.state::<AppState>();
+    match state.redactor.set_settings(settings.clone()) {
+        Ok(()) => CommandResponse::success(settings),
+        Err(e) => {
+            log.error(&format!("Failed to save redaction settings: {}", e));
+            CommandResponse::failure(&e)
+        },
+    }
+}
+
+// Zip the log files (optionally one tunnel's and/or a time window) together
+// with the CLI version and redacted settings for a bug report. Returns the
+// path of the archive.
+#[tauri::command]
+pub async fn export_logs<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    range: Option<LogRange>,
+    tunnel_id: Option<String>,
+) -> CommandResponse<String> {
+    let log = Logger::new(&app, "export_logs", tunnel_id.clone());
+    log.info("Exporting logs");
+
+    let state = app.state::<AppState>();
+    let cli = check_devtunnel_installation(app.clone()).await.data;
+    let system = serde_json::json!({
+        "appVersion": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "devtunnel": cli,
+    });
+    let mut settings = serde_json::json!({
+        "cliTimeouts": state.operations.timeouts(),
+        "cliLimits": state.executor.limits(),
+        "poller": state.poller.settings(),
+        "logFiles": state.log_files.settings(),
+        "hostSessions": state.supervisor.sessions_report(),
+    });
+    logfiles::redact_settings(&mut settings);
+
+    let extra = vec![
+        ("system.json", serde_json::to_string_pretty(&system).unwrap_or_default()),
+        ("settings.json", serde_json::to_string_pretty(&settings).unwrap_or_default()),
+    ];
+    let log_files = state.log_files.clone();
+    let range = range.unwrap_or_default();
+    let filter = tunnel_id.clone();
+    let result = tokio::task::spawn_blocking(move || log_files.export(&range, filter.as_deref(), &extra))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|r| r);
+
+    match result {
+        Ok(path) => {
+            log.info(&format!("Logs exported to {}", path.display()));
+            CommandResponse::success(path.to_string_lossy().to_string())
+        }
+        Err(e) => {
+            log.error(&format!("Failed to export logs: {}", e));
+            CommandResponse::failure(&e)
+        }
+    }
+}
+
 // System Commands
 
 #[derive(serde::Serialize)]
@@ -504,44 +1610,14 @@ pub struct DevTunnelInfo {
     pub version: Option<String>,
 }
 
+// The binary comes from DEVTUNNEL_BIN or PATH; `--version` runs under the
+// CLI timeout so a wedged binary can't hang the settings page.
 #[tauri::command]
-pub fn check_devtunnel_installation() -> CommandResponse<DevTunnelInfo> {
-    use std::process::Command;
-
-    // Try to find devtunnel binary
+pub async fn check_devtunnel_installation<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> CommandResponse<DevTunnelInfo> {
     let binary_path = get_devtunnel_path();
-
-    // Check if the binary exists and is executable
-    let path_obj = std::path::Path::new(&binary_path);
-
-    if !path_obj.exists() {
-        // Try to find it in PATH
-        if let Ok(output) = Command::new("which").arg("devtunnel").output() {
-            if output.status.success() {
-                let found_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                if !found_path.is_empty() {
-                    // Get version
-                    let version = Command::new(&found_path)
-                        .arg("--version")
-                        .output()
-                        .ok()
-                        .and_then(|o| {
-                            if o.status.success() {
-                                Some(String::from_utf8_lossy(&o.stdout).trim().to_string())
-                            } else {
-                                None
-                            }
-                        });
-
-                    return CommandResponse::success(DevTunnelInfo {
-                        installed: true,
-                        path: Some(found_path),
-                        version,
-                    });
-                }
-            }
-        }
-
+    if !Path::new(&binary_path).exists() {
         return CommandResponse::success(DevTunnelInfo {
             installed: false,
             path: None,
@@ -549,18 +1625,23 @@ pub fn check_devtunnel_installation() -> CommandResponse<DevTunnelInfo> {
         });
     }
 
-    // Binary exists at configured path, get version
-    let version = Command::new(&binary_path)
-        .arg("--version")
-        .output()
+    let state = app.state::<AppState>();
+    let version = state
+        .operations
+        .run("check_devtunnel_installation", None, async {
+            let output = tokio::process::Command::new(&binary_path)
+                .arg("--version")
+                .kill_on_drop(true)
+                .output()
+                .await?;
+            Ok(output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+        })
+        .await
         .ok()
-        .and_then(|o| {
-            if o.status.success() {
-                Some(String::from_utf8_lossy(&o.stdout).trim().to_string())
-            } else {
-                None
-            }
-        });
+        .flatten();
 
     CommandResponse::success(DevTunnelInfo {
         installed: true,
@@ -0,0 +1,267 @@
+// Client side of a tunnel: supervised `devtunnel connect` processes that
+// forward a remote tunnel's ports to localhost. Each one reports its local
+// port mappings on stdout; every change of state or mappings goes out as a
+// "connect-status" event carrying the whole ConnectSession.
+use crate::backend::DevTunnelBackend;
+use crate::error::DevTunnelError;
+use crate::events::{self, EventSink};
+use crate::parser::parse_port_mapping;
+use crate::redact::Redactor;
+use crate::supervisor::{
+    read_lines, spawn_piped, stop_process, Exit, OutputBuffer, Starting, StopRequest, Tracked, DEFAULT_STOP_GRACE_MS,
+    PROCESS_START_DELAY_MS,
+};
+use crate::types::*;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncRead;
+use tokio::sync::{oneshot, watch};
+
+const OUTPUT_BUFFER_LINES: usize = 200; // Lines of connect output kept per tunnel
+
+// Ports from `ports` that something on this machine is already listening on
+pub fn busy_ports(ports: &[u16]) -> Vec<u16> {
+    ports
+        .iter()
+        .copied()
+        .filter(|port| TcpListener::bind(("127.0.0.1", *port)).is_err())
+        .collect()
+}
+
+struct Connection {
+    tunnel_id: String,
+    pid: Option<u32>,
+    started_at: DateTime<Local>,
+    connected: Mutex<bool>,
+    mappings: Mutex<Vec<PortMapping>>,
+    output: Mutex<OutputBuffer>,
+    redactor: Arc<Redactor>,
+    events: Arc<dyn EventSink>,
+    // None while running, Some(description) once the process has exited
+    exit_status: watch::Receiver<Option<Exit>>,
+    stop_tx: Mutex<Option<oneshot::Sender<StopRequest>>>,
+}
+
+impl Connection {
+    fn is_running(&self) -> bool {
+        self.exit_status.borrow().is_none()
+    }
+
+    fn session(&self) -> ConnectSession {
+        let exit_status = self.exit_status.borrow().as_ref().map(|exit| exit.description.clone());
+        let state = match (&exit_status, *self.connected.lock().unwrap()) {
+            (Some(_), _) => ConnectState::Exited,
+            (None, true) => ConnectState::Connected,
+            (None, false) => ConnectState::Starting,
+        };
+
+        ConnectSession {
+            tunnel_id: self.tunnel_id.clone(),
+            pid: self.pid,
+            started_at: self.started_at.to_rfc3339(),
+            state,
+            mappings: self.mappings.lock().unwrap().clone(),
+            exit_status,
+        }
+    }
+
+    fn emit_status(&self) {
+        events::emit(self.events.as_ref(), "connect-status", &self.session());
+    }
+
+    fn record(&self, stream: OutputStream, line: String) {
+        let line = self.redactor.redact(&line).into_owned();
+
+        let changed = if let Some(mapping) = parse_port_mapping(&line) {
+            *self.connected.lock().unwrap() = true;
+            let mut mappings = self.mappings.lock().unwrap();
+            let new = !mappings.contains(&mapping);
+            if new {
+                mappings.push(mapping);
+            }
+            new
+        } else if line.starts_with("Connected to tunnel") {
+            !std::mem::replace(&mut *self.connected.lock().unwrap(), true)
+        } else {
+            false
+        };
+
+        self.output.lock().unwrap().push(stream, line);
+        if changed {
+            self.emit_status();
+        }
+    }
+}
+
+// Long-lived owner of connect processes, kept in AppState next to the host supervisor
+pub struct ConnectManager {
+    connections: Mutex<HashMap<String, Arc<Connection>>>,
+    starting: Starting,
+    backend: Arc<dyn DevTunnelBackend>,
+    events: Arc<dyn EventSink>,
+    redactor: Arc<Redactor>,
+}
+
+impl ConnectManager {
+    pub fn new(backend: Arc<dyn DevTunnelBackend>, events: Arc<dyn EventSink>, redactor: Arc<Redactor>) -> Self {
+        Self {
+            connections: Mutex::new(HashMap::new()),
+            starting: Starting::default(),
+            backend,
+            events,
+            redactor,
+        }
+    }
+
+    fn get(&self, tunnel_id: &str) -> Option<Arc<Connection>> {
+        self.connections.lock().unwrap().get(tunnel_id).cloned()
+    }
+
+    pub fn is_running(&self, tunnel_id: &str) -> bool {
+        self.get(tunnel_id).map(|c| c.is_running()).unwrap_or(false)
+    }
+
+    // Spawn `devtunnel connect` for the tunnel. `ports` are the tunnel's
+    // ports, checked for local conflicts first unless remapping is allowed.
+    pub async fn connect(&self, req: ConnectTunnelRequest, ports: &[u16]) -> Result<ConnectSession> {
+        let tunnel_id = req.tunnel_id.clone();
+
+        let Some(_reservation) = self.starting.reserve(&tunnel_id, || self.is_running(&tunnel_id)) else {
+            return Err(anyhow::anyhow!("Already connected to tunnel {}", tunnel_id));
+        };
+
+        let busy = busy_ports(ports);
+        if !busy.is_empty() && !req.allow_port_remap.unwrap_or(false) {
+            let busy: Vec<String> = busy.iter().map(u16::to_string).collect();
+            return Err(DevTunnelError::new(
+                ErrorCode::Conflict,
+                format!("Local port(s) already in use: {}", busy.join(", ")),
+            )
+            .into());
+        }
+
+        let mut child = spawn_piped(self.backend.connect_command(&req), "Failed to spawn devtunnel connect")?;
+        let (exit_tx, exit_rx) = watch::channel(None);
+        let (stop_tx, stop_rx) = oneshot::channel::<StopRequest>();
+
+        let connection = Arc::new(Connection {
+            tunnel_id: tunnel_id.clone(),
+            pid: child.id(),
+            started_at: Local::now(),
+            connected: Mutex::new(false),
+            mappings: Mutex::new(Vec::new()),
+            output: Mutex::new(OutputBuffer::new(OUTPUT_BUFFER_LINES)),
+            redactor: self.redactor.clone(),
+            events: self.events.clone(),
+            exit_status: exit_rx,
+            stop_tx: Mutex::new(Some(stop_tx)),
+        });
+
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(pump_output(stdout, OutputStream::Stdout, connection.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(pump_output(stderr, OutputStream::Stderr, connection.clone()));
+        }
+
+        self.connections.lock().unwrap().insert(tunnel_id, connection.clone());
+        tokio::spawn(watch_connection(Tracked::Child(child), connection.clone(), stop_rx, exit_tx));
+        connection.emit_status();
+
+        // Give it a moment to connect
+        tokio::time::sleep(Duration::from_millis(PROCESS_START_DELAY_MS)).await;
+
+        if let Some(status) = connection.exit_status.borrow().as_ref().map(|exit| exit.description.clone()) {
+            let stderr = connection.output.lock().unwrap().tail(5, OutputStream::Stderr);
+            return Err(anyhow::anyhow!(
+                "devtunnel connect process exited immediately with status: {}{}",
+                status,
+                if stderr.is_empty() {
+                    String::new()
+                } else {
+                    format!("\n{}", stderr.join("\n"))
+                }
+            ));
+        }
+
+        Ok(connection.session())
+    }
+
+    pub fn list(&self) -> Vec<ConnectSession> {
+        let mut sessions: Vec<ConnectSession> = self
+            .connections
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|c| c.is_running())
+            .map(|c| c.session())
+            .collect();
+        sessions.sort_by(|a, b| a.tunnel_id.cmp(&b.tunnel_id));
+        sessions
+    }
+
+    // Stop a connect process: SIGTERM, wait up to `grace`, then SIGKILL
+    pub async fn stop(&self, tunnel_id: &str, grace: Duration) -> Result<StopResult> {
+        let started = Instant::now();
+
+        let connection = match self.get(tunnel_id) {
+            Some(c) if c.is_running() => c,
+            _ => {
+                return Ok(StopResult {
+                    tunnel_id: tunnel_id.to_string(),
+                    method: StopMethod::NotRunning,
+                    pid: None,
+                    elapsed_ms: 0,
+                })
+            }
+        };
+
+        let method = stop_process(&connection.stop_tx, &connection.exit_status, grace)
+            .await
+            .context("Connect process waiter disappeared")?;
+
+        Ok(StopResult {
+            tunnel_id: tunnel_id.to_string(),
+            method,
+            pid: connection.pid,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+}
+
+// Watcher task: owns the process until it exits or a stop is requested.
+// Connections are not restarted; the exit is reported and the user reconnects.
+async fn watch_connection(
+    mut tracked: Tracked,
+    connection: Arc<Connection>,
+    stop_rx: oneshot::Receiver<StopRequest>,
+    exit_tx: watch::Sender<Option<Exit>>,
+) {
+    let tunnel_id = connection.tunnel_id.clone();
+
+    let (description, method) = tokio::select! {
+        (description, _) = tracked.wait(&tunnel_id) => (description, StopMethod::AlreadyExited),
+        stop = stop_rx => {
+            let grace = stop
+                .map(|s| s.grace)
+                .unwrap_or(Duration::from_millis(DEFAULT_STOP_GRACE_MS));
+            let (description, _, method) = tracked.terminate(&tunnel_id, grace, connection.events.as_ref()).await;
+            (description, method)
+        }
+    };
+
+    let _ = exit_tx.send(Some(Exit::new(description, method)));
+    connection.emit_status();
+}
+
+// Drain a child stream line by line into the connection
+async fn pump_output<R>(reader: R, stream: OutputStream, connection: Arc<Connection>)
+where
+    R: AsyncRead + Unpin,
+{
+    read_lines(reader, |line| connection.record(stream.clone(), line)).await;
+}
@@ -0,0 +1,552 @@
+// Local control socket. The running app serves it so devtunnel-gui-cli, editor
+// extensions and scripts (anything speaking line-delimited JSON-RPC 2.0) drive
+// the same supervisor as the window instead of spawning hosts of their own.
+use crate::types::*;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+// Must match `identifier` in tauri.conf.json so the CLI finds the app's data dir
+pub const APP_IDENTIFIER: &str = "com.bch.devtunnel-gui";
+pub const SOCKET_ENV: &str = "DEVTUNNEL_GUI_SOCKET";
+
+// JSON-RPC error codes
+pub const PARSE_ERROR: i64 = -32700;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+// Same directory Tauri resolves for app_data_dir()
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(APP_IDENTIFIER)
+}
+
+// Same directory Tauri resolves for app_log_dir()
+pub fn log_dir() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        dirs::home_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("Library/Logs")
+            .join(APP_IDENTIFIER)
+    } else {
+        dirs::data_local_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join(APP_IDENTIFIER)
+            .join("logs")
+    }
+}
+
+// $DEVTUNNEL_GUI_SOCKET, else the per-user runtime dir, else the data dir
+pub fn socket_path() -> PathBuf {
+    if let Some(path) = std::env::var_os(SOCKET_ENV) {
+        return PathBuf::from(path);
+    }
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join("devtunnel-gui.sock"),
+        None => data_dir().join("control.sock"),
+    }
+}
+
+impl RpcResponse {
+    pub fn result(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn error(id: Value, code: i64, message: String) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(RpcError { code, message }),
+        }
+    }
+}
+
+fn params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    // Methods without arguments may be called with params omitted
+    let params = if params.is_null() { Value::Object(Default::default()) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError {
+        code: INVALID_PARAMS,
+        message: format!("Invalid params: {}", e),
+    })
+}
+
+fn to_result<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError {
+        code: INTERNAL_ERROR,
+        message: e.to_string(),
+    })
+}
+
+#[cfg(unix)]
+pub use server::serve;
+
+#[cfg(unix)]
+mod server {
+    use super::*;
+    use crate::commands::{self, Logger};
+    use serde::Deserialize;
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+    use std::path::Path;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+
+    // Param names follow the camelCase keys the frontend passes to invoke()
+    #[derive(Deserialize)]
+    struct ReqParams<T> {
+        req: T,
+    }
+
+    #[derive(Deserialize)]
+    struct ListParams {
+        req: Option<ListTunnelsRequest>,
+        #[serde(rename = "opId")]
+        op_id: Option<String>,
+        #[serde(rename = "forceRefresh")]
+        force_refresh: Option<bool>,
+    }
+
+    #[derive(Deserialize)]
+    struct TunnelParams {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: String,
+    }
+
+    #[derive(Deserialize)]
+    struct ShowParams {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: Option<String>,
+        #[serde(rename = "opId")]
+        op_id: Option<String>,
+        #[serde(rename = "forceRefresh")]
+        force_refresh: Option<bool>,
+    }
+
+    // Cached per-tunnel reads (ports, access entries)
+    #[derive(Deserialize)]
+    struct TunnelReadParams {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: String,
+        #[serde(rename = "forceRefresh")]
+        force_refresh: Option<bool>,
+    }
+
+    #[derive(Deserialize)]
+    struct DiscoverParams {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: Option<String>,
+        probe: Option<bool>,
+    }
+
+    #[derive(Deserialize)]
+    struct CancelParams {
+        #[serde(rename = "opId")]
+        op_id: String,
+    }
+
+    #[derive(Deserialize)]
+    struct TunnelIdsParams {
+        #[serde(rename = "tunnelIds")]
+        tunnel_ids: Option<Vec<String>>,
+    }
+
+    #[derive(Deserialize)]
+    struct StopParams {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: String,
+        #[serde(rename = "gracePeriodMs")]
+        grace_period_ms: Option<u64>,
+    }
+
+    #[derive(Deserialize)]
+    struct OutputParams {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: String,
+        since: Option<u64>,
+    }
+
+    #[derive(Deserialize)]
+    struct ShowPortParams {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: String,
+        #[serde(rename = "portNumber")]
+        port_number: u16,
+    }
+
+    #[derive(Deserialize)]
+    struct IssueTokenParams {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: String,
+        scopes: Vec<String>,
+        #[serde(default)]
+        port: Option<u16>,
+    }
+
+    #[derive(Deserialize)]
+    struct DeletePortParams {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize)]
+    struct DiffAccessParams {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: String,
+        desired: Vec<AccessControlEntry>,
+    }
+
+    #[derive(Deserialize)]
+    struct SpecParams {
+        path: String,
+        #[serde(rename = "opId")]
+        op_id: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct NoParams {}
+
+    // Method names and params mirror the Tauri commands and their invoke
+    // arguments; results are the same CommandResponse values. Login/logout and
+    // app settings stay GUI-only.
+    async fn dispatch<R: tauri::Runtime>(
+        app: &tauri::AppHandle<R>,
+        method: &str,
+        raw: Value,
+    ) -> Result<Value, RpcError> {
+        let app = app.clone();
+        match method {
+            // Tunnels
+            "create_tunnel" => {
+                let p: ReqParams<CreateTunnelRequest> = params(raw)?;
+                to_result(commands::create_tunnel(app, p.req).await)
+            }
+            "list_tunnels" => {
+                let p: ListParams = params(raw)?;
+                to_result(commands::list_tunnels(app, p.req, p.op_id, p.force_refresh).await)
+            }
+            "list_tunnels_light" => {
+                let p: ListParams = params(raw)?;
+                to_result(commands::list_tunnels_light(app, p.req, p.op_id, p.force_refresh).await)
+            }
+            "show_tunnel" => {
+                let p: ShowParams = params(raw)?;
+                to_result(commands::show_tunnel(app, p.tunnel_id, p.op_id, p.force_refresh).await)
+            }
+            "update_tunnel" => {
+                let p: ReqParams<UpdateTunnelRequest> = params(raw)?;
+                to_result(commands::update_tunnel(app, p.req).await)
+            }
+            "delete_tunnel" => {
+                let p: TunnelParams = params(raw)?;
+                to_result(commands::delete_tunnel(app, p.tunnel_id).await)
+            }
+            // Hosting
+            "host_tunnel" => {
+                let p: ReqParams<HostTunnelRequest> = params(raw)?;
+                to_result(commands::host_tunnel(app, p.req).await)
+            }
+            "stop_tunnel" => {
+                let p: StopParams = params(raw)?;
+                to_result(commands::stop_tunnel(app, p.tunnel_id, p.grace_period_ms).await)
+            }
+            "restart_tunnel" => {
+                let p: ReqParams<HostTunnelRequest> = params(raw)?;
+                to_result(commands::restart_tunnel(app, p.req).await)
+            }
+            "get_tunnel_start_time" => {
+                let p: TunnelParams = params(raw)?;
+                to_result(commands::get_tunnel_start_time(app, p.tunnel_id))
+            }
+            "get_host_output" => {
+                let p: OutputParams = params(raw)?;
+                to_result(commands::get_host_output(app, p.tunnel_id, p.since))
+            }
+            "get_host_sessions" => {
+                let _: NoParams = params(raw)?;
+                to_result(commands::get_host_sessions(app))
+            }
+            "restore_host_sessions" => {
+                let p: TunnelIdsParams = params(raw)?;
+                to_result(commands::restore_host_sessions(app, p.tunnel_ids).await)
+            }
+            "forget_host_session" => {
+                let p: TunnelParams = params(raw)?;
+                to_result(commands::forget_host_session(app, p.tunnel_id))
+            }
+            "get_upstream_status" => {
+                let _: NoParams = params(raw)?;
+                to_result(commands::get_upstream_status(app))
+            }
+            // Connecting
+            "connect_tunnel" => {
+                let p: ReqParams<ConnectTunnelRequest> = params(raw)?;
+                to_result(commands::connect_tunnel(app, p.req).await)
+            }
+            "list_connections" => {
+                let _: NoParams = params(raw)?;
+                to_result(commands::list_connections(app))
+            }
+            "stop_connection" => {
+                let p: StopParams = params(raw)?;
+                to_result(commands::stop_connection(app, p.tunnel_id, p.grace_period_ms).await)
+            }
+            // Ports
+            "create_port" => {
+                let p: ReqParams<CreatePortRequest> = params(raw)?;
+                to_result(commands::create_port(app, p.req).await)
+            }
+            "list_ports" => {
+                let p: TunnelReadParams = params(raw)?;
+                to_result(commands::list_ports(app, p.tunnel_id, p.force_refresh).await)
+            }
+            "discover_ports" => {
+                let p: DiscoverParams = params(raw)?;
+                to_result(commands::discover_ports(app, p.tunnel_id, p.probe).await)
+            }
+            "show_port" => {
+                let p: ShowPortParams = params(raw)?;
+                to_result(commands::show_port(app, p.tunnel_id, p.port_number).await)
+            }
+            "update_port" => {
+                let p: ReqParams<UpdatePortRequest> = params(raw)?;
+                to_result(commands::update_port(app, p.req).await)
+            }
+            "delete_port" => {
+                let p: DeletePortParams = params(raw)?;
+                to_result(commands::delete_port(app, p.tunnel_id, p.port).await)
+            }
+            // Access
+            "create_access" => {
+                let p: ReqParams<CreateAccessRequest> = params(raw)?;
+                to_result(commands::create_access(app, p.req).await)
+            }
+            "list_access_entries" => {
+                let p: TunnelReadParams = params(raw)?;
+                to_result(commands::list_access_entries(app, p.tunnel_id, p.force_refresh).await)
+            }
+            "delete_access" => {
+                let p: ReqParams<DeleteAccessRequest> = params(raw)?;
+                to_result(commands::delete_access(app, p.req).await)
+            }
+            "reset_access" => {
+                let p: TunnelParams = params(raw)?;
+                to_result(commands::reset_access(app, p.tunnel_id).await)
+            }
+            "diff_access" => {
+                let p: DiffAccessParams = params(raw)?;
+                to_result(commands::diff_access(app, p.tunnel_id, p.desired).await)
+            }
+            "issue_token" => {
+                let p: IssueTokenParams = params(raw)?;
+                to_result(commands::issue_token(app, p.tunnel_id, p.scopes, p.port).await)
+            }
+            // Spec files
+            "plan_spec" => {
+                let p: SpecParams = params(raw)?;
+                to_result(commands::plan_spec(app, p.path, p.op_id).await)
+            }
+            "apply_spec" => {
+                let p: SpecParams = params(raw)?;
+                to_result(commands::apply_spec(app, p.path, p.op_id).await)
+            }
+            "cancel_operation" => {
+                let p: CancelParams = params(raw)?;
+                to_result(commands::cancel_operation(app, p.op_id))
+            }
+            _ => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Unknown method: {}", method),
+            }),
+        }
+    }
+
+    // None for notifications: they are carried out but never answered
+    async fn handle_line<R: tauri::Runtime>(app: &tauri::AppHandle<R>, line: &str) -> Option<RpcResponse> {
+        let request: RpcRequest = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Some(RpcResponse::error(Value::Null, PARSE_ERROR, format!("Parse error: {}", e))),
+        };
+
+        // The command itself logs the rest, so the GUI log shows external work too
+        let log = Logger::new(app, "control", None);
+        log.info(&format!("Control request: {}", request.method));
+
+        let result = dispatch(app, &request.method, request.params).await;
+        if let Err(e) = &result {
+            log.error(&format!("Control request {} rejected: {}", request.method, e.message));
+        }
+        let id = request.id?;
+        Some(match result {
+            Ok(result) => RpcResponse::result(id, result),
+            Err(e) => RpcResponse::error(id, e.code, e.message),
+        })
+    }
+
+    async fn handle_connection<R: tauri::Runtime>(app: tauri::AppHandle<R>, stream: UnixStream) {
+        // The socket is 0600 already; also refuse peers running as another user
+        // (root can bypass file permissions)
+        let log = Logger::new(&app, "control", None);
+        match stream.peer_cred() {
+            Ok(cred) if cred.uid() == current_uid() => {}
+            Ok(cred) => {
+                log.error(&format!("Control connection from uid {} refused", cred.uid()));
+                return;
+            }
+            Err(e) => {
+                log.error(&format!("Control connection refused: {}", e));
+                return;
+            }
+        }
+
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some(response) = handle_line(&app, &line).await else {
+                continue;
+            };
+            let Ok(mut out) = serde_json::to_string(&response) else {
+                break;
+            };
+            out.push('\n');
+            if writer.write_all(out.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    // The socket's directory must belong to us and not be writable by others,
+    // or someone else could swap the socket between bind and chmod
+    fn check_socket_dir(dir: &Path) -> Result<()> {
+        if !dir.exists() {
+            std::fs::create_dir_all(dir).context("Failed to create control socket directory")?;
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+                .context("Failed to restrict control socket directory permissions")?;
+        }
+
+        let meta = std::fs::metadata(dir).context("Failed to inspect control socket directory")?;
+        if meta.uid() != current_uid() {
+            return Err(anyhow::anyhow!("Control socket directory {} is owned by another user", dir.display()));
+        }
+        if meta.mode() & 0o022 != 0 {
+            return Err(anyhow::anyhow!(
+                "Control socket directory {} is writable by other users",
+                dir.display()
+            ));
+        }
+        Ok(())
+    }
+
+    // Bind the socket (replacing a stale one left by a crashed app) and serve
+    // connections until the app exits
+    pub async fn serve<R: tauri::Runtime>(app: tauri::AppHandle<R>, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            check_socket_dir(parent)?;
+        }
+        if path.exists() {
+            if UnixStream::connect(path).await.is_ok() {
+                return Err(anyhow::anyhow!("Control socket {} is already in use", path.display()));
+            }
+            std::fs::remove_file(path).context("Failed to remove stale control socket")?;
+        }
+
+        let listener = UnixListener::bind(path).context("Failed to bind control socket")?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .context("Failed to restrict control socket permissions")?;
+
+        loop {
+            let (stream, _) = listener.accept().await.context("Control socket accept failed")?;
+            tokio::spawn(handle_connection(app.clone(), stream));
+        }
+    }
+}
+
+#[cfg(unix)]
+fn current_uid() -> u32 {
+    // SAFETY: geteuid takes no arguments, always succeeds and touches no memory
+    unsafe { libc::geteuid() }
+}
+
+// Blocking client used by devtunnel-gui-cli
+#[cfg(unix)]
+pub struct ControlClient {
+    reader: std::io::BufReader<std::os::unix::net::UnixStream>,
+    writer: std::os::unix::net::UnixStream,
+    next_id: u64,
+}
+
+#[cfg(unix)]
+impl ControlClient {
+    // Ok(None) when no app is listening
+    pub fn connect(path: &std::path::Path) -> Result<Option<Self>> {
+        use std::io::ErrorKind;
+        use std::os::unix::net::UnixStream;
+
+        // Don't hand requests to a socket another user planted at our path
+        if let Ok(meta) = std::fs::metadata(path) {
+            use std::os::unix::fs::MetadataExt;
+            if meta.uid() != current_uid() {
+                return Err(anyhow::anyhow!("Control socket {} is owned by another user", path.display()));
+            }
+        }
+
+        let stream = match UnixStream::connect(path) {
+            Ok(stream) => stream,
+            Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => {
+                return Ok(None)
+            }
+            Err(e) => return Err(e).context("Failed to connect to control socket"),
+        };
+
+        Ok(Some(Self {
+            reader: std::io::BufReader::new(stream.try_clone()?),
+            writer: stream,
+            next_id: 1,
+        }))
+    }
+
+    pub fn call<T: DeserializeOwned>(&mut self, method: &str, params: Value) -> Result<T> {
+        use std::io::{BufRead, Write};
+
+        let request = RpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(Value::from(self.next_id)),
+            method: method.to_string(),
+            params,
+        };
+        self.next_id += 1;
+
+        let mut line = serde_json::to_string(&request)?;
+        line.push('\n');
+        self.writer
+            .write_all(line.as_bytes())
+            .context("Failed to send control request")?;
+
+        let mut reply = String::new();
+        if self.reader.read_line(&mut reply).context("Failed to read control response")? == 0 {
+            return Err(anyhow::anyhow!("Control socket closed the connection"));
+        }
+
+        let response: RpcResponse = serde_json::from_str(&reply).context("Invalid control response")?;
+        if let Some(error) = response.error {
+            return Err(anyhow::anyhow!("{} ({})", error.message, error.code));
+        }
+        let result = response
+            .result
+            .ok_or_else(|| anyhow::anyhow!("Control response has no result"))?;
+        serde_json::from_value(result).context("Unexpected control response")
+    }
+}
@@ -1,39 +1,142 @@
+use crate::backend::DevTunnelBackend;
+use crate::access;
+use crate::error::{cli_error, DevTunnelError};
+use crate::executor::CliExecutor;
 use crate::types::*;
 use crate::parser;
 use anyhow::{Context, Result};
-use std::process::{Command, Stdio};
-use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
-use tokio::process::Command as TokioCommand;
-
-// Configuration constants
-const PROCESS_START_DELAY_MS: u64 = 1500; // Wait time after starting a host process
-const PROCESS_STOP_DELAY_MS: u64 = 500;   // Wait time after stopping a process before restart
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::process::Command;
 
+#[derive(Clone)]
 pub struct DevTunnelClient {
     binary_path: String,
-    // Maps tunnel_id to process_id for tracking active host processes
-    active_processes: Arc<Mutex<HashMap<String, u32>>>,
+    executor: Arc<CliExecutor>,
+    // Cleared the first time the CLI rejects `-j`; from then on this client
+    // scrapes the text output instead
+    json_output: Arc<AtomicBool>,
 }
 
 impl DevTunnelClient {
     pub fn new(binary_path: String) -> Self {
+        Self::with_executor(binary_path, Arc::new(CliExecutor::default()))
+    }
+
+    // Share one executor (and its parallelism limit) with the rest of the app
+    pub fn with_executor(binary_path: String, executor: Arc<CliExecutor>) -> Self {
         Self {
             binary_path,
-            active_processes: Arc::new(Mutex::new(HashMap::new())),
+            executor,
+            json_output: Arc::new(AtomicBool::new(true)),
         }
     }
 
+    // One-shot CLI calls. kill_on_drop means a call abandoned on timeout or
+    // cancellation doesn't leave the devtunnel process running.
     fn build_command(&self) -> Command {
-        Command::new(&self.binary_path)
+        let mut cmd = Command::new(&self.binary_path);
+        cmd.kill_on_drop(true);
+        cmd
     }
 
-    fn build_tokio_command(&self) -> TokioCommand {
-        TokioCommand::new(&self.binary_path)
+    // Run `cmd` with `-j` and decode its output. Ok(None) means the caller should
+    // fall back to text mode: for good when this CLI version rejects `-j`, for
+    // this call only when the output just didn't decode.
+    async fn run_json<T>(
+        &self,
+        mut cmd: Command,
+        context: &str,
+        decode: impl FnOnce(&str) -> serde_json::Result<T>,
+    ) -> Result<Option<T>> {
+        if !self.json_output.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        let output = self.executor.output(cmd.arg("-j"), context).await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let lower = stderr.to_lowercase();
+            if lower.contains("unrecognized") || lower.contains("unknown option") {
+                self.json_output.store(false, Ordering::Relaxed);
+                return Ok(None);
+            }
+            return Err(cli_error(context, &output));
+        }
+
+        Ok(decode(&String::from_utf8_lossy(&output.stdout)).ok())
     }
 
+    fn list_command(&self, req: Option<ListTunnelsRequest>) -> Command {
+        let mut cmd = self.build_command();
+        cmd.arg("list");
+
+        if let Some(req) = req {
+            if let Some(tags) = &req.tags {
+                if req.all_tags.unwrap_or(false) {
+                    cmd.arg("--all-tags");
+                } else {
+                    cmd.arg("--tags");
+                }
+                for tag in tags {
+                    cmd.arg(tag);
+                }
+            }
+        }
+
+        cmd
+    }
+
+    fn show_command(&self, tunnel_id: Option<String>) -> Command {
+        let mut cmd = self.build_command();
+        cmd.arg("show");
+
+        if let Some(id) = tunnel_id {
+            cmd.arg(id);
+        }
+
+        cmd
+    }
+
+    // Plain `show` output, used when the CLI predates `-j`
+    async fn show_text(&self, tunnel_id: Option<String>) -> Result<String> {
+        let output = self.executor.output(&mut self.show_command(tunnel_id), "Failed to show tunnel").await?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(cli_error("Failed to show tunnel", &output))
+        }
+    }
+
+    async fn update_port_description(&self, req: UpdatePortRequest) -> Result<String> {
+        let mut cmd = self.build_command();
+        cmd.arg("port")
+            .arg("update")
+            .arg(&req.tunnel_id)
+            .arg("-p")
+            .arg(req.port_number.to_string());
+
+        if let Some(description) = &req.description {
+            cmd.arg("--description").arg(description);
+        }
+
+        let output = self.executor.output(&mut cmd, "Failed to update port").await?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(cli_error("Failed to update port", &output))
+        }
+    }
+}
+
+#[async_trait]
+impl DevTunnelBackend for DevTunnelClient {
     // Authentication
-    pub fn login(&self, provider: &str, use_device_code: bool) -> Result<String> {
+    async fn login(&self, provider: &str, use_device_code: bool) -> Result<String> {
         let mut cmd = self.build_command();
         cmd.arg("user").arg("login");
 
@@ -45,45 +148,38 @@ impl DevTunnelClient {
             cmd.arg("-d");
         }
 
-        let output = cmd
-            .output()
-            .context("Failed to execute devtunnel login")?;
+        let output = self.executor.output(&mut cmd, "Failed to log in").await?;
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Login failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Login failed", &output))
         }
     }
 
-    pub fn logout(&self) -> Result<String> {
-        let output = self
-            .build_command()
-            .arg("user")
-            .arg("logout")
-            .output()
-            .context("Failed to execute devtunnel logout")?;
+    async fn logout(&self) -> Result<String> {
+        let mut cmd = self.build_command();
+        cmd.arg("user").arg("logout");
+        let output = self.executor.output(&mut cmd, "Failed to log out").await?;
 
         if output.status.success() {
             Ok("Logged out successfully".to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Logout failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Logout failed", &output))
         }
     }
 
-    pub fn get_user_info(&self) -> Result<UserInfo> {
-        let output = self
-            .build_command()
-            .arg("user")
-            .arg("show")
-            .output()
-            .context("Failed to get user info")?;
+    async fn get_user_info(&self) -> Result<UserInfo> {
+        let mut cmd = self.build_command();
+        cmd.arg("user").arg("show");
+
+        if let Some(info) = self.run_json(cmd, "Not authenticated", parser::parse_user_info_json).await? {
+            return info.ok_or_else(|| DevTunnelError::new(ErrorCode::AuthRequired, "Not authenticated").into());
+        }
+
+        let mut cmd = self.build_command();
+        cmd.arg("user").arg("show");
+        let output = self.executor.output(&mut cmd, "Failed to get user info").await?;
 
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
@@ -95,12 +191,12 @@ impl DevTunnelClient {
                 Err(anyhow::anyhow!("Failed to parse user info"))
             }
         } else {
-            Err(anyhow::anyhow!("Not authenticated"))
+            Err(DevTunnelError::new(ErrorCode::AuthRequired, "Not authenticated").into())
         }
     }
 
     // Tunnel Management
-    pub fn create_tunnel(&self, req: CreateTunnelRequest) -> Result<String> {
+    async fn create_tunnel(&self, req: CreateTunnelRequest) -> Result<String> {
         let mut cmd = self.build_command();
         cmd.arg("create");
 
@@ -129,164 +225,66 @@ impl DevTunnelClient {
             cmd.arg("--expiration").arg(expiration);
         }
 
-        let output = cmd
-            .output()
-            .context("Failed to create tunnel")?;
+        let output = self.executor.output(&mut cmd, "Failed to create tunnel").await?;
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to create tunnel: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to create tunnel", &output))
         }
     }
 
     // 경량 목록: ports 정보 없이 빠르게 반환
-    pub fn list_tunnels_light(&self, req: Option<ListTunnelsRequest>) -> Result<Vec<TunnelListItem>> {
-        let mut cmd = self.build_command();
-        cmd.arg("list");
-
-        if let Some(req) = req {
-            if let Some(tags) = &req.tags {
-                if req.all_tags.unwrap_or(false) {
-                    cmd.arg("--all-tags");
-                } else {
-                    cmd.arg("--tags");
-                }
-                for tag in tags {
-                    cmd.arg(tag);
-                }
-            }
+    async fn list_tunnels_light(&self, req: Option<ListTunnelsRequest>) -> Result<Vec<TunnelListItem>> {
+        let json = self.run_json(
+            self.list_command(req.clone()),
+            "Failed to list tunnels",
+            parser::parse_tunnel_list_json,
+        ).await?;
+        if let Some(tunnels) = json {
+            return Ok(tunnels);
         }
 
-        let output = cmd
-            .output()
-            .context("Failed to list tunnels")?;
+        let output = self.executor.output(&mut self.list_command(req), "Failed to list tunnels").await?;
 
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
             let tunnels = parser::parse_tunnel_list(&stdout);
             Ok(tunnels)
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to list tunnels: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to list tunnels", &output))
         }
     }
 
-    // 병렬 처리로 상세 정보 추가
-    pub async fn enrich_tunnel_details(&self, tunnel_ids: Vec<String>) -> Result<Vec<TunnelListItem>> {
-        use tokio::task::JoinSet;
-
-        let mut set = JoinSet::new();
+    // Light list plus one `show` per tunnel for its ports, run one at a time
+    async fn list_tunnels(&self, req: Option<ListTunnelsRequest>) -> Result<Vec<TunnelListItem>> {
+        let mut tunnels = self.list_tunnels_light(req).await?;
 
-        for tunnel_id in tunnel_ids {
-            let binary_path = self.binary_path.clone();
-            set.spawn(async move {
-                let client = DevTunnelClient::new(binary_path);
-                let show_output = client.show_tunnel(Some(tunnel_id.clone()))?;
-                let port_numbers = parser::parse_tunnel_show(&show_output);
-                Ok::<(String, Vec<u16>), anyhow::Error>((tunnel_id, port_numbers))
-            });
-        }
-
-        let mut results = Vec::new();
-        while let Some(res) = set.join_next().await {
-            match res {
-                Ok(Ok((tunnel_id, port_numbers))) => {
-                    results.push((tunnel_id, port_numbers));
-                }
-                Ok(Err(e)) => {
-                    eprintln!("Failed to fetch details for tunnel: {}", e);
-                }
-                Err(e) => {
-                    eprintln!("Task join error: {}", e);
-                }
-            }
-        }
-
-        // 경량 목록을 다시 가져와서 ports 정보를 병합
-        let mut tunnels = self.list_tunnels_light(None)?;
+        // For each tunnel, fetch detailed info to get actual ports
         for tunnel in &mut tunnels {
-            if let Some((_, port_numbers)) = results.iter().find(|(id, _)| id == &tunnel.tunnel_id) {
-                tunnel.ports = port_numbers.clone();
+            match self.show_tunnel(Some(tunnel.tunnel_id.clone())).await {
+                Ok(details) => tunnel.ports = details.ports.iter().map(|p| p.port_number).collect(),
+                Err(e) => tunnel.details_error = Some(ItemError::from_error(&e)),
             }
         }
 
         Ok(tunnels)
     }
 
-    // 기존 호환성을 위한 함수 (동기 버전, 병렬 처리 없음)
-    pub fn list_tunnels(&self, req: Option<ListTunnelsRequest>) -> Result<Vec<TunnelListItem>> {
-        let mut cmd = self.build_command();
-        cmd.arg("list");
-
-        if let Some(req) = req {
-            if let Some(tags) = &req.tags {
-                if req.all_tags.unwrap_or(false) {
-                    cmd.arg("--all-tags");
-                } else {
-                    cmd.arg("--tags");
-                }
-                for tag in tags {
-                    cmd.arg(tag);
-                }
-            }
-        }
-
-        let output = cmd
-            .output()
-            .context("Failed to list tunnels")?;
-
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-
-            // Parse tunnel list
-            let mut tunnels = parser::parse_tunnel_list(&stdout);
-
-            // For each tunnel, fetch detailed info to get actual ports
-            for tunnel in &mut tunnels {
-                if let Ok(show_output) = self.show_tunnel(Some(tunnel.tunnel_id.clone())) {
-                    let ports = parser::parse_tunnel_show(&show_output);
-                    tunnel.ports = ports;
-                }
-            }
-
-            Ok(tunnels)
-        } else {
-            Err(anyhow::anyhow!(
-                "Failed to list tunnels: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
-        }
+    async fn show_tunnel(&self, tunnel_id: Option<String>) -> Result<Tunnel> {
+        let json = self.run_json(
+            self.show_command(tunnel_id.clone()),
+            "Failed to show tunnel",
+            parser::parse_tunnel_details_json,
+        ).await?;
+        let details = match json {
+            Some(details) => details,
+            None => parser::parse_tunnel_show_details(&self.show_text(tunnel_id).await?),
+        };
+        Ok(parser::tunnel_from_details(details))
     }
 
-    pub fn show_tunnel(&self, tunnel_id: Option<String>) -> Result<String> {
-        let mut cmd = self.build_command();
-        cmd.arg("show");
-
-        if let Some(id) = tunnel_id {
-            cmd.arg(id);
-        }
-
-        let output = cmd
-            .output()
-            .context("Failed to show tunnel")?;
-
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        } else {
-            Err(anyhow::anyhow!(
-                "Failed to show tunnel: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
-        }
-    }
-
-    pub fn update_tunnel(&self, req: UpdateTunnelRequest) -> Result<String> {
+    async fn update_tunnel(&self, req: UpdateTunnelRequest) -> Result<String> {
         let mut cmd = self.build_command();
         cmd.arg("update").arg(&req.tunnel_id);
 
@@ -307,57 +305,41 @@ impl DevTunnelClient {
             cmd.arg("--expiration").arg(expiration);
         }
 
-        let output = cmd
-            .output()
-            .context("Failed to update tunnel")?;
+        let output = self.executor.output(&mut cmd, "Failed to update tunnel").await?;
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to update tunnel: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to update tunnel", &output))
         }
     }
 
-    pub fn delete_tunnel(&self, tunnel_id: String) -> Result<String> {
-        let output = self
-            .build_command()
-            .arg("delete")
-            .arg(tunnel_id)
-            .output()
-            .context("Failed to delete tunnel")?;
+    async fn delete_tunnel(&self, tunnel_id: String) -> Result<String> {
+        let mut cmd = self.build_command();
+        cmd.arg("delete").arg(tunnel_id);
+        let output = self.executor.output(&mut cmd, "Failed to delete tunnel").await?;
 
         if output.status.success() {
             Ok("Tunnel deleted successfully".to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to delete tunnel: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to delete tunnel", &output))
         }
     }
 
-    pub fn delete_all_tunnels(&self) -> Result<String> {
-        let output = self
-            .build_command()
-            .arg("delete-all")
-            .output()
-            .context("Failed to delete all tunnels")?;
+    async fn delete_all_tunnels(&self) -> Result<String> {
+        let mut cmd = self.build_command();
+        cmd.arg("delete-all");
+        let output = self.executor.output(&mut cmd, "Failed to delete all tunnels").await?;
 
         if output.status.success() {
             Ok("All tunnels deleted successfully".to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to delete all tunnels: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to delete all tunnels", &output))
         }
     }
 
     // Port Management
-    pub fn create_port(&self, req: CreatePortRequest) -> Result<String> {
+    async fn create_port(&self, req: CreatePortRequest) -> Result<String> {
         let mut cmd = self.build_command();
         cmd.arg("port")
             .arg("create")
@@ -378,60 +360,33 @@ impl DevTunnelClient {
             cmd.arg("--description").arg(description);
         }
 
-        let output = cmd
-            .output()
-            .context("Failed to create port")?;
+        let output = self.executor.output(&mut cmd, "Failed to create port").await?;
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to create port: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to create port", &output))
         }
     }
 
-    pub fn list_ports(&self, tunnel_id: String) -> Result<Vec<Port>> {
-        let output = self
-            .build_command()
-            .arg("port")
-            .arg("list")
-            .arg(&tunnel_id)
-            .arg("-j")  // JSON output
-            .output()
-            .context("Failed to list ports")?;
+    async fn list_ports(&self, tunnel_id: String) -> Result<Vec<Port>> {
+        let mut cmd = self.build_command();
+        cmd.arg("port").arg("list").arg(&tunnel_id).arg("-j");  // JSON output
+        let output = self.executor.output(&mut cmd, "Failed to list ports").await?;
 
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
-
-            // Try to parse as JSON
-            match serde_json::from_str::<Vec<Port>>(&stdout) {
-                Ok(ports) => Ok(ports),
-                Err(e) => {
-                    // If JSON parsing fails, return empty vec with warning
-                    eprintln!("Failed to parse ports JSON for tunnel {}: {}", tunnel_id, e);
-                    Ok(Vec::new())
-                }
-            }
+            serde_json::from_str::<Vec<Port>>(&stdout)
+                .with_context(|| format!("Failed to parse ports of tunnel {}", tunnel_id))
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to list ports: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to list ports", &output))
         }
     }
 
-    pub fn show_port(&self, tunnel_id: String, port_number: u16) -> Result<Port> {
-        let output = self
-            .build_command()
-            .arg("port")
-            .arg("show")
-            .arg(tunnel_id)
-            .arg("--port-number")
-            .arg(port_number.to_string())
-            .output()
-            .context("Failed to show port")?;
+    async fn show_port(&self, tunnel_id: String, port_number: u16) -> Result<Port> {
+        let mut cmd = self.build_command();
+        cmd.arg("port").arg("show").arg(tunnel_id).arg("--port-number").arg(port_number.to_string());
+        let output = self.executor.output(&mut cmd, "Failed to show port").await?;
 
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
@@ -442,48 +397,19 @@ impl DevTunnelClient {
                 Err(anyhow::anyhow!("Failed to parse port details"))
             }
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to show port: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
-        }
-    }
-
-    pub fn update_port_description(&self, req: UpdatePortRequest) -> Result<String> {
-        let mut cmd = self.build_command();
-        cmd.arg("port")
-            .arg("update")
-            .arg(&req.tunnel_id)
-            .arg("-p")
-            .arg(req.port_number.to_string());
-
-        if let Some(description) = &req.description {
-            cmd.arg("--description").arg(description);
-        }
-
-        let output = cmd
-            .output()
-            .context("Failed to update port")?;
-
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        } else {
-            Err(anyhow::anyhow!(
-                "Failed to update port: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to show port", &output))
         }
     }
 
     // Update port by recreating it (needed for protocol changes)
-    pub fn update_port(&self, req: UpdatePortRequest) -> Result<String> {
+    async fn update_port(&self, req: UpdatePortRequest) -> Result<String> {
         // If protocol is specified and different, we need to recreate the port
         if let Some(new_protocol) = &req.protocol {
             // Get current port details to compare
-            if let Ok(current_port) = self.show_port(req.tunnel_id.clone(), req.port_number) {
+            if let Ok(current_port) = self.show_port(req.tunnel_id.clone(), req.port_number).await {
                 if &current_port.protocol != new_protocol {
                     // Protocol changed - need to delete and recreate
-                    self.delete_port(req.tunnel_id.clone(), req.port_number)?;
+                    self.delete_port(req.tunnel_id.clone(), req.port_number).await?;
 
                     // Recreate with new protocol
                     return self.create_port(CreatePortRequest {
@@ -491,119 +417,110 @@ impl DevTunnelClient {
                         port_number: req.port_number,
                         protocol: Some(new_protocol.clone()),
                         description: req.description.clone(),
-                    });
+                    }).await;
                 }
             }
         }
 
         // No protocol change, just update description
-        self.update_port_description(req)
+        self.update_port_description(req).await
     }
 
-    pub fn delete_port(&self, tunnel_id: String, port: u16) -> Result<String> {
-        let output = self
-            .build_command()
-            .arg("port")
-            .arg("delete")
-            .arg(tunnel_id)
-            .arg("-p")
-            .arg(port.to_string())
-            .output()
-            .context("Failed to delete port")?;
+    async fn delete_port(&self, tunnel_id: String, port: u16) -> Result<String> {
+        let mut cmd = self.build_command();
+        cmd.arg("port").arg("delete").arg(tunnel_id).arg("-p").arg(port.to_string());
+        let output = self.executor.output(&mut cmd, "Failed to delete port").await?;
 
         if output.status.success() {
             Ok("Port deleted successfully".to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to delete port: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to delete port", &output))
         }
     }
 
     // Access Control
-    pub fn create_access(&self, req: CreateAccessRequest) -> Result<String> {
+    async fn create_access(&self, req: CreateAccessRequest) -> Result<String> {
         let mut cmd = self.build_command();
         cmd.arg("access")
             .arg("create")
-            .arg(&req.tunnel_id);
-
-        match req.entry.entry_type.as_str() {
-            "anonymous" => {
-                cmd.arg("--anonymous");
-            }
-            "organization" => {
-                if let Some(org_id) = &req.entry.organization_id {
-                    cmd.arg("--org").arg(org_id);
-                }
-            }
-            _ => {}
-        }
+            .arg(&req.tunnel_id)
+            .args(access::create_args(&req.entry)?);
 
-        if let Some(ports) = &req.entry.ports {
-            for port in ports {
-                cmd.arg("-p").arg(port.to_string());
-            }
-        }
+        let output = self.executor.output(&mut cmd, "Failed to create access").await?;
 
-        if let Some(expiration) = &req.entry.expiration {
-            cmd.arg("--expiration").arg(expiration);
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(cli_error("Failed to create access", &output))
         }
+    }
 
-        let output = cmd
-            .output()
-            .context("Failed to create access")?;
+    async fn list_access(&self, tunnel_id: String) -> Result<String> {
+        let mut cmd = self.build_command();
+        cmd.arg("access").arg("list").arg(tunnel_id);
+        let output = self.executor.output(&mut cmd, "Failed to list access").await?;
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to create access: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to list access", &output))
         }
     }
 
-    pub fn list_access(&self, tunnel_id: String) -> Result<String> {
-        let output = self
-            .build_command()
-            .arg("access")
-            .arg("list")
-            .arg(tunnel_id)
-            .output()
-            .context("Failed to list access")?;
+    async fn list_access_entries(&self, tunnel_id: String) -> Result<Vec<AccessEntry>> {
+        let mut cmd = self.build_command();
+        cmd.arg("access").arg("list").arg(&tunnel_id);
+
+        if let Some(entries) = self.run_json(cmd, "Failed to list access", parser::parse_access_list_json).await? {
+            return Ok(entries);
+        }
+
+        let text = self.list_access(tunnel_id).await?;
+        Ok(parser::parse_access_list(&text))
+    }
+
+    async fn delete_access(&self, req: DeleteAccessRequest) -> Result<String> {
+        let mut cmd = self.build_command();
+        cmd.arg("access").arg("delete").arg(&req.tunnel_id).args(access::subject_args(&req.entry)?);
+        let output = self.executor.output(&mut cmd, "Failed to delete access").await?;
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to list access: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to delete access", &output))
         }
     }
 
-    pub fn reset_access(&self, tunnel_id: String) -> Result<String> {
-        let output = self
-            .build_command()
-            .arg("access")
-            .arg("reset")
-            .arg(tunnel_id)
-            .output()
-            .context("Failed to reset access")?;
+    async fn reset_access(&self, tunnel_id: String) -> Result<String> {
+        let mut cmd = self.build_command();
+        cmd.arg("access").arg("reset").arg(tunnel_id);
+        let output = self.executor.output(&mut cmd, "Failed to reset access").await?;
 
         if output.status.success() {
             Ok("Access reset successfully".to_string())
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to reset access: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to reset access", &output))
+        }
+    }
+
+    // Tokens
+    async fn issue_token(&self, tunnel_id: String, scopes: Vec<String>, port: Option<u16>) -> Result<String> {
+        let mut cmd = self.build_command();
+        cmd.arg("token").arg(&tunnel_id).arg("--scopes").args(&scopes);
+        if let Some(port) = port {
+            cmd.arg("-p").arg(port.to_string());
+        }
+        let output = self.executor.output(&mut cmd, "Failed to issue token").await?;
+
+        if !output.status.success() {
+            return Err(cli_error("Failed to issue token", &output));
         }
+        parser::parse_token_output(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| anyhow::anyhow!("Failed to issue token: no token in CLI output"))
     }
 
     // Clusters
-    pub fn list_clusters(&self, ping: bool) -> Result<Vec<Cluster>> {
+    async fn list_clusters(&self, ping: bool) -> Result<Vec<Cluster>> {
         let mut cmd = self.build_command();
         cmd.arg("clusters");
         cmd.arg("-j");  // JSON output
@@ -612,33 +529,20 @@ impl DevTunnelClient {
             cmd.arg("--ping");
         }
 
-        let output = cmd
-            .output()
-            .context("Failed to list clusters")?;
+        let output = self.executor.output(&mut cmd, "Failed to list clusters").await?;
 
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
-
-            // Try to parse as JSON
-            match serde_json::from_str::<Vec<Cluster>>(&stdout) {
-                Ok(clusters) => Ok(clusters),
-                Err(e) => {
-                    // If JSON parsing fails, return empty vec with warning
-                    eprintln!("Failed to parse clusters JSON: {}", e);
-                    Ok(Vec::new())
-                }
-            }
+            serde_json::from_str::<Vec<Cluster>>(&stdout).context("Failed to parse clusters")
         } else {
-            Err(anyhow::anyhow!(
-                "Failed to list clusters: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+            Err(cli_error("Failed to list clusters", &output))
         }
     }
 
-    // Host tunnel (async operation)
-    pub async fn host_tunnel(&self, req: HostTunnelRequest) -> Result<()> {
-        let mut cmd = self.build_tokio_command();
+    // Build the `devtunnel host` command; spawning and supervision live in supervisor.rs
+    fn host_command(&self, req: &HostTunnelRequest) -> Command {
+        // Not build_command(): the supervisor owns this process beyond any one call
+        let mut cmd = Command::new(&self.binary_path);
         cmd.arg("host");
 
         if let Some(tunnel_id) = &req.tunnel_id {
@@ -661,264 +565,21 @@ impl DevTunnelClient {
 
         // Don't pass protocol either - it's set when creating the port
 
-        // Use null() instead of piped() to discard output
-        // This prevents the process from blocking when buffers fill up
-        cmd.stdout(Stdio::null())
-            .stderr(Stdio::null());
-
-        let mut child = cmd.spawn()
-            .context("Failed to spawn devtunnel host")?;
-
-        // Give it a moment to start
-        tokio::time::sleep(tokio::time::Duration::from_millis(PROCESS_START_DELAY_MS)).await;
-
-        // Check if process is still running
-        match child.try_wait() {
-            Ok(Some(status)) => {
-                return Err(anyhow::anyhow!(
-                    "devtunnel host process exited immediately with status: {}",
-                    status
-                ));
-            }
-            Ok(None) => {
-                // Process is still running, store its PID for tracking
-                if let Some(id) = &req.tunnel_id {
-                    if let Some(pid) = child.id() {
-                        let mut processes = self.active_processes.lock().unwrap();
-                        processes.insert(id.clone(), pid);
-                    }
-                }
-                // Child will be dropped here, but the process continues running
-                // because stdout/stderr are set to null (not piped)
-            }
-            Err(e) => {
-                return Err(anyhow::anyhow!("Failed to check process status: {}", e));
-            }
-        }
-
-        Ok(())
-    }
-
-    #[allow(dead_code)]
-    pub fn get_active_hosts(&self) -> Vec<String> {
-        self.active_processes
-            .lock()
-            .unwrap()
-            .keys()
-            .cloned()
-            .collect()
-    }
-
-    // Stop hosting tunnel by killing the devtunnel host process
-    pub fn stop_tunnel(&self, tunnel_id: String) -> Result<String> {
-        // SECURITY: Validate tunnel_id to prevent command injection
-        // Tunnel IDs should only contain alphanumeric characters, dots, hyphens, and underscores
-        // This prevents wildcards, shell metacharacters, and other potentially dangerous inputs
-        let valid_id = regex::Regex::new(r"^[a-zA-Z0-9.\-_]+$").unwrap();
-        if !valid_id.is_match(&tunnel_id) {
-            return Err(anyhow::anyhow!(
-                "Invalid tunnel ID format: contains potentially dangerous characters. \
-                Only alphanumeric, dots, hyphens, and underscores are allowed."
-            ));
-        }
-
-        #[cfg(target_os = "linux")]
-        {
-            let output = Command::new("pkill")
-                .arg("-f")
-                .arg(format!("devtunnel host {}", tunnel_id))
-                .output()
-                .context("Failed to execute pkill")?;
-
-            // Remove from active process tracking
-            let mut processes = self.active_processes.lock().unwrap();
-            processes.remove(&tunnel_id);
-
-            // pkill returns 0 if processes were killed, 1 if no processes matched
-            if output.status.success() {
-                Ok(format!("Tunnel {} stopped successfully", tunnel_id))
-            } else {
-                // Even if pkill returns 1, it might mean the process was already stopped
-                Ok(format!("No active host process found for tunnel {}", tunnel_id))
-            }
-        }
-
-        #[cfg(target_os = "macos")]
-        {
-            let output = Command::new("pkill")
-                .arg("-f")
-                .arg(format!("devtunnel host {}", tunnel_id))
-                .output()
-                .context("Failed to execute pkill")?;
-
-            let mut processes = self.active_processes.lock().unwrap();
-            processes.remove(&tunnel_id);
-
-            if output.status.success() {
-                Ok(format!("Tunnel {} stopped successfully", tunnel_id))
-            } else {
-                Ok(format!("No active host process found for tunnel {}", tunnel_id))
-            }
-        }
-
-        #[cfg(target_os = "windows")]
-        {
-            // Use taskkill on Windows
-            // SECURITY: tunnel_id has been validated with regex above to prevent injection
-            // Only alphanumeric, dots, hyphens, and underscores are allowed
-            let output = Command::new("taskkill")
-                .arg("/F")
-                .arg("/FI")
-                .arg(format!("COMMANDLINE eq *devtunnel host {}*", tunnel_id))
-                .output()
-                .context("Failed to execute taskkill")?;
-
-            let mut processes = self.active_processes.lock().unwrap();
-            processes.remove(&tunnel_id);
-
-            if output.status.success() {
-                Ok(format!("Tunnel {} stopped successfully", tunnel_id))
-            } else {
-                Ok(format!("No active host process found for tunnel {}", tunnel_id))
-            }
-        }
+        cmd
     }
 
-    // Restart tunnel by stopping and restarting
-    pub async fn restart_tunnel(&self, req: HostTunnelRequest) -> Result<String> {
-        if let Some(tunnel_id) = req.tunnel_id.clone() {
-            // Stop the existing process
-            let _ = self.stop_tunnel(tunnel_id.clone());
-
-            // Wait a moment for the process to fully terminate
-            tokio::time::sleep(tokio::time::Duration::from_millis(PROCESS_STOP_DELAY_MS)).await;
-
-            // Start hosting again
-            self.host_tunnel(req).await?;
-
-            Ok(format!("Tunnel {} restarted successfully", tunnel_id))
-        } else {
-            Err(anyhow::anyhow!("Tunnel ID is required for restart"))
-        }
-    }
-
-    // Get tunnel host process start time
-    pub fn get_tunnel_start_time(&self, tunnel_id: String) -> Result<String> {
-        #[cfg(any(target_os = "linux", target_os = "macos"))]
-        {
-            // Use ps to find the process and get its start time
-            let output = Command::new("ps")
-                .arg("-eo")
-                .arg("pid,lstart,cmd")
-                .output()
-                .context("Failed to execute ps")?;
-
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-
-                // Look for lines containing "devtunnel host {tunnel_id}"
-                for line in stdout.lines() {
-                    if line.contains("devtunnel") && line.contains("host") && line.contains(&tunnel_id) {
-                        // Parse the line: PID STARTED CMD
-                        // Example: 12345 Mon Jan 15 14:30:25 2024 /home/user/bin/devtunnel host my-tunnel
-                        let parts: Vec<&str> = line.split_whitespace().collect();
-
-                        if parts.len() >= 6 {
-                            // Extract the start time (indices 1-5 typically contain the lstart format)
-                            let start_time = parts[1..6].join(" ");
-                            return Ok(start_time);
-                        }
-                    }
-                }
-
-                Err(anyhow::anyhow!("No active host process found for tunnel {}", tunnel_id))
-            } else {
-                Err(anyhow::anyhow!("Failed to get process information"))
-            }
-        }
-
-        #[cfg(target_os = "windows")]
-        {
-            // Use WMIC on Windows to get process start time
-            let output = Command::new("wmic")
-                .arg("process")
-                .arg("where")
-                .arg(format!("CommandLine like '%devtunnel host {}%'", tunnel_id))
-                .arg("get")
-                .arg("CreationDate")
-                .output()
-                .context("Failed to execute wmic")?;
-
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-
-                // Parse WMIC output (format: YYYYMMDDHHMMSS.mmmmmm+ZZZ)
-                for line in stdout.lines().skip(1) {
-                    let trimmed = line.trim();
-                    if !trimmed.is_empty() {
-                        // Convert Windows datetime format to readable format
-                        if trimmed.len() >= 14 {
-                            let year = &trimmed[0..4];
-                            let month = &trimmed[4..6];
-                            let day = &trimmed[6..8];
-                            let hour = &trimmed[8..10];
-                            let minute = &trimmed[10..12];
-                            let second = &trimmed[12..14];
-
-                            return Ok(format!("{}-{}-{} {}:{}:{}", year, month, day, hour, minute, second));
-                        }
-                    }
-                }
+    // Build the `devtunnel connect` command; spawning and supervision live in connect.rs
+    fn connect_command(&self, req: &ConnectTunnelRequest) -> Command {
+        let mut cmd = Command::new(&self.binary_path);
+        cmd.arg("connect").arg(&req.tunnel_id);
 
-                Err(anyhow::anyhow!("No active host process found for tunnel {}", tunnel_id))
-            } else {
-                Err(anyhow::anyhow!("Failed to get process information"))
-            }
+        // The CLI only accepts the token as an argument (there is no env var or
+        // stdin form), so other local users can read it from the process list
+        // while the connection runs. Short-lived connect tokens limit the exposure.
+        if let Some(token) = &req.access_token {
+            cmd.arg("--access-token").arg(token);
         }
-    }
-
-    // Ping a port URL to check if it's reachable
-    pub fn ping_port(&self, url: String) -> Result<PingResult> {
-        use std::time::Instant;
 
-        let start = Instant::now();
-
-        let output = Command::new("curl")
-            .arg("-s")
-            .arg("-o")
-            .arg("/dev/null")
-            .arg("-w")
-            .arg("%{http_code}")
-            .arg("--max-time")
-            .arg("5")
-            .arg(&url)
-            .output()
-            .context("Failed to execute curl")?;
-
-        let elapsed = start.elapsed();
-        let status_code = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-        if output.status.success() {
-            let code: u16 = status_code.parse().unwrap_or(0);
-
-            Ok(PingResult {
-                success: code > 0 && code < 600,
-                status_code: Some(code),
-                response_time_ms: elapsed.as_millis() as u64,
-                error: None,
-            })
-        } else {
-            let error_msg = String::from_utf8_lossy(&output.stderr).to_string();
-            Ok(PingResult {
-                success: false,
-                status_code: None,
-                response_time_ms: elapsed.as_millis() as u64,
-                error: Some(if error_msg.is_empty() {
-                    "Connection failed".to_string()
-                } else {
-                    error_msg
-                }),
-            })
-        }
+        cmd
     }
 }
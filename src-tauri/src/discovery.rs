@@ -0,0 +1,239 @@
+// Discovery of local services to suggest as tunnel ports. Listening TCP
+// sockets come from /proc/net/tcp and tcp6, their owners from the socket
+// links in /proc/<pid>/fd (only our own processes are readable), and the
+// protocol is guessed by trying HTTPS and then HTTP against the port.
+use crate::health;
+use crate::types::*;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use tokio::task::JoinSet;
+
+const PROBE_TIMEOUT_MS: u64 = 1500;
+const TCP_LISTEN: &str = "0A"; // Socket state column value for LISTEN
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListeningSocket {
+    pub address: IpAddr,
+    pub port: u16,
+    pub inode: u64,
+}
+
+// "0100007F:1F90" -> 127.0.0.1:8080. The kernel prints each 32-bit word of
+// the address in host byte order.
+fn parse_address(field: &str) -> Option<(IpAddr, u16)> {
+    let (addr, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let words: Vec<u32> = (0..addr.len())
+        .step_by(8)
+        .map(|i| addr.get(i..i + 8).and_then(|w| u32::from_str_radix(w, 16).ok()))
+        .collect::<Option<_>>()?;
+
+    let address = match words.as_slice() {
+        [w] => IpAddr::V4(Ipv4Addr::from(w.to_ne_bytes())),
+        [a, b, c, d] => {
+            let mut bytes = [0u8; 16];
+            for (i, w) in [a, b, c, d].iter().enumerate() {
+                bytes[i * 4..i * 4 + 4].copy_from_slice(&w.to_ne_bytes());
+            }
+            IpAddr::V6(Ipv6Addr::from(bytes))
+        }
+        _ => return None,
+    };
+    Some((address, port))
+}
+
+// Listening sockets from the contents of /proc/net/tcp or /proc/net/tcp6
+pub fn parse_proc_net_tcp(content: &str) -> Vec<ListeningSocket> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.get(3) != Some(&TCP_LISTEN) {
+                return None;
+            }
+            let (address, port) = parse_address(fields.get(1)?)?;
+            let inode = fields.get(9)?.parse().ok()?;
+            Some(ListeningSocket { address, port, inode })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn listening_sockets() -> Result<Vec<ListeningSocket>> {
+    let mut sockets = parse_proc_net_tcp(&std::fs::read_to_string("/proc/net/tcp")?);
+    // IPv6 may be disabled
+    if let Ok(content) = std::fs::read_to_string("/proc/net/tcp6") {
+        sockets.extend(parse_proc_net_tcp(&content));
+    }
+    Ok(sockets)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn listening_sockets() -> Result<Vec<ListeningSocket>> {
+    Err(anyhow::anyhow!("Port discovery is only supported on Linux"))
+}
+
+// Socket inode -> (pid, process name) for every process we may inspect
+#[cfg(target_os = "linux")]
+fn socket_owners() -> HashMap<u64, (u32, String)> {
+    let mut owners = HashMap::new();
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return owners;
+    };
+
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        // Other users' processes are not readable; they stay unnamed
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let name = std::fs::read_to_string(entry.path().join("comm"))
+            .map(|comm| comm.trim().to_string())
+            .unwrap_or_default();
+
+        for fd in fds.flatten() {
+            let inode = std::fs::read_link(fd.path()).ok().and_then(|target| {
+                target
+                    .to_str()?
+                    .strip_prefix("socket:[")?
+                    .strip_suffix(']')?
+                    .parse::<u64>()
+                    .ok()
+            });
+            if let Some(inode) = inode {
+                owners.entry(inode).or_insert_with(|| (pid, name.clone()));
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(not(target_os = "linux"))]
+fn socket_owners() -> HashMap<u64, (u32, String)> {
+    HashMap::new()
+}
+
+// Https if a TLS handshake and HTTP exchange work, Http if plain HTTP does,
+// None for anything else. HTTPS goes first because some TLS servers answer
+// plain HTTP with an error page of their own.
+pub async fn guess_protocol(port: u16) -> Option<Protocol> {
+    let options = HealthCheckOptions {
+        method: Some("HEAD".to_string()),
+        verify_tls: Some(false),
+        timeout_ms: Some(PROBE_TIMEOUT_MS),
+        ..Default::default()
+    };
+
+    for (scheme, protocol) in [("https", Protocol::Https), ("http", Protocol::Http)] {
+        let answered = health::check(&format!("{}://localhost:{}/", scheme, port), &options)
+            .await
+            .map(|result| result.status_code.is_some())
+            .unwrap_or(false);
+        if answered {
+            return Some(protocol);
+        }
+    }
+    None
+}
+
+// Local services worth offering as tunnel ports, one per port number, minus
+// `exclude` (the tunnel's existing ports). `probe` adds the protocol guess.
+pub async fn discover(exclude: &[u16], probe: bool) -> Result<Vec<DiscoveredPort>> {
+    // Walking /proc reads every fd of every process; keep it off the async workers
+    let (sockets, owners) = tokio::task::spawn_blocking(|| listening_sockets().map(|sockets| (sockets, socket_owners())))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|r| r)?;
+    let mut found: HashMap<u16, DiscoveredPort> = HashMap::new();
+
+    for socket in sockets {
+        if exclude.contains(&socket.port) {
+            continue;
+        }
+        let owner = owners.get(&socket.inode);
+        let port = found.entry(socket.port).or_insert_with(|| DiscoveredPort {
+            port_number: socket.port,
+            addresses: Vec::new(),
+            pid: None,
+            process: None,
+            protocol: None,
+        });
+        let address = socket.address.to_string();
+        if !port.addresses.contains(&address) {
+            port.addresses.push(address);
+        }
+        if port.pid.is_none() {
+            port.pid = owner.map(|(pid, _)| *pid);
+            port.process = owner.map(|(_, name)| name.clone());
+        }
+    }
+
+    let mut ports: Vec<DiscoveredPort> = found.into_values().collect();
+    if probe {
+        let mut probes = JoinSet::new();
+        for port in ports {
+            probes.spawn(async move {
+                let protocol = guess_protocol(port.port_number).await;
+                DiscoveredPort { protocol, ..port }
+            });
+        }
+        ports = probes.join_all().await;
+    }
+    ports.sort_by_key(|p| p.port_number);
+    Ok(ports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41273 1 0000000000000000 100 0 0 10 0
+   1: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 18001 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:1F90 0100007F:D2C4 01 00000000:00000000 00:00000000 00000000  1000        0 41299 1 0000000000000000 20 4 30 10 -1
+";
+
+    const TCP6: &str = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:0BB8 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 52110 1 0000000000000000 100 0 0 10 0
+";
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn test_parse_proc_net_tcp() {
+        assert_eq!(parse_proc_net_tcp(TCP), vec![
+            ListeningSocket { address: "127.0.0.1".parse().unwrap(), port: 8080, inode: 41273 },
+            ListeningSocket { address: "0.0.0.0".parse().unwrap(), port: 22, inode: 18001 },
+        ]);
+        assert_eq!(parse_proc_net_tcp(TCP6), vec![
+            ListeningSocket { address: "::1".parse().unwrap(), port: 3000, inode: 52110 },
+        ]);
+        assert!(parse_proc_net_tcp("garbage\n   0: nonsense").is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg(target_os = "linux")]
+    async fn test_discovers_own_listener() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                let _ = socket.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").await;
+            }
+        });
+
+        let found = discover(&[], false).await.unwrap();
+        let ours = found.iter().find(|p| p.port_number == port).unwrap();
+        assert_eq!(ours.addresses, vec!["127.0.0.1".to_string()]);
+        assert_eq!(ours.pid, Some(std::process::id()));
+        assert_eq!(guess_protocol(port).await, Some(Protocol::Http));
+
+        assert!(discover(&[port], false).await.unwrap().iter().all(|p| p.port_number != port));
+    }
+}
@@ -0,0 +1,182 @@
+// Typed failures for the devtunnel CLI. Backends return anyhow::Error as before;
+// a DevTunnelError inside it carries the classification up to CommandResponse.
+use crate::types::ErrorCode;
+use std::fmt;
+use std::process::Output;
+
+#[derive(Debug, Clone)]
+pub struct DevTunnelError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl DevTunnelError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    // Failed CLI run: "<context>: <stderr>", classified from stderr and exit code
+    pub fn from_output(context: &str, output: &Output) -> Self {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = if stderr.trim().is_empty() {
+            String::from_utf8_lossy(&output.stdout).to_string()
+        } else {
+            stderr.to_string()
+        };
+
+        let code = match output.status.code() {
+            // Shell wrappers exit 127 when the real binary is gone
+            Some(127) => ErrorCode::BinaryMissing,
+            _ => classify(&detail),
+        };
+        Self::new(code, format!("{}: {}", context, detail.trim()))
+    }
+
+    pub fn hint(&self) -> Option<&'static str> {
+        hint(self.code)
+    }
+}
+
+impl fmt::Display for DevTunnelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DevTunnelError {}
+
+// Shorthand for the `Err(...)` arm of a failed CLI run
+pub fn cli_error(context: &str, output: &Output) -> anyhow::Error {
+    DevTunnelError::from_output(context, output).into()
+}
+
+// The devtunnel binary could not be started at all
+pub fn spawn_error(context: &str, error: std::io::Error) -> anyhow::Error {
+    let code = match error.kind() {
+        std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied => ErrorCode::BinaryMissing,
+        _ => ErrorCode::Unknown,
+    };
+    DevTunnelError::new(code, format!("{}: failed to execute devtunnel: {}", context, error)).into()
+}
+
+// Match CLI / service messages. Order matters: "login required" on a missing
+// tunnel is still an auth problem, and "request timed out" is not a network
+// outage the user can fix. Bare HTTP status numbers are left out since port
+// numbers in the same messages would match them, and bare "ssl" / "tls" since
+// tunnel IDs and hostnames may contain them.
+pub fn classify(message: &str) -> ErrorCode {
+    const PATTERNS: &[(ErrorCode, &[&str])] = &[
+        (
+            ErrorCode::AuthRequired,
+            &["not authenticated", "not logged in", "login required", "unauthorized", "sign in", "token expired", "authentication"],
+        ),
+        (
+            ErrorCode::QuotaExceeded,
+            &["quota", "limit exceeded", "limit reached", "too many requests", "rate limit", "throttl"],
+        ),
+        (ErrorCode::Timeout, &["timed out", "timeout"]),
+        (
+            ErrorCode::Network,
+            &[
+                "network", "connection refused", "connection reset", "could not resolve", "name resolution", "no such host",
+                "unreachable", "ssl connection", "ssl error", "ssl handshake", "tls handshake", "tls error",
+            ],
+        ),
+        (ErrorCode::Conflict, &["already exists", "conflict", "already in use"]),
+        (ErrorCode::NotFound, &["not found", "does not exist", "no such tunnel"]),
+        (
+            ErrorCode::InvalidArgument,
+            &["invalid", "unrecognized", "required argument", "usage:", "bad request", "must be"],
+        ),
+    ];
+
+    let lower = message.to_lowercase();
+    PATTERNS
+        .iter()
+        .find(|(_, needles)| needles.iter().any(|n| lower.contains(n)))
+        .map(|(code, _)| *code)
+        .unwrap_or(ErrorCode::Unknown)
+}
+
+// Transient throttling worth retrying, as opposed to a hard quota such as the
+// tunnel limit, which classify() also reports as QuotaExceeded
+pub fn is_throttled(message: &str) -> bool {
+    const NEEDLES: &[&str] = &["too many requests", "rate limit", "throttl", "retry after"];
+    let lower = message.to_lowercase();
+    NEEDLES.iter().any(|n| lower.contains(n))
+}
+
+// The DevTunnelError in the chain if there is one, else a best guess from the
+// message (fake backend, spec files, supervisor)
+pub fn error_code(error: &anyhow::Error) -> ErrorCode {
+    for cause in error.chain() {
+        if let Some(e) = cause.downcast_ref::<DevTunnelError>() {
+            return e.code;
+        }
+        if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+            match e.kind() {
+                std::io::ErrorKind::TimedOut => return ErrorCode::Timeout,
+                // Spawn failures are already BinaryMissing via spawn_error(); what's
+                // left is a missing file, which says nothing about tunnels, so the
+                // "not found" in its message must not be read as one
+                std::io::ErrorKind::NotFound => return ErrorCode::Unknown,
+                _ => {}
+            }
+        }
+    }
+    classify(&format!("{:#}", error))
+}
+
+pub fn hint(code: ErrorCode) -> Option<&'static str> {
+    match code {
+        ErrorCode::AuthRequired => Some("Sign in again (devtunnel user login) and retry."),
+        ErrorCode::NotFound => Some("The tunnel or port may have been deleted or expired; refresh the list."),
+        ErrorCode::Conflict => Some("Pick a different tunnel ID or port, or update the existing one."),
+        ErrorCode::QuotaExceeded => Some("Delete unused tunnels or ports, or wait a moment before retrying."),
+        ErrorCode::InvalidArgument => Some("Check the values you entered; the devtunnel CLI rejected them."),
+        ErrorCode::BinaryMissing => Some("Install the devtunnel CLI or set DEVTUNNEL_BIN to its path."),
+        ErrorCode::Timeout => Some("The devtunnel service did not answer in time; try again."),
+        ErrorCode::Network => Some("Check your internet connection or proxy settings."),
+        ErrorCode::Cancelled => None,
+        ErrorCode::Unknown => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("Login required. Run devtunnel user login"), ErrorCode::AuthRequired);
+        assert_eq!(classify("Tunnel not found: demo"), ErrorCode::NotFound);
+        assert_eq!(classify("Tunnel already exists: demo"), ErrorCode::Conflict);
+        assert_eq!(classify("Maximum tunnel limit reached (10)"), ErrorCode::QuotaExceeded);
+        assert_eq!(classify("Unrecognized command or argument '--bogus'"), ErrorCode::InvalidArgument);
+        assert_eq!(classify("The operation timed out"), ErrorCode::Timeout);
+        assert_eq!(classify("No such host is known (global.rel.tunnels.api.visualstudio.com)"), ErrorCode::Network);
+        assert_eq!(classify("The SSL connection could not be established"), ErrorCode::Network);
+        assert_eq!(classify("Received fatal alert during TLS handshake"), ErrorCode::Network);
+        assert_eq!(classify("Tunnel not found: mtls-demo"), ErrorCode::NotFound);
+        assert_eq!(classify("something odd"), ErrorCode::Unknown);
+
+        assert!(is_throttled("429 Too Many Requests. Retry after 5 seconds"));
+        assert!(!is_throttled("Maximum tunnel limit reached (10)"));
+    }
+
+    #[test]
+    fn test_error_code_from_chain() {
+        let typed: anyhow::Error = DevTunnelError::new(ErrorCode::Conflict, "Port 3000 is taken").into();
+        assert_eq!(error_code(&typed.context("Failed to create port")), ErrorCode::Conflict);
+
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        let spawn = spawn_error("Failed to list tunnels", missing).context("Refresh failed");
+        assert_eq!(error_code(&spawn), ErrorCode::BinaryMissing);
+
+        let spec = anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::NotFound)).context("Failed to read spec file");
+        assert_eq!(error_code(&spec), ErrorCode::Unknown);
+    }
+}
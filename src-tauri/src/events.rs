@@ -0,0 +1,31 @@
+use crate::commands::Logger;
+use crate::types::LogLevel;
+use serde::Serialize;
+use tauri::Emitter;
+
+// Destination for backend events. The GUI forwards them to the webview through
+// tauri::Emitter; background tasks only hold the trait object.
+pub trait EventSink: Send + Sync {
+    fn emit_event(&self, event: &str, payload: serde_json::Value);
+
+    // Failures a background task has no caller to return to, such as a stop
+    // signal that could not be sent or a session file that could not be saved
+    fn log(&self, level: LogLevel, source: &'static str, tunnel_id: Option<&str>, message: &str);
+}
+
+impl<R: tauri::Runtime> EventSink for tauri::AppHandle<R> {
+    fn emit_event(&self, event: &str, payload: serde_json::Value) {
+        let _ = self.emit(event, payload);
+    }
+
+    fn log(&self, level: LogLevel, source: &'static str, tunnel_id: Option<&str>, message: &str) {
+        Logger::new(self, source, tunnel_id.map(str::to_string)).log(level, message);
+    }
+}
+
+// Serialize a typed payload and send it through the sink
+pub fn emit<T: Serialize>(sink: &dyn EventSink, event: &str, payload: &T) {
+    if let Ok(value) = serde_json::to_value(payload) {
+        sink.emit_event(event, value);
+    }
+}
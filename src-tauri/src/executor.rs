@@ -0,0 +1,192 @@
+// Gate for every one-shot devtunnel CLI process. Parallel callers (enriching a
+// 60-tunnel list, the control socket, a spec apply) share one semaphore so the
+// service never sees more than `max_parallel` requests from us at once, and a
+// throttled call is retried with exponential backoff plus jitter.
+use crate::error::{is_throttled, spawn_error, DevTunnelError};
+use crate::types::*;
+use anyhow::Result;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::path::PathBuf;
+use std::process::Output;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+
+pub const LIMITS_FILE_NAME: &str = "cli-limits.json";
+
+impl Default for CliLimits {
+    fn default() -> Self {
+        Self {
+            max_parallel: 4,
+            max_retries: 3,
+            retry_base_ms: 500,
+        }
+    }
+}
+
+impl CliLimits {
+    // Wait before retry number `attempt` (0-based)
+    fn backoff(&self, attempt: u32) -> Duration {
+        let base = self.retry_base_ms.saturating_mul(1 << attempt.min(10));
+        Duration::from_millis(base + jitter(base))
+    }
+}
+
+// Uniform-ish in 0..=max; std's hasher keys are random per RandomState
+fn jitter(max: u64) -> u64 {
+    if max == 0 {
+        return 0;
+    }
+    RandomState::new().build_hasher().finish() % (max + 1)
+}
+
+pub struct CliExecutor {
+    path: Option<PathBuf>,
+    limits: Mutex<CliLimits>,
+    // Swapped out when max_parallel changes; calls already holding or waiting
+    // for a permit finish against the old one
+    semaphore: Mutex<Arc<Semaphore>>,
+}
+
+impl Default for CliExecutor {
+    fn default() -> Self {
+        Self::load(None)
+    }
+}
+
+impl CliExecutor {
+    // Limits are read from (and saved to) `path`; None keeps them in memory
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let limits: CliLimits = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .filter(|limits: &CliLimits| limits.max_parallel > 0)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            semaphore: Mutex::new(Arc::new(Semaphore::new(limits.max_parallel))),
+            limits: Mutex::new(limits),
+        }
+    }
+
+    pub fn limits(&self) -> CliLimits {
+        self.limits.lock().unwrap().clone()
+    }
+
+    pub fn set_limits(&self, limits: CliLimits) -> Result<()> {
+        if limits.max_parallel == 0 {
+            return Err(DevTunnelError::new(ErrorCode::InvalidArgument, "maxParallel must be at least 1").into());
+        }
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, serde_json::to_string_pretty(&limits)?)?;
+        }
+
+        let mut current = self.limits.lock().unwrap();
+        if current.max_parallel != limits.max_parallel {
+            *self.semaphore.lock().unwrap() = Arc::new(Semaphore::new(limits.max_parallel));
+        }
+        *current = limits;
+        Ok(())
+    }
+
+    // Run `cmd` to completion once a slot is free. While the service answers
+    // with throttling the command is run again after a backoff (without holding
+    // a slot); any other result, success or not, is returned as is.
+    pub async fn output(&self, cmd: &mut Command, context: &str) -> Result<Output> {
+        let limits = self.limits();
+        let mut attempt = 0;
+
+        loop {
+            let semaphore = self.semaphore.lock().unwrap().clone();
+            let output = {
+                let _permit = semaphore.acquire().await?;
+                cmd.output().await.map_err(|e| spawn_error(context, e))?
+            };
+
+            let throttled = !output.status.success()
+                && (is_throttled(&String::from_utf8_lossy(&output.stderr))
+                    || is_throttled(&String::from_utf8_lossy(&output.stdout)));
+            if !throttled || attempt >= limits.max_retries {
+                return Ok(output);
+            }
+
+            tokio::time::sleep(limits.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn sh(script: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(script);
+        cmd
+    }
+
+    #[tokio::test]
+    async fn test_parallelism_is_bounded() {
+        let executor = Arc::new(CliExecutor::default());
+        executor
+            .set_limits(CliLimits {
+                max_parallel: 2,
+                ..CliLimits::default()
+            })
+            .unwrap();
+
+        let start = Instant::now();
+        let mut set = tokio::task::JoinSet::new();
+        for _ in 0..4 {
+            let executor = executor.clone();
+            set.spawn(async move { executor.output(&mut sh("sleep 0.2"), "sleep").await });
+        }
+        while let Some(result) = set.join_next().await {
+            assert!(result.unwrap().unwrap().status.success());
+        }
+        // Two rounds of two
+        assert!(start.elapsed() >= Duration::from_millis(400));
+        assert!(executor.set_limits(CliLimits { max_parallel: 0, ..CliLimits::default() }).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_throttled_calls_are_retried() {
+        let dir = std::env::temp_dir().join(format!("devtunnel-gui-executor-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let counter = dir.join("attempts");
+        let _ = std::fs::remove_file(&counter);
+
+        let executor = CliExecutor::default();
+        executor
+            .set_limits(CliLimits {
+                retry_base_ms: 1,
+                ..CliLimits::default()
+            })
+            .unwrap();
+
+        // Throttled twice, then succeeds
+        let script = format!(
+            "n=$(cat {0} 2>/dev/null || echo 0); echo $((n+1)) > {0}; \
+             [ $n -ge 2 ] || {{ echo 'Too many requests' >&2; exit 1; }}",
+            counter.display()
+        );
+        let output = executor.output(&mut sh(&script), "list").await.unwrap();
+        assert!(output.status.success());
+        assert_eq!(std::fs::read_to_string(&counter).unwrap().trim(), "3");
+
+        // Other failures are returned without retrying
+        let output = executor.output(&mut sh("echo 'Tunnel not found' >&2; exit 1"), "show").await.unwrap();
+        assert!(!output.status.success());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
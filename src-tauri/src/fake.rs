@@ -0,0 +1,627 @@
+// In-memory stand-in for the devtunnel CLI, used by the integration tests in
+// tests/ so tunnel, port and access flows can run without the Microsoft binary
+// or an account. Output strings mimic the real CLI closely enough for parser.rs.
+use crate::access;
+use crate::backend::DevTunnelBackend;
+use crate::parser;
+use crate::types::*;
+use anyhow::Result;
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use tokio::process::Command as TokioCommand;
+
+const FAKE_CLUSTER: &str = "usw2";
+const FAKE_TENANT: &str = "72f988bf-0000-0000-0000-000000000000";
+
+#[derive(Debug, Clone)]
+struct FakeTunnel {
+    tunnel_id: String,
+    description: Option<String>,
+    tags: Vec<String>,
+    expiration: Option<String>,
+    ports: Vec<Port>,
+    access: Vec<AccessControlEntry>,
+}
+
+#[derive(Debug, Default)]
+struct FakeState {
+    user: Option<UserInfo>,
+    tunnels: Vec<FakeTunnel>,
+    next_tunnel: u32,
+    // Operation name -> error returned by the next call to that operation
+    failures: HashMap<String, String>,
+    calls: Vec<String>,
+    host_script: Option<String>,
+}
+
+pub struct FakeBackend {
+    state: Mutex<FakeState>,
+}
+
+impl Default for FakeBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeBackend {
+    // Starts logged in with no tunnels
+    pub fn new() -> Self {
+        let backend = Self {
+            state: Mutex::new(FakeState::default()),
+        };
+        backend.state.lock().unwrap().user = Some(fake_user("fake-user", AuthProvider::GitHub));
+        backend
+    }
+
+    pub fn logged_out() -> Self {
+        let backend = Self::new();
+        backend.state.lock().unwrap().user = None;
+        backend
+    }
+
+    // Make the next call to `op` (a DevTunnelBackend method name) fail with `message`
+    pub fn fail_next(&self, op: &str, message: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .failures
+            .insert(op.to_string(), message.to_string());
+    }
+
+    // Method names of every call made so far, in order
+    pub fn calls(&self) -> Vec<String> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    // Replace the shell script run by `host`. `$1` is the tunnel ID.
+    pub fn set_host_script(&self, script: &str) {
+        self.state.lock().unwrap().host_script = Some(script.to_string());
+    }
+
+    pub fn tunnel_ids(&self) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .tunnels
+            .iter()
+            .map(|t| t.tunnel_id.clone())
+            .collect()
+    }
+
+    // Record the call and apply any scripted failure
+    fn begin(&self, op: &str) -> Result<MutexGuard<'_, FakeState>> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(op.to_string());
+
+        if let Some(message) = state.failures.remove(op) {
+            return Err(anyhow::anyhow!("{}", message));
+        }
+        Ok(state)
+    }
+
+    // Like begin, but also requires a logged-in user as the real CLI does
+    fn begin_authenticated(&self, op: &str) -> Result<MutexGuard<'_, FakeState>> {
+        let state = self.begin(op)?;
+        if state.user.is_none() {
+            return Err(anyhow::anyhow!("Not authenticated. Run `devtunnel user login` first."));
+        }
+        Ok(state)
+    }
+}
+
+impl FakeState {
+    // The CLI accepts both the full ID and the ID without the cluster suffix
+    fn find(&mut self, tunnel_id: &str) -> Result<&mut FakeTunnel> {
+        self.tunnels
+            .iter_mut()
+            .find(|t| t.tunnel_id == tunnel_id || t.tunnel_id.split('.').next() == Some(tunnel_id))
+            .ok_or_else(|| anyhow::anyhow!("Tunnel not found: {}", tunnel_id))
+    }
+
+    fn find_port(&mut self, tunnel_id: &str, port_number: u16) -> Result<&mut Port> {
+        self.find(tunnel_id)?
+            .ports
+            .iter_mut()
+            .find(|p| p.port_number == port_number)
+            .ok_or_else(|| anyhow::anyhow!("Port {} not found on tunnel {}", port_number, tunnel_id))
+    }
+}
+
+fn fake_user(name: &str, provider: AuthProvider) -> UserInfo {
+    UserInfo {
+        user_id: name.to_string(),
+        user_name: Some(name.to_string()),
+        email: None,
+        provider,
+        is_authenticated: true,
+    }
+}
+
+fn full_tunnel_id(tunnel_id: &str) -> String {
+    if tunnel_id.contains('.') {
+        tunnel_id.to_string()
+    } else {
+        format!("{}.{}", tunnel_id, FAKE_CLUSTER)
+    }
+}
+
+fn port_uri(tunnel_id: &str, port_number: u16) -> String {
+    let (name, cluster) = tunnel_id.split_once('.').unwrap_or((tunnel_id, FAKE_CLUSTER));
+    format!("https://{}-{}.{}.devtunnels.ms/", name, port_number, cluster)
+}
+
+fn protocol_str(protocol: &Protocol) -> &'static str {
+    match protocol {
+        Protocol::Http => "http",
+        Protocol::Https => "https",
+        Protocol::Auto => "auto",
+    }
+}
+
+fn describe_access(entry: &AccessControlEntry) -> String {
+    let sign = if entry.deny.unwrap_or(false) { "-" } else { "+" };
+    let subject = match entry.entry_type.as_str() {
+        "anonymous" => format!("{}Anonymous", sign),
+        "organization" => format!("{}Org:{}", sign, entry.organization_id.as_deref().unwrap_or("")),
+        "tenant" => format!("{}Tenant:{}", sign, FAKE_TENANT),
+        other => format!("{}{}", sign, other),
+    };
+    let scopes = entry
+        .scopes
+        .clone()
+        .unwrap_or_else(|| vec!["connect".to_string()])
+        .join(", ");
+
+    match &entry.ports {
+        Some(ports) if !ports.is_empty() => {
+            let ports: Vec<String> = ports.iter().map(|p| p.to_string()).collect();
+            format!("{} [{}] (ports: {})", subject, scopes, ports.join(", "))
+        }
+        _ => format!("{} [{}]", subject, scopes),
+    }
+}
+
+fn show_text(tunnel: &FakeTunnel) -> String {
+    let access: Vec<String> = tunnel.access.iter().map(describe_access).collect();
+    let mut out = String::new();
+    out.push_str(&format!("Tunnel ID             : {}\n", tunnel.tunnel_id));
+    out.push_str(&format!(
+        "Description           : {}\n",
+        tunnel.description.as_deref().unwrap_or("")
+    ));
+    out.push_str(&format!("Labels                : {}\n", tunnel.tags.join(" ")));
+    out.push_str(&format!("Access control        : {{{}}}\n", access.join(", ")));
+    out.push_str("Host connections      : 0\n");
+    out.push_str("Client connections    : 0\n");
+    out.push_str(&format!(
+        "Tunnel Expiration     : {}\n",
+        tunnel.expiration.as_deref().unwrap_or("30 days")
+    ));
+    out.push_str(&format!("Ports                 : {}\n", tunnel.ports.len()));
+    for port in &tunnel.ports {
+        out.push_str(&format!(
+            "  {}  {}  {}\n",
+            port.port_number,
+            protocol_str(&port.protocol),
+            port_uri(&tunnel.tunnel_id, port.port_number)
+        ));
+    }
+    out
+}
+
+fn details(tunnel: &FakeTunnel) -> CliTunnelDetails {
+    CliTunnelDetails {
+        tunnel_id: tunnel.tunnel_id.clone(),
+        cluster_id: Some(FAKE_CLUSTER.to_string()),
+        domain: None,
+        description: tunnel.description.clone(),
+        labels: tunnel.tags.clone(),
+        access_control: tunnel.access.iter().map(describe_access).collect(),
+        host_connections: Some(0),
+        client_connections: Some(0),
+        created_at: None,
+        tunnel_expiration: tunnel.expiration.clone(),
+        ports: tunnel
+            .ports
+            .iter()
+            .map(|p| CliTunnelPort {
+                port_number: p.port_number,
+                protocol: Some(protocol_str(&p.protocol).to_string()),
+                description: p.description.clone(),
+                port_uri: Some(port_uri(&tunnel.tunnel_id, p.port_number)),
+                port_forwarding_uris: p.port_forwarding_uris.clone().unwrap_or_default(),
+                inspect_uri: p.inspect_uri.clone(),
+                client_connections: Some(0),
+            })
+            .collect(),
+    }
+}
+
+fn access_entry(entry: &AccessControlEntry) -> AccessEntry {
+    let (entry_type, subject) = match entry.entry_type.as_str() {
+        "anonymous" => (AccessSubjectType::Anonymous, None),
+        "organization" => (AccessSubjectType::Organization, entry.organization_id.clone()),
+        "tenant" => (AccessSubjectType::Tenant, Some(FAKE_TENANT.to_string())),
+        _ => (AccessSubjectType::Unknown, None),
+    };
+
+    AccessEntry {
+        entry_type,
+        subject,
+        provider: None,
+        is_deny: entry.deny.unwrap_or(false),
+        is_inherited: false,
+        scopes: entry.scopes.clone().unwrap_or_else(|| vec!["connect".to_string()]),
+        ports: entry.ports.clone().unwrap_or_default(),
+        expiration: entry.expiration.clone(),
+    }
+}
+
+fn list_item(tunnel: &FakeTunnel, with_ports: bool) -> TunnelListItem {
+    TunnelListItem {
+        tunnel_id: tunnel.tunnel_id.clone(),
+        description: tunnel.description.clone(),
+        tags: None,
+        ports: if with_ports {
+            tunnel.ports.iter().map(|p| p.port_number).collect()
+        } else {
+            Vec::new()
+        },
+        status: TunnelStatus::Active,
+        expires_at: tunnel.expiration.as_deref().and_then(parser::expiration_timestamp),
+        expiration: tunnel.expiration.clone(),
+        details_error: None,
+    }
+}
+
+fn matches_tags(tunnel: &FakeTunnel, req: &Option<ListTunnelsRequest>) -> bool {
+    let Some(req) = req else { return true };
+    let Some(tags) = &req.tags else { return true };
+
+    if req.all_tags.unwrap_or(false) {
+        tags.iter().all(|t| tunnel.tags.contains(t))
+    } else {
+        tags.iter().any(|t| tunnel.tags.contains(t))
+    }
+}
+
+#[async_trait]
+impl DevTunnelBackend for FakeBackend {
+    // Authentication
+    async fn login(&self, provider: &str, _use_device_code: bool) -> Result<String> {
+        let mut state = self.begin("login")?;
+        let (provider, label) = if provider == "github" {
+            (AuthProvider::GitHub, "GitHub")
+        } else {
+            (AuthProvider::Microsoft, "Microsoft")
+        };
+        state.user = Some(fake_user("fake-user", provider));
+        Ok(format!("Logged in as fake-user using {}.\n", label))
+    }
+
+    async fn logout(&self) -> Result<String> {
+        let mut state = self.begin("logout")?;
+        state.user = None;
+        Ok("Logged out successfully".to_string())
+    }
+
+    async fn get_user_info(&self) -> Result<UserInfo> {
+        let state = self.begin("get_user_info")?;
+        state.user.clone().ok_or_else(|| anyhow::anyhow!("Not authenticated"))
+    }
+
+    // Tunnel Management
+    async fn create_tunnel(&self, req: CreateTunnelRequest) -> Result<String> {
+        let mut state = self.begin_authenticated("create_tunnel")?;
+
+        let tunnel_id = match &req.tunnel_id {
+            Some(id) => full_tunnel_id(id),
+            None => {
+                state.next_tunnel += 1;
+                format!("fake-tunnel-{}.{}", state.next_tunnel, FAKE_CLUSTER)
+            }
+        };
+        if state.find(&tunnel_id).is_ok() {
+            return Err(anyhow::anyhow!("Tunnel already exists: {}", tunnel_id));
+        }
+
+        let mut access = Vec::new();
+        if req.allow_anonymous.unwrap_or(false) {
+            access.push(AccessControlEntry {
+                entry_type: "anonymous".to_string(),
+                scopes: Some(vec!["connect".to_string()]),
+                expiration: None,
+                ports: None,
+                organization_id: None,
+                deny: None,
+            });
+        }
+
+        let tunnel = FakeTunnel {
+            tunnel_id,
+            description: req.description,
+            tags: req.tags.unwrap_or_default(),
+            expiration: req.expiration,
+            ports: Vec::new(),
+            access,
+        };
+        let text = show_text(&tunnel);
+        state.tunnels.push(tunnel);
+        Ok(text)
+    }
+
+    async fn list_tunnels_light(&self, req: Option<ListTunnelsRequest>) -> Result<Vec<TunnelListItem>> {
+        let state = self.begin_authenticated("list_tunnels_light")?;
+        Ok(state
+            .tunnels
+            .iter()
+            .filter(|t| matches_tags(t, &req))
+            .map(|t| list_item(t, false))
+            .collect())
+    }
+
+    async fn list_tunnels(&self, req: Option<ListTunnelsRequest>) -> Result<Vec<TunnelListItem>> {
+        let state = self.begin_authenticated("list_tunnels")?;
+        Ok(state
+            .tunnels
+            .iter()
+            .filter(|t| matches_tags(t, &req))
+            .map(|t| list_item(t, true))
+            .collect())
+    }
+
+    // Without an ID the CLI shows the most recently created tunnel
+    async fn show_tunnel(&self, tunnel_id: Option<String>) -> Result<Tunnel> {
+        let mut state = self.begin_authenticated("show_tunnel")?;
+        let details = match tunnel_id {
+            Some(id) => details(state.find(&id)?),
+            None => state
+                .tunnels
+                .last()
+                .map(details)
+                .ok_or_else(|| anyhow::anyhow!("No current tunnel"))?,
+        };
+        Ok(parser::tunnel_from_details(details))
+    }
+
+    async fn update_tunnel(&self, req: UpdateTunnelRequest) -> Result<String> {
+        let mut state = self.begin_authenticated("update_tunnel")?;
+        let tunnel = state.find(&req.tunnel_id)?;
+
+        if let Some(description) = req.description {
+            tunnel.description = Some(description);
+        }
+        if req.remove_tags.unwrap_or(false) {
+            tunnel.tags.clear();
+        } else if let Some(tags) = req.tags {
+            tunnel.tags = tags;
+        }
+        if let Some(expiration) = req.expiration {
+            tunnel.expiration = Some(expiration);
+        }
+
+        Ok(show_text(tunnel))
+    }
+
+    async fn delete_tunnel(&self, tunnel_id: String) -> Result<String> {
+        let mut state = self.begin_authenticated("delete_tunnel")?;
+        let full_id = state.find(&tunnel_id)?.tunnel_id.clone();
+        state.tunnels.retain(|t| t.tunnel_id != full_id);
+        Ok("Tunnel deleted successfully".to_string())
+    }
+
+    async fn delete_all_tunnels(&self) -> Result<String> {
+        let mut state = self.begin_authenticated("delete_all_tunnels")?;
+        state.tunnels.clear();
+        Ok("All tunnels deleted successfully".to_string())
+    }
+
+    // Port Management
+    async fn create_port(&self, req: CreatePortRequest) -> Result<String> {
+        let mut state = self.begin_authenticated("create_port")?;
+        let tunnel = state.find(&req.tunnel_id)?;
+
+        if tunnel.ports.iter().any(|p| p.port_number == req.port_number) {
+            return Err(anyhow::anyhow!(
+                "Port {} already exists on tunnel {}",
+                req.port_number,
+                tunnel.tunnel_id
+            ));
+        }
+
+        let uri = port_uri(&tunnel.tunnel_id, req.port_number);
+        tunnel.ports.push(Port {
+            port_number: req.port_number,
+            protocol: req.protocol.unwrap_or(Protocol::Auto),
+            description: req.description,
+            port_forwarding_uris: Some(vec![uri.clone()]),
+            inspect_uri: None,
+        });
+
+        Ok(format!("Created port {} on tunnel {}: {}\n", req.port_number, tunnel.tunnel_id, uri))
+    }
+
+    async fn list_ports(&self, tunnel_id: String) -> Result<Vec<Port>> {
+        let mut state = self.begin_authenticated("list_ports")?;
+        Ok(state.find(&tunnel_id)?.ports.clone())
+    }
+
+    async fn show_port(&self, tunnel_id: String, port_number: u16) -> Result<Port> {
+        let mut state = self.begin_authenticated("show_port")?;
+        Ok(state.find_port(&tunnel_id, port_number)?.clone())
+    }
+
+    async fn update_port(&self, req: UpdatePortRequest) -> Result<String> {
+        let mut state = self.begin_authenticated("update_port")?;
+        let port = state.find_port(&req.tunnel_id, req.port_number)?;
+
+        if let Some(protocol) = req.protocol {
+            port.protocol = protocol;
+        }
+        if let Some(description) = req.description {
+            port.description = Some(description);
+        }
+
+        Ok(format!("Updated port {} on tunnel {}\n", req.port_number, req.tunnel_id))
+    }
+
+    async fn delete_port(&self, tunnel_id: String, port: u16) -> Result<String> {
+        let mut state = self.begin_authenticated("delete_port")?;
+        state.find_port(&tunnel_id, port)?;
+        state.find(&tunnel_id)?.ports.retain(|p| p.port_number != port);
+        Ok("Port deleted successfully".to_string())
+    }
+
+    // Access Control
+    async fn create_access(&self, req: CreateAccessRequest) -> Result<String> {
+        let mut state = self.begin_authenticated("create_access")?;
+        let tunnel = state.find(&req.tunnel_id)?;
+
+        access::subject_args(&req.entry)?;
+        let line = describe_access(&req.entry);
+        tunnel.access.push(req.entry);
+        Ok(format!("Added access control entry to tunnel {}: {}\n", tunnel.tunnel_id, line))
+    }
+
+    async fn list_access(&self, tunnel_id: String) -> Result<String> {
+        let mut state = self.begin_authenticated("list_access")?;
+        let tunnel = state.find(&tunnel_id)?;
+
+        let mut out = format!(
+            "Found {} access control {}.\n\n",
+            tunnel.access.len(),
+            if tunnel.access.len() == 1 { "entry" } else { "entries" }
+        );
+        for entry in &tunnel.access {
+            out.push_str(&describe_access(entry));
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    async fn list_access_entries(&self, tunnel_id: String) -> Result<Vec<AccessEntry>> {
+        let mut state = self.begin_authenticated("list_access_entries")?;
+        Ok(state.find(&tunnel_id)?.access.iter().map(access_entry).collect())
+    }
+
+    async fn delete_access(&self, req: DeleteAccessRequest) -> Result<String> {
+        let mut state = self.begin_authenticated("delete_access")?;
+        let tunnel = state.find(&req.tunnel_id)?;
+
+        let index = tunnel
+            .access
+            .iter()
+            .position(|e| access::same_entry(e, &req.entry))
+            .ok_or_else(|| anyhow::anyhow!("Access control entry not found: {}", describe_access(&req.entry)))?;
+        tunnel.access.remove(index);
+        Ok(format!("Deleted access control entry from tunnel {}\n", tunnel.tunnel_id))
+    }
+
+    async fn reset_access(&self, tunnel_id: String) -> Result<String> {
+        let mut state = self.begin_authenticated("reset_access")?;
+        state.find(&tunnel_id)?.access.clear();
+        Ok("Access reset successfully".to_string())
+    }
+
+    // Tokens: an unsigned JWT with the claims the service would put in
+    async fn issue_token(&self, tunnel_id: String, scopes: Vec<String>, port: Option<u16>) -> Result<String> {
+        let mut state = self.begin_authenticated("issue_token")?;
+        if let Some(port) = port {
+            state.find_port(&tunnel_id, port)?;
+        }
+        let tunnel = state.find(&tunnel_id)?;
+        let (name, cluster) = tunnel.tunnel_id.split_once('.').unwrap_or((&tunnel.tunnel_id, FAKE_CLUSTER));
+
+        let now = chrono::Utc::now().timestamp();
+        let mut claims = serde_json::json!({
+            "clusterId": cluster,
+            "tunnelId": name,
+            "scp": scopes.join(" "),
+            "iat": now,
+            "exp": now + 24 * 60 * 60,
+        });
+        if let Some(port) = port {
+            claims["port"] = port.into();
+        }
+        Ok(format!(
+            "{}.{}.fake-signature",
+            URL_SAFE_NO_PAD.encode(br#"{"alg":"none","typ":"JWT"}"#),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        ))
+    }
+
+    // Clusters
+    async fn list_clusters(&self, ping: bool) -> Result<Vec<Cluster>> {
+        let _state = self.begin("list_clusters")?;
+        Ok([("usw2", "West US 2", 30), ("euw", "West Europe", 120), ("asse", "Southeast Asia", 180)]
+            .into_iter()
+            .map(|(id, region, latency)| Cluster {
+                cluster_id: id.to_string(),
+                uri: format!("https://{}.rel.tunnels.api.visualstudio.com/", id),
+                region: Some(region.to_string()),
+                latency: if ping { Some(latency) } else { None },
+            })
+            .collect())
+    }
+
+    // A shell loop standing in for `devtunnel host`: prints the connect URLs like the
+    // real host does and runs until signalled. The leading "devtunnel-fake host <id>"
+    // arguments keep it recognisable to process::is_host_process.
+    fn host_command(&self, req: &HostTunnelRequest) -> TokioCommand {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push("host_command".to_string());
+
+        let tunnel_id = req.tunnel_id.clone().unwrap_or_default();
+        let script = state.host_script.clone().unwrap_or_else(|| {
+            let mut script = String::from("echo \"Hosting port(s) for tunnel: $1\"\n");
+            if let Ok(tunnel) = state.find(&tunnel_id) {
+                for port in &tunnel.ports {
+                    script.push_str(&format!(
+                        "echo \"Connect via browser: {}\"\n",
+                        port_uri(&tunnel.tunnel_id, port.port_number)
+                    ));
+                }
+            }
+            script.push_str("echo \"Ready to accept connections for tunnel: $1\"\n");
+            script.push_str("while true; do sleep 1; done\n");
+            script
+        });
+
+        let mut cmd = TokioCommand::new("sh");
+        cmd.arg("-c")
+            .arg(format!("shift\n{}", script))
+            .arg("devtunnel-fake")
+            .arg("host")
+            .arg(&tunnel_id);
+        cmd
+    }
+
+    // Reports a mapping per port of the tunnel, like the real CLI, then stays up
+    fn connect_command(&self, req: &ConnectTunnelRequest) -> TokioCommand {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push("connect_command".to_string());
+
+        let mut script = String::from("echo \"Connected to tunnel: $1\"\n");
+        if let Ok(tunnel) = state.find(&req.tunnel_id) {
+            for port in &tunnel.ports {
+                script.push_str(&format!(
+                    "echo \"Forwarding from 127.0.0.1:{0} to host port {0}.\"\n",
+                    port.port_number
+                ));
+            }
+        }
+        script.push_str("while true; do sleep 1; done\n");
+
+        let mut cmd = TokioCommand::new("sh");
+        cmd.arg("-c")
+            .arg(format!("shift\n{}", script))
+            .arg("devtunnel-fake")
+            .arg("connect")
+            .arg(&req.tunnel_id);
+        cmd
+    }
+}
@@ -0,0 +1,450 @@
+// In-process HTTP health checks behind ping_port. The request is made step by
+// step (DNS lookup, TCP connect, TLS handshake, HTTP/1.1 exchange) so each
+// phase can be timed on its own, and nothing depends on curl being installed.
+use crate::error::DevTunnelError;
+use crate::types::*;
+use anyhow::{Context, Result};
+use http_body_util::{BodyExt, Empty};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{HeaderName, HeaderValue, HOST, USER_AGENT};
+use hyper::{Method, Request, Response};
+use hyper_util::rt::TokioIo;
+use regex::Regex;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use tokio_rustls::rustls::crypto::{self, CryptoProvider};
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{self, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio_rustls::TlsConnector;
+use url::{Host, Position, Url};
+
+pub const DEFAULT_TIMEOUT_MS: u64 = 5000;
+const MAX_BODY_BYTES: usize = 1024 * 1024; // Only this much of a body is read for matching
+
+fn invalid(message: String) -> anyhow::Error {
+    DevTunnelError::new(ErrorCode::InvalidArgument, message).into()
+}
+
+fn ms(since: Instant) -> u64 {
+    since.elapsed().as_millis() as u64
+}
+
+// "200-299", "304" or "2xx" -> inclusive range
+pub fn parse_status_range(spec: &str) -> Option<(u16, u16)> {
+    let spec = spec.trim();
+    let (low, high) = if let Some(class) = spec.strip_suffix("xx") {
+        let class: u16 = class.parse().ok()?;
+        (class * 100, class * 100 + 99)
+    } else if let Some((low, high)) = spec.split_once('-') {
+        (low.trim().parse().ok()?, high.trim().parse().ok()?)
+    } else {
+        let code = spec.parse().ok()?;
+        (code, code)
+    };
+    ((100..=599).contains(&low) && (low..=599).contains(&high)).then_some((low, high))
+}
+
+// Options validated up front, so a bad option fails the command instead of
+// showing up as an unhealthy port
+struct Check {
+    url: Url,
+    method: Method,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    expected: Vec<(u16, u16)>,
+    expected_text: String,
+    body_contains: Option<String>,
+    body_regex: Option<Regex>,
+    verify_tls: bool,
+    timeout: Duration,
+}
+
+impl Check {
+    fn new(url: &str, options: &HealthCheckOptions) -> Result<Self> {
+        let mut parsed = Url::parse(url).map_err(|e| invalid(format!("Invalid URL '{}': {}", url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") || parsed.host().is_none() {
+            return Err(invalid(format!("Not an http(s) URL: {}", url)));
+        }
+        if let Some(path) = &options.path {
+            let path = if path.starts_with('/') { path.clone() } else { format!("/{}", path) };
+            parsed = parsed.join(&path).map_err(|e| invalid(format!("Invalid path '{}': {}", path, e)))?;
+        }
+
+        let method = options.method.as_deref().unwrap_or("GET").trim().to_uppercase();
+        let method = Method::from_bytes(method.as_bytes()).map_err(|_| invalid(format!("Invalid method '{}'", method)))?;
+
+        let token = options
+            .tunnel_token
+            .as_ref()
+            .map(|token| ("X-Tunnel-Authorization".to_string(), format!("tunnel {}", token)));
+        let headers = options
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .chain(token)
+            .map(|(name, value)| {
+                let header = HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid(format!("Invalid header name '{}'", name)))?;
+                let value = HeaderValue::from_str(&value).map_err(|_| invalid(format!("Invalid value for header '{}'", name)))?;
+                Ok((header, value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let expected = options
+            .expected_status
+            .iter()
+            .map(|spec| parse_status_range(spec).ok_or_else(|| invalid(format!("Invalid status range '{}'", spec))))
+            .collect::<Result<Vec<_>>>()?;
+        let (expected, expected_text) = if expected.is_empty() {
+            (vec![(100, 599)], "any".to_string())
+        } else {
+            (expected, options.expected_status.join(", "))
+        };
+
+        let body_regex = options
+            .body_regex
+            .as_ref()
+            .map(|re| Regex::new(re).map_err(|e| invalid(format!("Invalid body pattern '{}': {}", re, e))))
+            .transpose()?;
+
+        Ok(Self {
+            url: parsed,
+            method,
+            headers,
+            expected,
+            expected_text,
+            body_contains: options.body_contains.clone(),
+            body_regex,
+            verify_tls: options.verify_tls.unwrap_or(true),
+            timeout: Duration::from_millis(options.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)),
+        })
+    }
+
+    fn request(&self) -> Result<Request<Empty<Bytes>>> {
+        let mut request = Request::builder()
+            .method(self.method.clone())
+            .uri(&self.url[Position::BeforePath..Position::AfterQuery])
+            .header(HOST, &self.url[Position::BeforeHost..Position::AfterPort])
+            .header(USER_AGENT, concat!("devtunnel-gui/", env!("CARGO_PKG_VERSION")))
+            .body(Empty::new())
+            .context("Failed to build request")?;
+
+        // Custom headers win over the defaults above
+        for (name, value) in &self.headers {
+            request.headers_mut().insert(name.clone(), value.clone());
+        }
+        Ok(request)
+    }
+
+    fn tls_config(&self) -> Result<ClientConfig> {
+        let provider = Arc::new(crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone()).with_safe_default_protocol_versions()?;
+
+        let mut config = if self.verify_tls {
+            let roots = RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            };
+            builder.with_root_certificates(roots).with_no_client_auth()
+        } else {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+                .with_no_client_auth()
+        };
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        Ok(config)
+    }
+
+    // Fills in `result` as far as the check gets; an Err is the reason it failed
+    async fn run(&self, result: &mut PingResult) -> Result<()> {
+        let host = self.url.host().context("URL has no host")?;
+        let port = self.url.port_or_known_default().context("URL has no port")?;
+
+        let addrs: Vec<SocketAddr> = match host {
+            Host::Ipv4(ip) => vec![(ip, port).into()],
+            Host::Ipv6(ip) => vec![(ip, port).into()],
+            Host::Domain(domain) => {
+                let phase = Instant::now();
+                let addrs: Vec<SocketAddr> = tokio::net::lookup_host((domain, port))
+                    .await
+                    .with_context(|| format!("DNS lookup for {} failed", domain))?
+                    .collect();
+                result.timings.dns_ms = Some(ms(phase));
+                addrs
+            }
+        };
+
+        let phase = Instant::now();
+        let (stream, addr) = connect(&addrs).await?;
+        result.timings.connect_ms = Some(ms(phase));
+        result.remote_address = Some(addr.to_string());
+
+        let request = self.request()?;
+        let (response, first_byte) = if self.url.scheme() == "https" {
+            let name = match host {
+                Host::Domain(domain) => domain.to_string(),
+                Host::Ipv4(ip) => ip.to_string(),
+                Host::Ipv6(ip) => ip.to_string(),
+            };
+            let name = ServerName::try_from(name).context("Invalid TLS server name")?;
+
+            let phase = Instant::now();
+            let stream = TlsConnector::from(Arc::new(self.tls_config()?))
+                .connect(name, stream)
+                .await
+                .context("TLS handshake failed")?;
+            result.timings.tls_ms = Some(ms(phase));
+            exchange(stream, request).await?
+        } else {
+            exchange(stream, request).await?
+        };
+        result.timings.first_byte_ms = Some(first_byte.as_millis() as u64);
+
+        let status = response.status().as_u16();
+        result.status_code = Some(status);
+        if !self.expected.iter().any(|(low, high)| (*low..=*high).contains(&status)) {
+            anyhow::bail!("Unexpected status {} (expected {})", status, self.expected_text);
+        }
+
+        if self.body_contains.is_none() && self.body_regex.is_none() {
+            return Ok(());
+        }
+        let body = read_body(response.into_body()).await?;
+        let body = String::from_utf8_lossy(&body);
+
+        let mut failures = Vec::new();
+        if let Some(text) = self.body_contains.as_ref().filter(|text| !body.contains(text.as_str())) {
+            failures.push(format!("does not contain '{}'", text));
+        }
+        if let Some(re) = self.body_regex.as_ref().filter(|re| !re.is_match(&body)) {
+            failures.push(format!("does not match /{}/", re));
+        }
+        result.body_matched = Some(failures.is_empty());
+        if !failures.is_empty() {
+            anyhow::bail!("Response body {}", failures.join(" and "));
+        }
+        Ok(())
+    }
+}
+
+// Addresses are tried in order, e.g. ::1 then 127.0.0.1 for localhost
+async fn connect(addrs: &[SocketAddr]) -> Result<(TcpStream, SocketAddr)> {
+    let mut last_error = anyhow::anyhow!("DNS lookup returned no addresses");
+    for addr in addrs {
+        match TcpStream::connect(addr).await {
+            Ok(stream) => return Ok((stream, *addr)),
+            Err(e) => last_error = anyhow::Error::new(e).context(format!("Connection to {} failed", addr)),
+        }
+    }
+    Err(last_error)
+}
+
+// Send the request over an established (plain or TLS) stream; returns the
+// response head and how long it took to arrive
+async fn exchange<S>(stream: S, request: Request<Empty<Bytes>>) -> Result<(Response<Incoming>, Duration)>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .context("HTTP handshake failed")?;
+    // Drives the connection; ends once the response and sender are dropped
+    tokio::spawn(connection);
+
+    let sent = Instant::now();
+    let response = sender.send_request(request).await.context("Request failed")?;
+    Ok((response, sent.elapsed()))
+}
+
+async fn read_body(mut body: Incoming) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    while let Some(frame) = body.frame().await {
+        if let Ok(data) = frame.context("Failed to read response body")?.into_data() {
+            bytes.extend_from_slice(&data);
+        }
+        if bytes.len() >= MAX_BODY_BYTES {
+            bytes.truncate(MAX_BODY_BYTES);
+            break;
+        }
+    }
+    Ok(bytes)
+}
+
+// verifyTls: false. Signatures are still checked so the handshake itself is
+// sound; only the certificate chain and host name are not.
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+// Fails the same way check() would for bad options, without running it
+pub fn validate(url: &str, options: &HealthCheckOptions) -> Result<()> {
+    Check::new(url, options).map(|_| ())
+}
+
+// Run one health check against `url`. Invalid options are an error; anything
+// that goes wrong with the request itself is reported in the result.
+pub async fn check(url: &str, options: &HealthCheckOptions) -> Result<PingResult> {
+    let check = Check::new(url, options)?;
+    let started = Instant::now();
+    let mut result = PingResult {
+        success: false,
+        status_code: None,
+        response_time_ms: 0,
+        error: None,
+        timings: PingTimings::default(),
+        remote_address: None,
+        body_matched: None,
+    };
+
+    match tokio::time::timeout(check.timeout, check.run(&mut result)).await {
+        Ok(Ok(())) => result.success = true,
+        Ok(Err(e)) => result.error = Some(format!("{:#}", e)),
+        Err(_) => result.error = Some(format!("Timed out after {}ms", check.timeout.as_millis())),
+    }
+    result.response_time_ms = ms(started);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // Answer one request with `response`; the task returns the request head
+    async fn serve(response: &'static str) -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let task = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            socket.write_all(response.as_bytes()).await.unwrap();
+            socket.shutdown().await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+        (port, task)
+    }
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 15\r\nConnection: close\r\n\r\n{\"status\":\"up\"}";
+
+    #[test]
+    fn test_parse_status_range() {
+        assert_eq!(parse_status_range("200-299"), Some((200, 299)));
+        assert_eq!(parse_status_range(" 304 "), Some((304, 304)));
+        assert_eq!(parse_status_range("5xx"), Some((500, 599)));
+        assert_eq!(parse_status_range("299-200"), None);
+        assert_eq!(parse_status_range("700"), None);
+        assert_eq!(parse_status_range("ok"), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_sends_options_and_matches_body() {
+        let (port, request) = serve(OK).await;
+        let options = HealthCheckOptions {
+            method: Some("head".to_string()),
+            path: Some("healthz?full=1".to_string()),
+            headers: [("X-Probe".to_string(), "gui".to_string())].into(),
+            tunnel_token: Some("abc".to_string()),
+            ..Default::default()
+        };
+
+        let result = check(&format!("http://127.0.0.1:{}/app", port), &options).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.status_code, Some(200));
+        assert_eq!(result.remote_address, Some(format!("127.0.0.1:{}", port)));
+        // An IP needs no lookup and plain http no handshake
+        assert_eq!(result.timings.dns_ms, None);
+        assert_eq!(result.timings.tls_ms, None);
+        assert!(result.timings.connect_ms.is_some() && result.timings.first_byte_ms.is_some());
+        assert_eq!(result.body_matched, None);
+
+        let request = request.await.unwrap().to_lowercase();
+        assert!(request.starts_with("head /healthz?full=1 http/1.1\r\n"), "{}", request);
+        assert!(request.contains("x-probe: gui\r\n"));
+        assert!(request.contains("x-tunnel-authorization: tunnel abc\r\n"));
+
+        let (port, _) = serve(OK).await;
+        let options = HealthCheckOptions {
+            expected_status: vec!["2xx".to_string()],
+            body_contains: Some("status".to_string()),
+            body_regex: Some(r#""status":\s*"down""#.to_string()),
+            ..Default::default()
+        };
+        let result = check(&format!("http://localhost:{}/", port), &options).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.body_matched, Some(false));
+        assert!(result.timings.dns_ms.is_some());
+        assert!(result.error.unwrap().contains("does not match"));
+    }
+
+    #[tokio::test]
+    async fn test_check_failures() {
+        let (port, _) = serve("HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+        let options = HealthCheckOptions {
+            expected_status: vec!["200-299".to_string()],
+            ..Default::default()
+        };
+        let result = check(&format!("http://127.0.0.1:{}/", port), &options).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.status_code, Some(503));
+        assert_eq!(result.error.as_deref(), Some("Unexpected status 503 (expected 200-299)"));
+
+        // Nothing listening
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let result = check(&format!("http://127.0.0.1:{}/", port), &HealthCheckOptions::default()).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.timings.connect_ms, None);
+        assert!(result.error.unwrap().starts_with("Connection to 127.0.0.1"));
+
+        assert!(check("ftp://example.com", &HealthCheckOptions::default()).await.is_err());
+        let bad = HealthCheckOptions {
+            body_regex: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert!(check("http://127.0.0.1/", &bad).await.is_err());
+    }
+}
@@ -1,14 +1,61 @@
-mod devtunnel;
-mod types;
-mod commands;
+mod access;
+mod cache;
+mod connect;
+mod discovery;
+pub mod backend;
+pub mod devtunnel;
+pub mod types;
+pub mod commands;
+pub mod control;
+pub mod error;
+pub mod events;
+mod executor;
+mod health;
+#[cfg(any(test, feature = "test-support"))]
+pub mod fake;
+mod logfiles;
+mod logging;
+mod monitor;
+mod operations;
 mod parser;
+mod poller;
+mod process;
+mod redact;
+mod sessions;
+pub mod spec;
+mod supervisor;
+mod token;
+mod upstream;
+
+pub use supervisor::DEFAULT_STOP_GRACE_MS;
 
 use commands::*;
+use std::sync::Arc;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            let data_dir = app.path().app_data_dir()?;
+            let log_dir = app.path().app_log_dir()?;
+            app.manage(AppState::new(Arc::new(app.handle().clone()), &data_dir, &log_dir));
+            tauri::async_runtime::spawn(reconcile_host_sessions(app.handle().clone()));
+            tauri::async_runtime::spawn(poll_tunnel_state(app.handle().clone()));
+            tauri::async_runtime::spawn(monitor_ports(app.handle().clone()));
+            tauri::async_runtime::spawn(watch_upstreams(app.handle().clone()));
+            #[cfg(unix)]
+            {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = control::serve(handle.clone(), &control::socket_path()).await {
+                        Logger::new(&handle, "control", None).error(&format!("Control socket unavailable: {:#}", e));
+                    }
+                });
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Authentication
             login_devtunnel,
@@ -27,19 +74,61 @@ pub fn run() {
             stop_tunnel,
             restart_tunnel,
             get_tunnel_start_time,
+            get_host_output,
+            get_host_sessions,
+            restore_host_sessions,
+            forget_host_session,
+            set_restore_sessions_on_startup,
+            check_upstream,
+            get_upstream_status,
             ping_port,
+            // Connect
+            connect_tunnel,
+            list_connections,
+            stop_connection,
             // Port Management
             create_port,
             list_ports,
+            discover_ports,
             show_port,
             update_port,
             delete_port,
             // Access Control
             create_access,
             list_access,
+            list_access_entries,
+            delete_access,
             reset_access,
+            diff_access,
+            // Tokens
+            issue_token,
+            // Spec Files
+            plan_spec,
+            apply_spec,
             // Clusters
             list_clusters,
+            // Operations
+            cancel_operation,
+            get_cli_timeouts,
+            set_cli_timeouts,
+            get_cli_limits,
+            set_cli_limits,
+            // Background Refresh
+            get_poller_settings,
+            set_poller_settings,
+            set_poller_paused,
+            // Port Monitoring
+            get_monitor_settings,
+            set_monitor_settings,
+            get_port_health,
+            get_port_health_history,
+            // Logs
+            get_logs,
+            get_log_file_settings,
+            set_log_file_settings,
+            export_logs,
+            get_redaction_settings,
+            set_redaction_settings,
             // System
             check_devtunnel_installation,
             open_url,
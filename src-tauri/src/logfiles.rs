@@ -0,0 +1,461 @@
+// Log files in the app log directory, so a tunnel that dropped overnight can
+// still be diagnosed after the window was closed. App log entries go to
+// app.log and each tunnel's captured `devtunnel host` output to
+// host-<tunnel>.log, one JSON object per line. The active file is renamed to
+// <name>.<stamp>.log when it fills up or the day changes.
+use crate::cache::same_tunnel;
+use crate::error::DevTunnelError;
+use crate::types::*;
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+pub const LOG_FILES_FILE_NAME: &str = "log-files.json";
+
+const APP_LOG: &str = "app";
+const HOST_LOG_PREFIX: &str = "host-";
+// Sorts chronologically; milliseconds keep quick size rotations apart
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S%3f";
+const STAMP_LEN: usize = 18;
+const REDACTED: &str = "[redacted]";
+
+impl Default for LogFileSettings {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 5 * 1024 * 1024,
+            max_files: 10,
+            retention_days: 14,
+        }
+    }
+}
+
+// The file currently appended to for one log
+struct ActiveFile {
+    file: File,
+    size: u64,
+    day: NaiveDate,
+}
+
+pub struct LogFiles {
+    dir: PathBuf,
+    path: Option<PathBuf>,
+    settings: Mutex<LogFileSettings>,
+    // Keyed by log name ("app", "host-demo.usw2")
+    active: Mutex<HashMap<String, ActiveFile>>,
+}
+
+impl LogFiles {
+    // Logs are written to `dir`; settings are read from (and saved to) `path`,
+    // None keeps them in memory
+    pub fn load(dir: PathBuf, path: Option<PathBuf>) -> Self {
+        let settings = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            dir,
+            path,
+            settings: Mutex::new(settings),
+            active: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn settings(&self) -> LogFileSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set_settings(&self, settings: LogFileSettings) -> Result<()> {
+        if settings.max_file_bytes == 0 || settings.max_files == 0 {
+            return Err(DevTunnelError::new(ErrorCode::InvalidArgument, "maxFileBytes and maxFiles must be at least 1").into());
+        }
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, serde_json::to_string_pretty(&settings)?)?;
+        }
+        *self.settings.lock().unwrap() = settings.clone();
+
+        // Apply tighter retention right away rather than at the next rotation
+        let mut names: Vec<String> = self.files(|_| true).into_iter().map(|(name, _)| name).collect();
+        names.dedup();
+        for name in names {
+            self.prune(&name, &settings)?;
+        }
+        Ok(())
+    }
+
+    // Failures are dropped: a full disk must not break the command being logged
+    pub fn record_entry(&self, entry: &LogEntry) {
+        if let Ok(line) = serde_json::to_string(entry) {
+            let _ = self.append(APP_LOG, &line);
+        }
+    }
+
+    pub fn record_host_line(&self, tunnel_id: &str, line: &HostOutputLine) {
+        if let Ok(line) = serde_json::to_string(line) {
+            let _ = self.append(&host_log_name(tunnel_id), &line);
+        }
+    }
+
+    fn append(&self, name: &str, line: &str) -> Result<()> {
+        let settings = self.settings();
+        let today = Local::now().date_naive();
+        let mut active = self.active.lock().unwrap();
+
+        if let Some(current) = active.get(name) {
+            if current.day != today || (current.size > 0 && current.size + line.len() as u64 >= settings.max_file_bytes) {
+                active.remove(name);
+                self.rotate(name, &settings)?;
+            }
+        }
+        if !active.contains_key(name) {
+            let opened = self.open(name, today, &settings)?;
+            active.insert(name.to_string(), opened);
+        }
+
+        let current = active.get_mut(name).unwrap();
+        writeln!(current.file, "{}", line)?;
+        current.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    // Continue the file left by a previous run unless it is already due for rotation
+    fn open(&self, name: &str, today: NaiveDate, settings: &LogFileSettings) -> Result<ActiveFile> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!("{}.log", name));
+
+        if let Ok(meta) = fs::metadata(&path) {
+            let day = meta.modified().map(|m| DateTime::<Local>::from(m).date_naive()).unwrap_or(today);
+            if day != today || meta.len() >= settings.max_file_bytes {
+                self.rotate(name, settings)?;
+            }
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let size = file.metadata()?.len();
+        Ok(ActiveFile { file, size, day: today })
+    }
+
+    fn rotate(&self, name: &str, settings: &LogFileSettings) -> Result<()> {
+        let stamp = Local::now().format(STAMP_FORMAT);
+        fs::rename(
+            self.dir.join(format!("{}.log", name)),
+            self.dir.join(format!("{}.{}.log", name, stamp)),
+        )?;
+        self.prune(name, settings)
+    }
+
+    // Delete rotated files of `name` beyond maxFiles or past retentionDays
+    fn prune(&self, name: &str, settings: &LogFileSettings) -> Result<()> {
+        let rotated: Vec<PathBuf> = self
+            .files(|n| n == name)
+            .into_iter()
+            .map(|(_, path)| path)
+            .filter(|path| path.file_name() != Some(format!("{}.log", name).as_ref()))
+            .collect();
+        remove_old(&rotated, settings)
+    }
+
+    // Delete export archives beyond maxFiles or past retentionDays
+    fn prune_exports(&self, exports: &Path) -> Result<()> {
+        let mut archives: Vec<PathBuf> = fs::read_dir(exports)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "zip"))
+            .collect();
+        // The stamp in the name sorts them oldest first
+        archives.sort();
+        remove_old(&archives, &self.settings())
+    }
+
+    // Log files whose name passes `wanted`, oldest first per log
+    fn files(&self, wanted: impl Fn(&str) -> bool) -> Vec<(String, PathBuf)> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<(String, bool, PathBuf)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let (name, rotated) = parse_file_name(&file_name)?;
+                wanted(&name).then(|| (name, !rotated, entry.path()))
+            })
+            .collect();
+        // Rotated files by stamp, then the active one
+        files.sort();
+        files.into_iter().map(|(name, _, path)| (name, path)).collect()
+    }
+
+    // Bundle the logs (narrowed to `range` and `tunnel_id`) plus `extra` files
+    // into a zip archive under <log dir>/exports and return its path. Older
+    // archives are pruned with the same limits as rotated logs.
+    pub fn export(&self, range: &LogRange, tunnel_id: Option<&str>, extra: &[(&str, String)]) -> Result<PathBuf> {
+        let since = parse_bound(range.since.as_deref())?;
+        let until = parse_bound(range.until.as_deref())?;
+        let in_range = |timestamp: &str| match DateTime::parse_from_rfc3339(timestamp) {
+            Ok(t) => since.is_none_or(|s| t >= s) && until.is_none_or(|u| t <= u),
+            Err(_) => false,
+        };
+
+        let mut app_log = String::new();
+        for (_, path) in self.files(|name| name == APP_LOG) {
+            for entry in read_lines::<LogEntry>(&path) {
+                let wanted = match (tunnel_id, &entry.tunnel_id) {
+                    (None, _) => true,
+                    (Some(wanted), Some(id)) => same_tunnel(wanted, id),
+                    (Some(_), None) => false,
+                };
+                if wanted && in_range(&entry.timestamp) {
+                    app_log.push_str(&format_entry(&entry));
+                }
+            }
+        }
+
+        let mut host_logs: Vec<(String, String)> = Vec::new();
+        let host_files = self.files(|name| {
+            name.strip_prefix(HOST_LOG_PREFIX)
+                .is_some_and(|id| tunnel_id.is_none_or(|wanted| same_tunnel(wanted, id)))
+        });
+        for (name, path) in host_files {
+            let mut text = String::new();
+            for line in read_lines::<HostOutputLine>(&path) {
+                if in_range(&line.timestamp) {
+                    let stream = match line.stream {
+                        OutputStream::Stdout => "stdout",
+                        OutputStream::Stderr => "stderr",
+                    };
+                    text.push_str(&format!("{} [{}] {}\n", line.timestamp, stream, line.line));
+                }
+            }
+            match host_logs.last_mut() {
+                Some((last, content)) if *last == name => content.push_str(&text),
+                _ => host_logs.push((name, text)),
+            }
+        }
+
+        let exports = self.dir.join("exports");
+        fs::create_dir_all(&exports)?;
+        let path = exports.join(format!("devtunnel-gui-logs-{}.zip", Local::now().format(STAMP_FORMAT)));
+
+        let mut zip = zip::ZipWriter::new(File::create(&path)?);
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("app.log", options)?;
+        zip.write_all(app_log.as_bytes())?;
+        for (name, content) in &host_logs {
+            zip.start_file(format!("{}.log", name), options)?;
+            zip.write_all(content.as_bytes())?;
+        }
+        for (name, content) in extra {
+            zip.start_file(*name, options)?;
+            zip.write_all(content.as_bytes())?;
+        }
+        zip.finish()?;
+        self.prune_exports(&exports)?;
+        Ok(path)
+    }
+}
+
+// Delete `paths` (oldest first) beyond maxFiles or past retentionDays
+fn remove_old(paths: &[PathBuf], settings: &LogFileSettings) -> Result<()> {
+    let max_age = Duration::from_secs(u64::from(settings.retention_days) * 24 * 60 * 60);
+    let excess = paths.len().saturating_sub(settings.max_files);
+    for (i, path) in paths.iter().enumerate() {
+        let expired = settings.retention_days > 0
+            && fs::metadata(path)
+                .and_then(|m| m.modified())
+                .map(|m| SystemTime::now().duration_since(m).unwrap_or_default() > max_age)
+                .unwrap_or(false);
+        if i < excess || expired {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+fn host_log_name(tunnel_id: &str) -> String {
+    format!("{}{}", HOST_LOG_PREFIX, safe_file_name(tunnel_id))
+}
+
+// A tunnel ID (or other user input) as a file name that stays inside its
+// directory: separators and anything unusual become '_'
+pub(crate) fn safe_file_name(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    // "." and ".." survive the filter above
+    if safe.chars().all(|c| c == '.') {
+        safe.replace('.', "_")
+    } else {
+        safe
+    }
+}
+
+// "host-demo.usw2.20261018-101500123.log" -> ("host-demo.usw2", rotated)
+fn parse_file_name(file_name: &str) -> Option<(String, bool)> {
+    let stem = file_name.strip_suffix(".log")?;
+    match stem.rsplit_once('.') {
+        Some((name, stamp)) if is_stamp(stamp) => Some((name.to_string(), true)),
+        _ => Some((stem.to_string(), false)),
+    }
+}
+
+fn is_stamp(s: &str) -> bool {
+    s.len() == STAMP_LEN
+        && s.char_indices().all(|(i, c)| if i == 8 { c == '-' } else { c.is_ascii_digit() })
+}
+
+fn parse_bound(bound: Option<&str>) -> Result<Option<DateTime<FixedOffset>>> {
+    bound
+        .map(|b| {
+            DateTime::parse_from_rfc3339(b).map_err(|e| {
+                DevTunnelError::new(ErrorCode::InvalidArgument, format!("Invalid time '{}': {}", b, e)).into()
+            })
+        })
+        .transpose()
+}
+
+// Lines that don't parse (a partial last write) are skipped
+fn read_lines<T: DeserializeOwned>(path: &Path) -> Vec<T> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+fn format_entry(entry: &LogEntry) -> String {
+    let mut origin = entry.source.clone().unwrap_or_default();
+    if let Some(tunnel_id) = &entry.tunnel_id {
+        origin.push('@');
+        origin.push_str(tunnel_id);
+    }
+    format!("{} [{:?}] {}: {}\n", entry.timestamp, entry.level, origin, entry.message)
+}
+
+// Blank out anything credential-like and the user's home directory before
+// settings leave the machine in a bug report
+pub fn redact_settings(value: &mut Value) {
+    let home = dirs::home_dir().map(|h| h.to_string_lossy().to_string()).filter(|h| h.len() > 1);
+    redact_value(value, home.as_deref());
+}
+
+fn redact_value(value: &mut Value, home: Option<&str>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if ["token", "secret", "password", "credential"].iter().any(|k| key.contains(k)) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(value, home);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact_value(item, home)),
+        Value::String(s) => {
+            if let Some(home) = home {
+                *s = s.replace(home, "~");
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn entry(id: u64, tunnel_id: Option<&str>, message: &str) -> LogEntry {
+        LogEntry {
+            id,
+            timestamp: Local::now().to_rfc3339(),
+            level: LogLevel::Info,
+            message: message.to_string(),
+            source: Some("create_port".to_string()),
+            tunnel_id: tunnel_id.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_rotation_retention_and_export() {
+        let dir = std::env::temp_dir().join(format!("devtunnel-gui-logfiles-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let files = LogFiles::load(dir.clone(), None);
+        files
+            .set_settings(LogFileSettings {
+                max_file_bytes: 200,
+                max_files: 2,
+                retention_days: 0,
+            })
+            .unwrap();
+
+        // ~150 bytes per entry, so every write after the first rotates
+        for i in 0..5 {
+            files.record_entry(&entry(i, Some("demo"), &format!("Port {} on demo", i)));
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        files.record_entry(&entry(5, Some("other"), "Port 8080 on other"));
+        files.record_host_line("demo.usw2", &HostOutputLine {
+            seq: 0,
+            stream: OutputStream::Stdout,
+            line: "Ready to accept connections for tunnel: demo".to_string(),
+            timestamp: Local::now().to_rfc3339(),
+        });
+
+        let app_files = files.files(|name| name == APP_LOG);
+        assert_eq!(app_files.len(), 3, "two rotated files plus the active one");
+
+        let path = files
+            .export(&LogRange::default(), Some("demo"), &[("settings.json", "{}".to_string())])
+            .unwrap();
+        let mut archive = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let mut app_log = String::new();
+        archive.by_name("app.log").unwrap().read_to_string(&mut app_log).unwrap();
+        // Entries 0-2 were pruned with the oldest files; "other" is filtered out
+        assert_eq!(app_log.lines().count(), 2);
+        assert!(app_log.contains("create_port@demo: Port 4 on demo"));
+        let mut host_log = String::new();
+        archive.by_name("host-demo.usw2.log").unwrap().read_to_string(&mut host_log).unwrap();
+        assert!(host_log.contains("[stdout] Ready to accept"));
+        assert!(archive.by_name("settings.json").is_ok());
+
+        let future = LogRange {
+            since: Some("2999-01-01T00:00:00Z".to_string()),
+            until: None,
+        };
+        let path = files.export(&future, None, &[]).unwrap();
+        let mut archive = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(archive.by_name("app.log").unwrap().size(), 0);
+        assert!(files.export(&LogRange { since: Some("yesterday".to_string()), until: None }, None, &[]).is_err());
+
+        // Exports are pruned like rotated logs: maxFiles is 2
+        std::thread::sleep(Duration::from_millis(2));
+        let newest = files.export(&future, None, &[]).unwrap();
+        let exports: Vec<PathBuf> = fs::read_dir(dir.join("exports")).unwrap().flatten().map(|e| e.path()).collect();
+        assert_eq!(exports.len(), 2);
+        assert!(exports.contains(&path) && exports.contains(&newest));
+
+        let mut settings = serde_json::json!({ "request": { "accessToken": "abc" }, "program": "/x/devtunnel" });
+        redact_settings(&mut settings);
+        assert_eq!(settings["request"]["accessToken"], REDACTED);
+        assert_eq!(settings["program"], "/x/devtunnel");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,121 @@
+// In-memory log of what commands and background tasks did. Entries carry a
+// level and where they came from (command name, tunnel) so the Logs view and
+// get_logs can filter them; the oldest fall off once the buffer is full.
+use crate::cache::same_tunnel;
+use crate::types::*;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+pub const LOG_CAPACITY: usize = 2000;
+
+pub struct LogStore {
+    capacity: usize,
+    // Entries plus the id for the next one
+    entries: Mutex<(VecDeque<LogEntry>, u64)>,
+}
+
+impl Default for LogStore {
+    fn default() -> Self {
+        Self::new(LOG_CAPACITY)
+    }
+}
+
+impl LogStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new((VecDeque::with_capacity(capacity), 1)),
+        }
+    }
+
+    pub fn push(&self, level: LogLevel, source: Option<&str>, tunnel_id: Option<&str>, message: &str) -> LogEntry {
+        let mut guard = self.entries.lock().unwrap();
+        let (entries, next_id) = &mut *guard;
+
+        let entry = LogEntry {
+            id: *next_id,
+            timestamp: chrono::Local::now().to_rfc3339(),
+            level,
+            message: message.to_string(),
+            source: source.map(str::to_string),
+            tunnel_id: tunnel_id.map(str::to_string),
+        };
+        *next_id += 1;
+
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry.clone());
+        entry
+    }
+
+    pub fn query(&self, filter: &LogFilter) -> Vec<LogEntry> {
+        let search = filter.search.as_ref().map(|s| s.to_lowercase());
+        let guard = self.entries.lock().unwrap();
+
+        let mut matches: Vec<LogEntry> = guard
+            .0
+            .iter()
+            .filter(|e| filter.level.is_none_or(|level| e.level >= level))
+            .filter(|e| filter.since_id.is_none_or(|id| e.id > id))
+            .filter(|e| filter.source.is_none() || e.source == filter.source)
+            .filter(|e| match (&filter.tunnel_id, &e.tunnel_id) {
+                (None, _) => true,
+                (Some(wanted), Some(id)) => same_tunnel(wanted, id),
+                (Some(_), None) => false,
+            })
+            .filter(|e| search.as_ref().is_none_or(|s| e.message.to_lowercase().contains(s)))
+            .cloned()
+            .collect();
+
+        if let Some(limit) = filter.limit {
+            let skip = matches.len().saturating_sub(limit);
+            matches.drain(..skip);
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_and_filters() {
+        let logs = LogStore::new(3);
+        logs.push(LogLevel::Info, Some("create_tunnel"), Some("demo"), "Creating tunnel: demo");
+        logs.push(LogLevel::Error, Some("create_port"), Some("demo"), "Failed to create port");
+        logs.push(LogLevel::Info, Some("create_port"), Some("other.usw2"), "Creating port 3000");
+        logs.push(LogLevel::Warn, Some("poller"), None, "Background refresh failed");
+
+        // The oldest entry was dropped
+        let all = logs.query(&LogFilter::default());
+        assert_eq!(all.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2, 3, 4]);
+
+        let warnings = logs.query(&LogFilter {
+            level: Some(LogLevel::Warn),
+            ..LogFilter::default()
+        });
+        assert_eq!(warnings.len(), 2);
+
+        let other = logs.query(&LogFilter {
+            tunnel_id: Some("other".to_string()),
+            ..LogFilter::default()
+        });
+        assert_eq!(other[0].message, "Creating port 3000");
+
+        let filtered = logs.query(&LogFilter {
+            source: Some("create_port".to_string()),
+            search: Some("FAILED".to_string()),
+            ..LogFilter::default()
+        });
+        assert_eq!(filtered.len(), 1);
+
+        let newest = logs.query(&LogFilter {
+            since_id: Some(2),
+            limit: Some(1),
+            ..LogFilter::default()
+        });
+        assert_eq!(newest[0].id, 4);
+    }
+}
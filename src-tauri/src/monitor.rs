@@ -0,0 +1,446 @@
+// Scheduled health checks of forwarded ports. Each configured port is
+// checked every `intervalMs` with health.rs; results go to an in-memory
+// history backed by one JSON-lines file per port under health/, from which
+// uptime and latency percentiles are computed. A change between up and down
+// is handed back to the caller to report as "port-health-changed".
+use crate::error::DevTunnelError;
+use crate::health;
+use crate::logfiles::safe_file_name;
+use crate::types::*;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+pub const MONITOR_FILE_NAME: &str = "monitors.json";
+pub const HISTORY_DIR_NAME: &str = "health";
+
+const MIN_INTERVAL_MS: u64 = 5000; // Shortest allowed check interval
+const RETENTION_DAYS: i64 = 7; // Samples older than this are dropped
+
+// Samples of one monitored port, oldest first
+struct Series {
+    samples: VecDeque<HealthSample>,
+    // When the history file was last rewritten without expired samples
+    compacted_at: DateTime<Utc>,
+}
+
+fn key(tunnel_id: &str, port_number: u16) -> String {
+    format!("{}-{}", tunnel_id, port_number)
+}
+
+fn retention() -> chrono::Duration {
+    chrono::Duration::days(RETENTION_DAYS)
+}
+
+// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+pub struct HealthMonitor {
+    path: Option<PathBuf>,
+    // None keeps history in memory only
+    history_dir: Option<PathBuf>,
+    settings: Mutex<MonitorSettings>,
+    series: Mutex<HashMap<String, Series>>,
+    next_run: Mutex<HashMap<String, Instant>>,
+    wake: Notify,
+}
+
+impl HealthMonitor {
+    // Monitors are read from (and saved to) `path`; None keeps them in memory
+    pub fn load(path: Option<PathBuf>, history_dir: Option<PathBuf>) -> Self {
+        let settings = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            path,
+            history_dir,
+            settings: Mutex::new(settings),
+            series: Mutex::new(HashMap::new()),
+            next_run: Mutex::new(HashMap::new()),
+            wake: Notify::new(),
+        }
+    }
+
+    pub fn settings(&self) -> MonitorSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set_settings(&self, settings: MonitorSettings) -> Result<()> {
+        let invalid = |message: String| DevTunnelError::new(ErrorCode::InvalidArgument, message);
+
+        for (i, monitor) in settings.monitors.iter().enumerate() {
+            let name = key(&monitor.tunnel_id, monitor.port_number);
+            if monitor.interval_ms < MIN_INTERVAL_MS {
+                return Err(invalid(format!("{}: interval must be at least {}ms", name, MIN_INTERVAL_MS)).into());
+            }
+            if settings.monitors[..i]
+                .iter()
+                .any(|m| m.tunnel_id == monitor.tunnel_id && m.port_number == monitor.port_number)
+            {
+                return Err(invalid(format!("{}: monitored more than once", name)).into());
+            }
+            health::validate(&monitor.url, &monitor.check)?;
+        }
+
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, serde_json::to_string_pretty(&settings)?)?;
+        }
+        *self.settings.lock().unwrap() = settings;
+        // New or changed monitors run right away
+        self.next_run.lock().unwrap().clear();
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    // Monitors whose check is due; each is scheduled one interval ahead
+    pub fn take_due(&self) -> Vec<PortMonitor> {
+        let now = Instant::now();
+        let mut next_run = self.next_run.lock().unwrap();
+
+        self.settings()
+            .monitors
+            .into_iter()
+            .filter(|monitor| {
+                let next = next_run.entry(key(&monitor.tunnel_id, monitor.port_number)).or_insert(now);
+                if *next > now {
+                    return false;
+                }
+                *next = now + Duration::from_millis(monitor.interval_ms);
+                true
+            })
+            .collect()
+    }
+
+    // Returns when the next check may be due, or right away when the
+    // monitors change
+    pub async fn wait_next(&self) {
+        let next = {
+            let next_run = self.next_run.lock().unwrap();
+            self.settings()
+                .monitors
+                .iter()
+                .map(|m| next_run.get(&key(&m.tunnel_id, m.port_number)).copied().unwrap_or_else(Instant::now))
+                .min()
+        };
+
+        match next {
+            Some(at) => {
+                tokio::select! {
+                    _ = tokio::time::sleep_until(at.into()) => {}
+                    _ = self.wake.notified() => {}
+                }
+            }
+            None => self.wake.notified().await,
+        }
+    }
+
+    // Add the outcome of a check to the port's history. Returns the event to
+    // report when the port went from up to down or back, and whether the
+    // sample made it to disk; it is kept in memory either way.
+    pub fn record(&self, monitor: &PortMonitor, result: &PingResult) -> (Option<PortHealthChangedEvent>, Result<()>) {
+        let sample = HealthSample {
+            timestamp: Utc::now(),
+            up: result.success,
+            latency_ms: result.response_time_ms,
+            status_code: result.status_code,
+            error: result.error.clone(),
+        };
+        let (previous, written) = self.push(&monitor.tunnel_id, monitor.port_number, sample.clone());
+
+        let event = (previous.is_some_and(|up| up != sample.up)).then(|| PortHealthChangedEvent {
+            tunnel_id: monitor.tunnel_id.clone(),
+            port_number: monitor.port_number,
+            up: sample.up,
+            sample,
+        });
+        (event, written)
+    }
+
+    // Append a sample; returns whether the port was up at the previous one
+    // and the outcome of writing the sample to the history file
+    fn push(&self, tunnel_id: &str, port_number: u16, sample: HealthSample) -> (Option<bool>, Result<()>) {
+        let key = key(tunnel_id, port_number);
+        let mut all = self.series.lock().unwrap();
+        let series = all.entry(key.clone()).or_insert_with(|| self.read_history(&key));
+        let previous = series.samples.back().map(|s| s.up);
+
+        let cutoff = sample.timestamp - retention();
+        while series.samples.front().is_some_and(|s| s.timestamp < cutoff) {
+            series.samples.pop_front();
+        }
+        series.samples.push_back(sample);
+
+        let written = match self.history_file(&key) {
+            Some(file) => {
+                let compact = Utc::now() - series.compacted_at > chrono::Duration::days(1);
+                let written = if compact {
+                    series.compacted_at = Utc::now();
+                    write_history(&file, &series.samples)
+                } else {
+                    append_history(&file, series.samples.back().unwrap())
+                };
+                written.with_context(|| format!("Failed to write health history {}", file.display()))
+            }
+            None => Ok(()),
+        };
+
+        (previous, written)
+    }
+
+    // Tunnel IDs come from the user, so they are sanitized like host log names
+    fn history_file(&self, key: &str) -> Option<PathBuf> {
+        self.history_dir.as_ref().map(|dir| dir.join(format!("{}.jsonl", safe_file_name(key))))
+    }
+
+    // History left by a previous run, without expired samples
+    fn read_history(&self, key: &str) -> Series {
+        let cutoff = Utc::now() - retention();
+        let samples: VecDeque<HealthSample> = self
+            .history_file(key)
+            .and_then(|file| std::fs::read_to_string(file).ok())
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<HealthSample>(line).ok())
+                    .filter(|s| s.timestamp >= cutoff)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if let Some(file) = self.history_file(key).filter(|file| file.exists()) {
+            let _ = write_history(&file, &samples);
+        }
+        Series {
+            samples,
+            compacted_at: Utc::now(),
+        }
+    }
+
+    // Stats of every configured monitor over the last `window`
+    pub fn health(&self, window: Duration) -> Vec<PortHealth> {
+        let cutoff = Utc::now() - chrono::Duration::from_std(window).unwrap_or_else(|_| retention());
+        let mut all = self.series.lock().unwrap();
+
+        self.settings()
+            .monitors
+            .into_iter()
+            .map(|monitor| {
+                let key = key(&monitor.tunnel_id, monitor.port_number);
+                let series = all.entry(key.clone()).or_insert_with(|| self.read_history(&key));
+                let recent: Vec<&HealthSample> = series.samples.iter().filter(|s| s.timestamp >= cutoff).collect();
+                let last = series.samples.back();
+
+                let up = recent.iter().filter(|s| s.up).count();
+                let mut latencies: Vec<u64> = recent.iter().filter(|s| s.up).map(|s| s.latency_ms).collect();
+                latencies.sort_unstable();
+
+                PortHealth {
+                    tunnel_id: monitor.tunnel_id,
+                    port_number: monitor.port_number,
+                    url: monitor.url,
+                    up: last.map(|s| s.up),
+                    last_checked_at: last.map(|s| s.timestamp.to_rfc3339()),
+                    last_error: last.and_then(|s| s.error.clone()),
+                    samples: recent.len(),
+                    uptime_percent: (!recent.is_empty()).then(|| up as f64 * 100.0 / recent.len() as f64),
+                    latency_p50_ms: percentile(&latencies, 50.0),
+                    latency_p95_ms: percentile(&latencies, 95.0),
+                    latency_p99_ms: percentile(&latencies, 99.0),
+                }
+            })
+            .collect()
+    }
+
+    // Raw samples of one port, oldest first
+    pub fn history(&self, tunnel_id: &str, port_number: u16, since: Option<DateTime<Utc>>) -> Vec<HealthSample> {
+        let key = key(tunnel_id, port_number);
+        let mut all = self.series.lock().unwrap();
+        let series = all.entry(key.clone()).or_insert_with(|| self.read_history(&key));
+        series
+            .samples
+            .iter()
+            .filter(|s| since.is_none_or(|since| s.timestamp >= since))
+            .cloned()
+            .collect()
+    }
+}
+
+fn append_history(file: &PathBuf, sample: &HealthSample) -> Result<()> {
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut out = std::fs::OpenOptions::new().create(true).append(true).open(file)?;
+    writeln!(out, "{}", serde_json::to_string(sample)?)?;
+    Ok(())
+}
+
+fn write_history(file: &PathBuf, samples: &VecDeque<HealthSample>) -> Result<()> {
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut content = String::new();
+    for sample in samples {
+        content.push_str(&serde_json::to_string(sample)?);
+        content.push('\n');
+    }
+    std::fs::write(file, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port_monitor(port_number: u16) -> PortMonitor {
+        PortMonitor {
+            tunnel_id: "demo.usw2".to_string(),
+            port_number,
+            url: format!("https://demo-{}.usw2.devtunnels.ms/", port_number),
+            interval_ms: 60_000,
+            check: HealthCheckOptions::default(),
+        }
+    }
+
+    fn sample(minutes_ago: i64, up: bool, latency_ms: u64) -> HealthSample {
+        HealthSample {
+            timestamp: Utc::now() - chrono::Duration::minutes(minutes_ago),
+            up,
+            latency_ms,
+            status_code: up.then_some(200),
+            error: (!up).then(|| "Connection refused".to_string()),
+        }
+    }
+
+    fn ping(success: bool) -> PingResult {
+        PingResult {
+            success,
+            status_code: success.then_some(200),
+            response_time_ms: 42,
+            error: (!success).then(|| "Unexpected status 502 (expected 2xx)".to_string()),
+            timings: PingTimings::default(),
+            remote_address: None,
+            body_matched: None,
+        }
+    }
+
+    #[test]
+    fn test_flips_become_events() {
+        let monitor = HealthMonitor::load(None, None);
+        let port = port_monitor(3000);
+
+        let record = |success| monitor.record(&port, &ping(success)).0;
+
+        // The first result is a baseline, not a change
+        assert!(record(true).is_none());
+        assert!(record(true).is_none());
+        let down = record(false).unwrap();
+        assert!(!down.up);
+        assert_eq!(down.port_number, 3000);
+        assert_eq!(down.sample.error.as_deref(), Some("Unexpected status 502 (expected 2xx)"));
+        assert!(record(false).is_none());
+        assert!(record(true).unwrap().up);
+    }
+
+    #[test]
+    fn test_uptime_and_percentiles() {
+        let monitor = HealthMonitor::load(None, None);
+        monitor.set_settings(MonitorSettings { monitors: vec![port_monitor(3000), port_monitor(4000)] }).unwrap();
+
+        // Outside a 1h window
+        assert!(monitor.push("demo.usw2", 3000, sample(120, false, 5000)).1.is_ok());
+        for (i, latency) in (10..=100).step_by(10).enumerate() {
+            assert!(monitor.push("demo.usw2", 3000, sample(50 - i as i64, true, latency)).1.is_ok());
+        }
+        assert!(monitor.push("demo.usw2", 3000, sample(5, false, 5000)).1.is_ok());
+        assert!(monitor.push("demo.usw2", 3000, sample(1, false, 5000)).1.is_ok());
+
+        let health = monitor.health(Duration::from_secs(60 * 60));
+        assert_eq!(health.len(), 2);
+        let port = &health[0];
+        assert_eq!(port.samples, 12);
+        assert_eq!(port.up, Some(false));
+        assert_eq!(port.last_error.as_deref(), Some("Connection refused"));
+        assert!((port.uptime_percent.unwrap() - 83.333).abs() < 0.01);
+        assert_eq!(port.latency_p50_ms, Some(50));
+        assert_eq!(port.latency_p95_ms, Some(100));
+        assert_eq!(port.latency_p99_ms, Some(100));
+
+        // Never checked yet
+        assert_eq!(health[1].samples, 0);
+        assert_eq!(health[1].up, None);
+        assert_eq!(health[1].uptime_percent, None);
+
+        assert_eq!(monitor.history("demo.usw2", 3000, None).len(), 13);
+        assert_eq!(monitor.history("demo.usw2", 3000, Some(Utc::now() - chrono::Duration::minutes(6))).len(), 2);
+    }
+
+    #[test]
+    fn test_history_survives_reload() {
+        let dir = std::env::temp_dir().join(format!("devtunnel-gui-monitor-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let load = || HealthMonitor::load(Some(dir.join(MONITOR_FILE_NAME)), Some(dir.join(HISTORY_DIR_NAME)));
+
+        let first = load();
+        first.set_settings(MonitorSettings { monitors: vec![port_monitor(3000)] }).unwrap();
+        assert!(first.push("demo.usw2", 3000, sample(RETENTION_DAYS * 24 * 60 + 60, true, 10)).1.is_ok());
+        assert!(first.push("demo.usw2", 3000, sample(10, true, 20)).1.is_ok());
+        assert!(first.push("demo.usw2", 3000, sample(5, false, 5000)).1.is_ok());
+
+        let second = load();
+        assert_eq!(second.settings().monitors.len(), 1);
+        // The expired sample is gone, also from the file
+        let history = second.history("demo.usw2", 3000, None);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].latency_ms, 20);
+        let file = std::fs::read_to_string(dir.join(HISTORY_DIR_NAME).join("demo.usw2-3000.jsonl")).unwrap();
+        assert_eq!(file.lines().count(), 2);
+
+        // A hostile tunnel ID stays inside the history directory
+        assert!(second.push("../../escape", 3000, sample(1, true, 10)).1.is_ok());
+        assert!(dir.join(HISTORY_DIR_NAME).join(".._.._escape-3000.jsonl").exists());
+
+        // Write failures are returned, and the sample is still kept
+        let blocked = HealthMonitor::load(None, Some(dir.join(MONITOR_FILE_NAME)));
+        let (_, written) = blocked.record(&port_monitor(3000), &ping(true));
+        assert!(written.is_err());
+        assert_eq!(blocked.history("demo.usw2", 3000, None).len(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_schedule_and_validation() {
+        let monitor = HealthMonitor::load(None, None);
+        monitor.set_settings(MonitorSettings { monitors: vec![port_monitor(3000)] }).unwrap();
+
+        assert_eq!(monitor.take_due().len(), 1);
+        // Not due again until the interval has passed
+        assert!(monitor.take_due().is_empty());
+
+        let mut fast = port_monitor(4000);
+        fast.interval_ms = 1000;
+        assert!(monitor.set_settings(MonitorSettings { monitors: vec![fast] }).is_err());
+        assert!(monitor.set_settings(MonitorSettings { monitors: vec![port_monitor(3000), port_monitor(3000)] }).is_err());
+        let mut bad = port_monitor(5000);
+        bad.check.expected_status = vec!["fine".to_string()];
+        assert!(monitor.set_settings(MonitorSettings { monitors: vec![bad] }).is_err());
+        assert_eq!(monitor.settings().monitors.len(), 1);
+    }
+}
@@ -0,0 +1,181 @@
+// Timeouts and cancellation for backend calls. Every CLI child is spawned with
+// kill_on_drop, so giving up on the future here (deadline hit or the UI called
+// cancel_operation) also kills the devtunnel process behind it.
+use crate::error::DevTunnelError;
+use crate::types::*;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+
+pub const TIMEOUTS_FILE_NAME: &str = "cli-timeouts.json";
+
+impl Default for CliTimeouts {
+    fn default() -> Self {
+        let operations = [
+            // Waits for the user to finish signing in in the browser
+            ("login", 300_000),
+            // One `show` per tunnel on top of `list`
+            ("list_tunnels", 120_000),
+            ("enrich_tunnel_details", 120_000),
+            ("poll_state", 120_000),
+            ("list_clusters", 60_000),
+            ("apply_spec", 600_000),
+        ];
+        Self {
+            default_ms: 30_000,
+            operations: operations.iter().map(|(op, ms)| (op.to_string(), *ms)).collect(),
+        }
+    }
+}
+
+impl CliTimeouts {
+    pub fn for_operation(&self, name: &str) -> Duration {
+        Duration::from_millis(*self.operations.get(name).unwrap_or(&self.default_ms))
+    }
+}
+
+#[derive(Clone)]
+pub struct CancelToken(Arc<watch::Sender<bool>>);
+
+impl CancelToken {
+    fn new() -> Self {
+        Self(Arc::new(watch::Sender::new(false)))
+    }
+
+    pub fn cancel(&self) {
+        self.0.send_replace(true);
+    }
+
+    pub async fn cancelled(&self) {
+        let mut rx = self.0.subscribe();
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+pub struct Operations {
+    path: Option<PathBuf>,
+    timeouts: Mutex<CliTimeouts>,
+    active: Mutex<HashMap<String, CancelToken>>,
+}
+
+impl Operations {
+    // Timeouts are read from (and saved to) `path`; None keeps them in memory
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let timeouts = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            path,
+            timeouts: Mutex::new(timeouts),
+            active: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn timeouts(&self) -> CliTimeouts {
+        self.timeouts.lock().unwrap().clone()
+    }
+
+    pub fn set_timeouts(&self, timeouts: CliTimeouts) -> Result<()> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, serde_json::to_string_pretty(&timeouts)?)?;
+        }
+        *self.timeouts.lock().unwrap() = timeouts;
+        Ok(())
+    }
+
+    // Run `fut` under the timeout configured for `name`. With an `op_id` the
+    // operation can also be aborted through cancel().
+    pub async fn run<T>(
+        &self,
+        name: &str,
+        op_id: Option<String>,
+        fut: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let limit = self.timeouts().for_operation(name);
+        let token = op_id.as_ref().map(|id| {
+            let token = CancelToken::new();
+            self.active.lock().unwrap().insert(id.clone(), token.clone());
+            token
+        });
+
+        let result = tokio::select! {
+            result = tokio::time::timeout(limit, fut) => result.unwrap_or_else(|_| {
+                Err(DevTunnelError::new(
+                    ErrorCode::Timeout,
+                    format!("{} timed out after {}s", name, limit.as_secs_f64()),
+                )
+                .into())
+            }),
+            _ = async {
+                match &token {
+                    Some(token) => token.cancelled().await,
+                    None => std::future::pending().await,
+                }
+            } => Err(DevTunnelError::new(ErrorCode::Cancelled, format!("{} was cancelled", name)).into()),
+        };
+
+        if let Some(id) = op_id {
+            self.active.lock().unwrap().remove(&id);
+        }
+        result
+    }
+
+    // false when no operation with that id is in flight
+    pub fn cancel(&self, op_id: &str) -> bool {
+        match self.active.lock().unwrap().remove(op_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::error_code;
+
+    #[tokio::test]
+    async fn test_timeout_and_cancel() {
+        let ops = Arc::new(Operations::load(None));
+        let mut timeouts = CliTimeouts::default();
+        timeouts.operations.insert("slow".to_string(), 50);
+        ops.set_timeouts(timeouts).unwrap();
+
+        let slow = ops.run("slow", None, async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        });
+        assert_eq!(error_code(&slow.await.unwrap_err()), ErrorCode::Timeout);
+
+        let running = tokio::spawn({
+            let ops = ops.clone();
+            async move {
+                ops.run("show_tunnel", Some("op-1".to_string()), async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok(())
+                })
+                .await
+            }
+        });
+        while !ops.cancel("op-1") {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(error_code(&running.await.unwrap().unwrap_err()), ErrorCode::Cancelled);
+        assert!(!ops.cancel("op-1"));
+
+        assert_eq!(ops.run("fast", Some("op-2".to_string()), async { Ok(7) }).await.unwrap(), 7);
+    }
+}
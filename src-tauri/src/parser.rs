@@ -1,11 +1,20 @@
 use crate::types::*;
 use regex::Regex;
+use std::sync::LazyLock;
+
+static USER_INFO: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"Logged in as (.+?) using (GitHub|Microsoft)\.").unwrap());
+static ACCESS_ENTRY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(Inherited:\s*)?([+-])([^\s\[{},]+)\s*\[([^\]]*)\](?:\s*\(ports:\s*([\d,\s]+)\))?").unwrap()
+});
+static PORT_MAPPING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"Forwarding from (\S+):(\d+) to host port (\d+)").unwrap());
+static RELATIVE_EXPIRATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d+)\s*(days?|d|hours?|h|minutes?|m)$").unwrap());
 
 pub fn parse_user_info(output: &str) -> Option<UserInfo> {
     // Example output: "Logged in as Bae-ChangHyun using GitHub."
-    let re = Regex::new(r"Logged in as (.+?) using (GitHub|Microsoft)\.").ok()?;
-
-    if let Some(caps) = re.captures(output) {
+    if let Some(caps) = USER_INFO.captures(output) {
         let user_name = caps.get(1)?.as_str().to_string();
         let provider_str = caps.get(2)?.as_str();
 
@@ -51,6 +60,10 @@ pub fn parse_tunnel_list(output: &str) -> Vec<TunnelListItem> {
         // Find "days" or "day" keyword to locate expiration
         let expiration_keyword_idx = parts.iter().position(|&p| p == "days" || p == "day");
 
+        let expiration = expiration_keyword_idx
+            .filter(|idx| *idx > 0)
+            .map(|idx| format!("{} {}", parts[idx - 1], parts[idx]));
+
         // Parse expiration
         let expires_at = if let Some(idx) = expiration_keyword_idx {
             if idx > 0 {
@@ -106,46 +119,14 @@ pub fn parse_tunnel_list(output: &str) -> Vec<TunnelListItem> {
             ports,
             status: TunnelStatus::Active,
             expires_at,
+            expiration,
+            details_error: None,
         });
     }
 
     tunnels
 }
 
-pub fn parse_tunnel_show(output: &str) -> Vec<u16> {
-    let mut ports = Vec::new();
-    let lines: Vec<&str> = output.lines().collect();
-
-    // Find the "Ports" line
-    let mut in_ports_section = false;
-
-    for line in lines {
-        let trimmed = line.trim();
-
-        // Check if we've reached the Ports section
-        if trimmed.starts_with("Ports") {
-            in_ports_section = true;
-            continue;
-        }
-
-        // If we're in ports section and line starts with a number, it's a port entry
-        if in_ports_section {
-            // Port lines look like: "  3002  http  https://..."
-            let parts: Vec<&str> = trimmed.split_whitespace().collect();
-            if let Some(first) = parts.first() {
-                if let Ok(port) = first.parse::<u16>() {
-                    ports.push(port);
-                } else {
-                    // If we can't parse a port number, we've left the ports section
-                    break;
-                }
-            }
-        }
-    }
-
-    ports
-}
-
 pub fn parse_port_show(output: &str) -> Option<Port> {
     // Example output:
     // Tunnel ID             : main.asse
@@ -200,6 +181,322 @@ pub fn parse_port_show(output: &str) -> Option<Port> {
     }
 }
 
+// JSON output (`-j`) of newer CLI versions
+
+// Example: {"status":"Logged in","provider":"github","username":"Bae-ChangHyun"}
+// Ok(None) means the CLI answered but nobody is logged in
+pub fn parse_user_info_json(output: &str) -> serde_json::Result<Option<UserInfo>> {
+    let user: CliUser = serde_json::from_str(output)?;
+
+    let logged_in = user
+        .status
+        .as_deref()
+        .map(|s| s.eq_ignore_ascii_case("logged in"))
+        .unwrap_or(true);
+    let Some(user_name) = user.username.filter(|_| logged_in) else {
+        return Ok(None);
+    };
+
+    let provider = match user.provider.as_deref() {
+        Some(p) if p.eq_ignore_ascii_case("github") => AuthProvider::GitHub,
+        _ => AuthProvider::Microsoft,
+    };
+
+    Ok(Some(UserInfo {
+        user_id: user_name.clone(),
+        user_name: Some(user_name),
+        email: None,
+        provider,
+        is_authenticated: true,
+    }))
+}
+
+pub fn parse_tunnel_list_json(output: &str) -> serde_json::Result<Vec<TunnelListItem>> {
+    let list: CliTunnelList = serde_json::from_str(output)?;
+
+    Ok(list
+        .tunnels
+        .into_iter()
+        .map(|t| TunnelListItem {
+            tunnel_id: t.tunnel_id,
+            description: t.description.filter(|d| !d.is_empty()),
+            tags: if t.labels.is_empty() { None } else { Some(t.labels) },
+            // Filled in from `show`, same as the text list
+            ports: Vec::new(),
+            status: TunnelStatus::Active,
+            expires_at: t.tunnel_expiration.as_deref().and_then(expiration_timestamp),
+            expiration: t.tunnel_expiration,
+            details_error: None,
+        })
+        .collect())
+}
+
+pub fn parse_tunnel_details_json(output: &str) -> serde_json::Result<CliTunnelDetails> {
+    let show: CliTunnelShow = serde_json::from_str(output)?;
+    Ok(show.tunnel)
+}
+
+pub fn parse_access_list_json(output: &str) -> serde_json::Result<Vec<AccessEntry>> {
+    let list: CliAccessList = serde_json::from_str(output)?;
+    Ok(list.access_control_entries.into_iter().flat_map(access_entries).collect())
+}
+
+// "Organizations", "Org", "repo", ... as printed by the CLI
+fn access_subject_type(kind: &str) -> AccessSubjectType {
+    match kind.to_lowercase().as_str() {
+        "anonymous" => AccessSubjectType::Anonymous,
+        "org" | "organization" | "organizations" => AccessSubjectType::Organization,
+        "tenant" | "tenants" => AccessSubjectType::Tenant,
+        "repo" | "repository" | "repositories" => AccessSubjectType::Repository,
+        "user" | "users" => AccessSubjectType::User,
+        _ => AccessSubjectType::Unknown,
+    }
+}
+
+// The CLI groups subjects of one type into a single entry; split them up
+fn access_entries(entry: CliAccessEntry) -> Vec<AccessEntry> {
+    let subjects: Vec<Option<String>> = if entry.subjects.is_empty() {
+        vec![None]
+    } else {
+        entry.subjects.into_iter().map(Some).collect()
+    };
+
+    subjects
+        .into_iter()
+        .map(|subject| AccessEntry {
+            entry_type: access_subject_type(&entry.entry_type),
+            subject,
+            provider: entry.provider.clone(),
+            is_deny: entry.is_deny,
+            is_inherited: entry.is_inherited,
+            scopes: entry.scopes.clone(),
+            ports: Vec::new(),
+            expiration: entry.expiration.clone(),
+        })
+        .collect()
+}
+
+// Full Tunnel model from `show` output (JSON or text)
+pub fn tunnel_from_details(details: CliTunnelDetails) -> Tunnel {
+    // Tunnel IDs carry the cluster as a suffix, e.g. "bch.asse"
+    let cluster_id = details
+        .cluster_id
+        .clone()
+        .or_else(|| details.tunnel_id.split_once('.').map(|(_, c)| c.to_string()));
+
+    let allow_anonymous = details
+        .access_control
+        .iter()
+        .any(|e| e.contains("+Anonymous"));
+
+    let ports = details
+        .ports
+        .into_iter()
+        .map(|p| {
+            let mut uris = p.port_forwarding_uris;
+            if let Some(uri) = p.port_uri {
+                if !uris.contains(&uri) {
+                    uris.insert(0, uri);
+                }
+            }
+            let inspect_uri = p
+                .inspect_uri
+                .or_else(|| uris.first().and_then(|u| inspect_uri(u, p.port_number)));
+
+            Port {
+                port_number: p.port_number,
+                protocol: match p.protocol.as_deref() {
+                    Some("http") => Protocol::Http,
+                    Some("https") => Protocol::Https,
+                    _ => Protocol::Auto,
+                },
+                description: p.description.filter(|d| !d.is_empty()),
+                port_forwarding_uris: if uris.is_empty() { None } else { Some(uris) },
+                inspect_uri,
+            }
+        })
+        .collect();
+
+    Tunnel {
+        tunnel_id: details.tunnel_id,
+        cluster_id,
+        domain: details.domain,
+        description: details.description.filter(|d| !d.is_empty()),
+        tags: if details.labels.is_empty() { None } else { Some(details.labels) },
+        ports,
+        status: TunnelStatus::Active,
+        created_at: details.created_at,
+        expires_at: details.tunnel_expiration.as_deref().and_then(expiration_timestamp),
+        allow_anonymous: Some(allow_anonymous),
+        host_connections: details.host_connections,
+        client_connections: details.client_connections,
+        access_control: details.access_control,
+    }
+}
+
+// https://bch-3000.asse.devtunnels.ms/ -> https://bch-3000-inspect.asse.devtunnels.ms/
+fn inspect_uri(port_uri: &str, port_number: u16) -> Option<String> {
+    let marker = format!("-{}.", port_number);
+    let idx = port_uri.find(&marker)?;
+    Some(format!(
+        "{}-{}-inspect.{}",
+        &port_uri[..idx],
+        port_number,
+        &port_uri[idx + marker.len()..]
+    ))
+}
+
+// Text fallback for `show`: the same fields the JSON output carries
+pub fn parse_tunnel_show_details(output: &str) -> CliTunnelDetails {
+    let mut details = CliTunnelDetails::default();
+
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        match key.trim() {
+            "Tunnel ID" => details.tunnel_id = value.to_string(),
+            "Description" if !value.is_empty() => details.description = Some(value.to_string()),
+            "Labels" => details.labels = value.split_whitespace().map(|l| l.to_string()).collect(),
+            "Access control" => {
+                details.access_control = value
+                    .trim_start_matches('{')
+                    .trim_end_matches('}')
+                    .split(", ")
+                    .filter(|e| !e.trim().is_empty())
+                    .map(|e| e.trim().to_string())
+                    .collect()
+            }
+            "Host connections" => details.host_connections = value.parse().ok(),
+            "Client connections" => details.client_connections = value.parse().ok(),
+            "Tunnel Expiration" if !value.is_empty() => details.tunnel_expiration = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    // Port lines look like: "  3002  http  https://..."
+    let mut in_ports_section = false;
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("Ports") {
+            in_ports_section = true;
+            continue;
+        }
+        if !in_ports_section || trimmed.is_empty() {
+            continue;
+        }
+
+        let parts: Vec<&str> = trimmed.split_whitespace().collect();
+        let Some(port_number) = parts.first().and_then(|p| p.parse::<u16>().ok()) else {
+            break;
+        };
+        let is_uri = |p: &&&str| p.starts_with("http://") || p.starts_with("https://");
+        let port_uri = parts.iter().find(is_uri).map(|p| p.to_string());
+
+        details.ports.push(CliTunnelPort {
+            port_number,
+            protocol: parts.get(1).filter(|p| !is_uri(p)).map(|p| p.to_string()),
+            port_forwarding_uris: port_uri.clone().into_iter().collect(),
+            port_uri,
+            ..Default::default()
+        });
+    }
+
+    details
+}
+
+// Text fallback for `access list`. Entries look like "+Anonymous [connect]",
+// "-Org:1234 [connect, manage] (ports: 3000)" or "Inherited: +Anonymous [connect]".
+pub fn parse_access_list(output: &str) -> Vec<AccessEntry> {
+    ACCESS_ENTRY
+        .captures_iter(output)
+        .map(|caps| {
+            let subject = &caps[3];
+            let (entry_type, subject) = match subject.split_once(':') {
+                Some((kind, id)) => (access_subject_type(kind), Some(id.to_string())),
+                None => (access_subject_type(subject), None),
+            };
+
+            AccessEntry {
+                entry_type,
+                subject,
+                provider: None,
+                is_deny: &caps[2] == "-",
+                is_inherited: caps.get(1).is_some(),
+                scopes: caps[4]
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                ports: caps
+                    .get(5)
+                    .map(|ports| {
+                        ports
+                            .as_str()
+                            .split(',')
+                            .filter_map(|p| p.trim().parse().ok())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                expiration: None,
+            }
+        })
+        .collect()
+}
+
+// `token` output, either the JSON object ({"token": "..."}) or the text form
+// with a "Token : eyJ..." line among "Token scope", "Token expiration", ...
+pub fn parse_token_output(output: &str) -> Option<String> {
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(output) {
+        if let Some(token) = value.get("token").and_then(|t| t.as_str()) {
+            return Some(token.to_string());
+        }
+    }
+
+    output.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        let value = value.trim();
+        (key.trim().eq_ignore_ascii_case("token") && !value.is_empty()).then(|| value.to_string())
+    })
+}
+
+// One line of `devtunnel connect` output, e.g.
+// "Forwarding from 127.0.0.1:3000 to host port 3000." IPv6 addresses keep
+// their brackets ("[::1]").
+pub fn parse_port_mapping(line: &str) -> Option<PortMapping> {
+    let caps = PORT_MAPPING.captures(line)?;
+    Some(PortMapping {
+        local_address: caps[1].to_string(),
+        local_port: caps[2].parse().ok()?,
+        remote_port: caps[3].parse().ok()?,
+    })
+}
+
+// Durations like "30 days" count from now, so their timestamp moves with time
+pub fn is_relative_expiration(value: &str) -> bool {
+    chrono::DateTime::parse_from_rfc3339(value).is_err()
+}
+
+// Unix timestamp (seconds) for an expiration the CLI reports either as an
+// RFC 3339 date or as a remaining duration like "30 days" / "8h"
+pub fn expiration_timestamp(value: &str) -> Option<String> {
+    if let Ok(date) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(date.timestamp().to_string());
+    }
+
+    let caps = RELATIVE_EXPIRATION.captures(value.trim())?;
+    let amount: i64 = caps[1].parse().ok()?;
+    let seconds = match &caps[2] {
+        "day" | "days" | "d" => amount * 86400,
+        "hour" | "hours" | "h" => amount * 3600,
+        _ => amount * 60,
+    };
+
+    Some((chrono::Utc::now().timestamp() + seconds).to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,4 +520,141 @@ test2.asse                          0
         assert_eq!(tunnels.len(), 2);
         assert_eq!(tunnels[0].tunnel_id, "bch.asse");
     }
+
+    #[test]
+    fn test_parse_user_info_json() {
+        let output = r#"{"status":"Logged in","provider":"github","username":"Bae-ChangHyun"}"#;
+        let info = parse_user_info_json(output).unwrap().unwrap();
+        assert_eq!(info.user_name, Some("Bae-ChangHyun".to_string()));
+        assert!(matches!(info.provider, AuthProvider::GitHub));
+
+        let output = r#"{"status":"Not logged in"}"#;
+        assert!(parse_user_info_json(output).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_tunnel_list_json() {
+        let output = r#"{"tunnels":[
+            {"tunnelId":"bch.asse","hostConnections":1,"labels":["web"],"portCount":8,"tunnelExpiration":"30 days","description":""},
+            {"tunnelId":"test2.asse","hostConnections":0,"labels":[],"portCount":0,"tunnelExpiration":"2030-01-01T00:00:00Z","description":"test"}
+        ]}"#;
+        let tunnels = parse_tunnel_list_json(output).unwrap();
+        assert_eq!(tunnels.len(), 2);
+        assert_eq!(tunnels[0].tags, Some(vec!["web".to_string()]));
+        assert_eq!(tunnels[0].description, None);
+        assert_eq!(tunnels[1].description, Some("test".to_string()));
+        assert_eq!(tunnels[1].expires_at, Some("1893456000".to_string()));
+
+        assert!(parse_tunnel_list_json("Found 2 tunnels.").is_err());
+    }
+
+    #[test]
+    fn test_parse_tunnel_show_details() {
+        let output = r#"Tunnel ID             : bch.asse
+Description           : demo
+Labels                : web prod
+Access control        : {+Anonymous [connect], +Org:1234 [connect]}
+Host connections      : 1
+Client connections    : 3
+Tunnel Expiration     : 30 days
+Ports                 : 2
+  3000  http  https://bch-3000.asse.devtunnels.ms/
+  8080  auto  https://bch-8080.asse.devtunnels.ms/
+"#;
+        let details = parse_tunnel_show_details(output);
+        assert_eq!(details.tunnel_id, "bch.asse");
+        assert_eq!(details.labels, vec!["web", "prod"]);
+        assert_eq!(details.access_control, vec!["+Anonymous [connect]", "+Org:1234 [connect]"]);
+        assert_eq!(details.host_connections, Some(1));
+        assert_eq!(details.ports.len(), 2);
+        assert_eq!(details.ports[1].protocol.as_deref(), Some("auto"));
+        assert_eq!(details.ports[0].port_uri.as_deref(), Some("https://bch-3000.asse.devtunnels.ms/"));
+
+        let tunnel = tunnel_from_details(details);
+        assert_eq!(tunnel.cluster_id.as_deref(), Some("asse"));
+        assert_eq!(tunnel.allow_anonymous, Some(true));
+        assert_eq!(tunnel.client_connections, Some(3));
+        assert_eq!(tunnel.ports[0].protocol, Protocol::Http);
+        assert_eq!(
+            tunnel.ports[0].inspect_uri.as_deref(),
+            Some("https://bch-3000-inspect.asse.devtunnels.ms/")
+        );
+    }
+
+    #[test]
+    fn test_parse_tunnel_show_details_blank_line_in_ports() {
+        let output = "Tunnel ID             : bch.asse\nPorts                 : 2\n\n  3000  http\n\n  8080  auto\n\nDone.\n";
+        let details = parse_tunnel_show_details(output);
+        let ports: Vec<u16> = details.ports.iter().map(|p| p.port_number).collect();
+        assert_eq!(ports, vec![3000, 8080]);
+    }
+
+    #[test]
+    fn test_tunnel_from_json_details() {
+        let output = r#"{"tunnel":{"tunnelId":"bch.asse","description":"","labels":[],"accessControl":[],
+            "hostConnections":0,"ports":[{"portNumber":8001,"protocol":"auto"}]}}"#;
+        let tunnel = tunnel_from_details(parse_tunnel_details_json(output).unwrap());
+        assert_eq!(tunnel.description, None);
+        assert_eq!(tunnel.tags, None);
+        assert_eq!(tunnel.allow_anonymous, Some(false));
+        assert_eq!(tunnel.host_connections, Some(0));
+        // Not hosted yet, so no forwarding URI to derive an inspect URI from
+        assert_eq!(tunnel.ports[0].port_forwarding_uris, None);
+        assert_eq!(tunnel.ports[0].inspect_uri, None);
+    }
+
+    #[test]
+    fn test_parse_access_list() {
+        let output = "Found 2 access control entries.\n\n+Anonymous [connect] (ports: 3000, 8080)\n-Org:1234 [connect, manage]\n";
+        let entries = parse_access_list(output);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].entry_type, AccessSubjectType::Anonymous);
+        assert_eq!(entries[0].ports, vec![3000, 8080]);
+        assert!(!entries[0].is_deny);
+        assert_eq!(entries[1].entry_type, AccessSubjectType::Organization);
+        assert_eq!(entries[1].subject.as_deref(), Some("1234"));
+        assert!(entries[1].ports.is_empty());
+        assert_eq!(entries[1].scopes, vec!["connect", "manage"]);
+        assert!(entries[1].is_deny);
+
+        let inherited = parse_access_list("{Inherited: +Anonymous [connect]}");
+        assert!(inherited[0].is_inherited);
+
+        let json = r#"{"accessControlEntries":[{"type":"Anonymous","isDeny":false,"isInherited":false,"subjects":[],"scopes":["connect"]}]}"#;
+        assert_eq!(parse_access_list_json(json).unwrap(), parse_access_list("+Anonymous [connect]"));
+
+        let json = r#"{"accessControlEntries":[{"type":"Users","provider":"github","subjects":["1","2"],"scopes":["connect"]}]}"#;
+        let users = parse_access_list_json(json).unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[1].entry_type, AccessSubjectType::User);
+        assert_eq!(users[1].subject.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn test_parse_token_output() {
+        let output = "Tunnel ID             : demo.usw2\nToken scope           : connect\nToken lifetime        : 1.00:00:00\nToken expiration      : 2026-10-19 10:00:00 UTC\nToken                 : eyJa.eyJb.c2ln\n";
+        assert_eq!(parse_token_output(output).as_deref(), Some("eyJa.eyJb.c2ln"));
+        assert_eq!(parse_token_output(r#"{"tunnelId":"demo.usw2","token":"eyJa.eyJb.c2ln"}"#).as_deref(), Some("eyJa.eyJb.c2ln"));
+        assert_eq!(parse_token_output("Tunnel ID : demo.usw2\n"), None);
+    }
+
+    #[test]
+    fn test_parse_port_mapping() {
+        assert_eq!(
+            parse_port_mapping("Forwarding from 127.0.0.1:3000 to host port 3000."),
+            Some(PortMapping {
+                local_address: "127.0.0.1".to_string(),
+                local_port: 3000,
+                remote_port: 3000,
+            })
+        );
+        // Remapped because 8080 was taken locally
+        let mapping = parse_port_mapping("SSH: Forwarding from [::1]:51234 to host port 8080.").unwrap();
+        assert_eq!(mapping.local_address, "[::1]");
+        assert_eq!(mapping.local_port, 51234);
+        assert_eq!(mapping.remote_port, 8080);
+
+        assert_eq!(parse_port_mapping("Connected to tunnel: demo.usw2"), None);
+        assert_eq!(parse_port_mapping("Forwarding from 127.0.0.1:99999 to host port 3000."), None);
+    }
 }
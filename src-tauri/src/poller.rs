@@ -0,0 +1,331 @@
+// Background reconciliation of tunnel state. Every `intervalMs` the poller
+// re-reads the list and each tunnel's `show` (refreshing the shared cache on
+// the way) and reports what changed since the previous pass as tunnel-added /
+// tunnel-removed / tunnel-updated / port-changed events, so the UI notices
+// edits made from other machines or the bare CLI without polling itself.
+use crate::backend::{self, DevTunnelBackend};
+use crate::cache::StateCache;
+use crate::parser;
+use crate::types::*;
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+pub const POLLER_FILE_NAME: &str = "poller.json";
+
+impl Default for PollerSettings {
+    fn default() -> Self {
+        Self { interval_ms: 60_000 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TunnelState {
+    item: TunnelListItem,
+    // None when `show` failed this pass; ports and host state are then unknown
+    details: Option<Tunnel>,
+}
+
+// Keyed by tunnel ID
+pub type Snapshot = BTreeMap<String, TunnelState>;
+
+// One pass: fresh list plus fresh `show` per tunnel, written through the cache
+pub async fn snapshot(backend: Arc<dyn DevTunnelBackend>, cache: &StateCache) -> Result<Snapshot> {
+    let list = backend.list_tunnels_light(None);
+    let tunnels = cache.lists.get_or_fetch(&StateCache::list_key(&None), true, list).await?;
+    let tunnel_ids: Vec<String> = tunnels.iter().map(|t| t.tunnel_id.clone()).collect();
+    let tunnels = backend::enrich_tunnel_details(backend, cache, tunnels, &tunnel_ids, true).await;
+
+    Ok(tunnels
+        .into_iter()
+        .map(|item| {
+            let details = match item.details_error {
+                None => cache.tunnels.get(&item.tunnel_id),
+                Some(_) => None,
+            };
+            (item.tunnel_id.clone(), TunnelState { item, details })
+        })
+        .collect())
+}
+
+pub struct StatePoller {
+    path: Option<PathBuf>,
+    settings: Mutex<PollerSettings>,
+    // Set while the window is hidden
+    paused: AtomicBool,
+    wake: Notify,
+    previous: Mutex<Option<Snapshot>>,
+    last_error: Mutex<Option<String>>,
+}
+
+impl StatePoller {
+    // Settings are read from (and saved to) `path`; None keeps them in memory
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let settings = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            path,
+            settings: Mutex::new(settings),
+            paused: AtomicBool::new(false),
+            wake: Notify::new(),
+            previous: Mutex::new(None),
+            last_error: Mutex::new(None),
+        }
+    }
+
+    pub fn settings(&self) -> PollerSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set_settings(&self, settings: PollerSettings) -> Result<()> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, serde_json::to_string_pretty(&settings)?)?;
+        }
+        *self.settings.lock().unwrap() = settings;
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        if self.paused.swap(paused, Ordering::Relaxed) != paused {
+            self.wake.notify_one();
+        }
+    }
+
+    // Returns when the next pass is due: one interval after the last, or right
+    // away when resuming, since anything may have changed while paused
+    pub async fn wait_next(&self) {
+        let mut was_idle = false;
+        loop {
+            let interval = self.settings().interval_ms;
+            let idle = interval == 0 || self.paused.load(Ordering::Relaxed);
+            if idle {
+                self.wake.notified().await;
+                was_idle = true;
+                continue;
+            }
+            if was_idle {
+                return;
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_millis(interval)) => return,
+                // Interval changed; start over with the new one
+                _ = self.wake.notified() => {}
+            }
+        }
+    }
+
+    // Keep `next` and return the events describing how it differs from the
+    // previous pass. The first pass only records a baseline.
+    pub fn update(&self, next: Snapshot) -> Vec<(&'static str, Value)> {
+        let mut previous = self.previous.lock().unwrap();
+        let events = match previous.as_ref() {
+            Some(prev) => diff(prev, &next),
+            None => Vec::new(),
+        };
+        *previous = Some(next);
+        events
+    }
+
+    // Remember the outcome of a pass; true when `error` is new, so a failure
+    // that persists across passes is only reported once
+    pub fn record_error(&self, error: Option<String>) -> bool {
+        let mut last = self.last_error.lock().unwrap();
+        let changed = *last != error;
+        *last = error.clone();
+        changed && error.is_some()
+    }
+}
+
+fn event<T: Serialize>(name: &'static str, payload: &T) -> Option<(&'static str, Value)> {
+    serde_json::to_value(payload).ok().map(|value| (name, value))
+}
+
+fn field<T: Serialize + PartialEq>(changes: &mut Vec<FieldChange>, name: &str, before: &T, after: &T) {
+    if before != after {
+        changes.push(FieldChange {
+            field: name.to_string(),
+            before: serde_json::to_value(before).unwrap_or(Value::Null),
+            after: serde_json::to_value(after).unwrap_or(Value::Null),
+        });
+    }
+}
+
+fn diff(prev: &Snapshot, next: &Snapshot) -> Vec<(&'static str, Value)> {
+    let mut events = Vec::new();
+
+    for (tunnel_id, state) in next {
+        let Some(old) = prev.get(tunnel_id) else {
+            events.extend(event("tunnel-added", &TunnelAddedEvent { tunnel: state.item.clone() }));
+            continue;
+        };
+
+        let mut changes = Vec::new();
+        field(&mut changes, "description", &old.item.description, &state.item.description);
+        field(&mut changes, "tags", &old.item.tags, &state.item.tags);
+        // A relative expiration's timestamp is counted from the time of the
+        // pass, so it moves every time; compare only when one side is absolute
+        let relative = |item: &TunnelListItem| item.expiration.as_deref().is_some_and(parser::is_relative_expiration);
+        if !(relative(&old.item) && relative(&state.item)) {
+            field(&mut changes, "expiresAt", &old.item.expires_at, &state.item.expires_at);
+        }
+
+        // Only compare what both passes actually saw
+        if let (Some(before), Some(after)) = (&old.details, &state.details) {
+            field(&mut changes, "hostConnections", &before.host_connections, &after.host_connections);
+            field(&mut changes, "accessControl", &before.access_control, &after.access_control);
+
+            let added: Vec<Port> = after
+                .ports
+                .iter()
+                .filter(|p| !before.ports.iter().any(|b| b.port_number == p.port_number))
+                .cloned()
+                .collect();
+            let removed: Vec<u16> = before
+                .ports
+                .iter()
+                .filter(|b| !after.ports.iter().any(|p| p.port_number == b.port_number))
+                .map(|b| b.port_number)
+                .collect();
+            let updated: Vec<Port> = after
+                .ports
+                .iter()
+                .filter(|p| before.ports.iter().any(|b| b.port_number == p.port_number && b != *p))
+                .cloned()
+                .collect();
+
+            if !added.is_empty() || !removed.is_empty() || !updated.is_empty() {
+                events.extend(event("port-changed", &PortChangedEvent {
+                    tunnel_id: tunnel_id.clone(),
+                    added,
+                    removed,
+                    updated,
+                }));
+            }
+        }
+
+        if !changes.is_empty() {
+            events.extend(event("tunnel-updated", &TunnelUpdatedEvent {
+                tunnel_id: tunnel_id.clone(),
+                changes,
+            }));
+        }
+    }
+
+    for tunnel_id in prev.keys().filter(|id| !next.contains_key(*id)) {
+        events.extend(event("tunnel-removed", &TunnelRemovedEvent { tunnel_id: tunnel_id.clone() }));
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake::FakeBackend;
+
+    fn create_req(tunnel_id: &str) -> CreateTunnelRequest {
+        CreateTunnelRequest {
+            tunnel_id: Some(tunnel_id.to_string()),
+            description: None,
+            tags: None,
+            allow_anonymous: None,
+            expiration: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_changes_become_events() {
+        let fake = Arc::new(FakeBackend::new());
+        let backend: Arc<dyn DevTunnelBackend> = fake.clone();
+        let cache = StateCache::default();
+        let poller = StatePoller::load(None);
+
+        fake.create_tunnel(create_req("demo")).await.unwrap();
+        fake.create_tunnel(create_req("old")).await.unwrap();
+        let baseline = snapshot(backend.clone(), &cache).await.unwrap();
+        assert!(poller.update(baseline).is_empty());
+
+        fake.create_tunnel(create_req("new")).await.unwrap();
+        fake.delete_tunnel("old".to_string()).await.unwrap();
+        fake.create_port(CreatePortRequest {
+            tunnel_id: "demo".to_string(),
+            port_number: 3000,
+            protocol: None,
+            description: None,
+        })
+        .await
+        .unwrap();
+        fake.update_tunnel(UpdateTunnelRequest {
+            tunnel_id: "demo".to_string(),
+            description: Some("api".to_string()),
+            tags: None,
+            remove_tags: None,
+            expiration: None,
+        })
+        .await
+        .unwrap();
+
+        let events = poller.update(snapshot(backend.clone(), &cache).await.unwrap());
+        let names: Vec<&str> = events.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, vec!["port-changed", "tunnel-updated", "tunnel-added", "tunnel-removed"]);
+
+        let (_, ports) = &events[0];
+        assert_eq!(ports["tunnelId"], "demo.usw2");
+        assert_eq!(ports["added"][0]["portNumber"], 3000);
+        let (_, updated) = &events[1];
+        assert_eq!(updated["changes"][0]["field"], "description");
+        assert_eq!(updated["changes"][0]["after"], "api");
+
+        // Nothing changed since the last pass
+        assert!(poller.update(snapshot(backend, &cache).await.unwrap()).is_empty());
+
+        assert!(poller.record_error(Some("Not authenticated".to_string())));
+        assert!(!poller.record_error(Some("Not authenticated".to_string())));
+        assert!(!poller.record_error(None));
+    }
+
+    #[tokio::test]
+    async fn test_relative_expiration_is_not_a_change() {
+        let fake = Arc::new(FakeBackend::new());
+        let backend: Arc<dyn DevTunnelBackend> = fake.clone();
+        let cache = StateCache::default();
+        let poller = StatePoller::load(None);
+
+        let mut req = create_req("demo");
+        req.expiration = Some("30 days".to_string());
+        fake.create_tunnel(req).await.unwrap();
+        assert!(poller.update(snapshot(backend.clone(), &cache).await.unwrap()).is_empty());
+
+        // "30 days" now lands on a later timestamp than on the previous pass
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(poller.update(snapshot(backend.clone(), &cache).await.unwrap()).is_empty());
+
+        fake.update_tunnel(UpdateTunnelRequest {
+            tunnel_id: "demo".to_string(),
+            description: None,
+            tags: None,
+            remove_tags: None,
+            expiration: Some("2030-01-01T00:00:00Z".to_string()),
+        })
+        .await
+        .unwrap();
+        let events = poller.update(snapshot(backend, &cache).await.unwrap());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].1["changes"][0]["field"], "expiresAt");
+        assert_eq!(events[0].1["changes"][0]["after"], "1893456000");
+    }
+}
@@ -0,0 +1,126 @@
+// Helpers for host processes identified by PID: signalling supervised process
+// groups and recognising processes adopted from a previous app session.
+use anyhow::Result;
+
+// True if `pid` is alive and is a `devtunnel host <tunnel_id>` process.
+// Guards against the PID having been recycled by an unrelated process.
+pub fn is_host_process(pid: u32, tunnel_id: &str) -> bool {
+    match command_line(pid) {
+        Some(args) => {
+            args.iter().any(|a| a.contains("devtunnel"))
+                && args.iter().any(|a| a == "host")
+                && args.iter().any(|a| a == tunnel_id)
+        }
+        None => false,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn command_line(pid: u32) -> Option<Vec<String>> {
+    let raw = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<String> = raw
+        .split(|b| *b == 0)
+        .filter(|a| !a.is_empty())
+        .map(|a| String::from_utf8_lossy(a).to_string())
+        .collect();
+    if args.is_empty() {
+        None
+    } else {
+        Some(args)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn command_line(pid: u32) -> Option<Vec<String>> {
+    use std::process::Command;
+
+    let output = Command::new("ps")
+        .arg("-p")
+        .arg(pid.to_string())
+        .arg("-o")
+        .arg("command=")
+        .output()
+        .ok()?;
+    let line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || line.is_empty() {
+        None
+    } else {
+        Some(line.split_whitespace().map(|s| s.to_string()).collect())
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn command_line(pid: u32) -> Option<Vec<String>> {
+    use std::process::Command;
+
+    let output = Command::new("wmic")
+        .arg("process")
+        .arg("where")
+        .arg(format!("ProcessId={}", pid))
+        .arg("get")
+        .arg("CommandLine")
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().skip(1).map(|l| l.trim()).find(|l| !l.is_empty())?;
+    Some(line.split_whitespace().map(|s| s.trim_matches('"').to_string()).collect())
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Signal {
+    Terminate,
+    Kill,
+}
+
+// Signal the process group led by `pid` (supervised hosts are spawned as group
+// leaders), or just the process itself if it doesn't lead its own group.
+// A process that has already gone away is not an error.
+#[cfg(unix)]
+pub fn signal(pid: u32, signal: Signal) -> Result<()> {
+    let sig = match signal {
+        Signal::Terminate => libc::SIGTERM,
+        Signal::Kill => libc::SIGKILL,
+    };
+    let pid = pid as libc::pid_t;
+
+    // SAFETY: getpgid/kill only take plain integers and report failure through errno
+    let target = if unsafe { libc::getpgid(pid) } == pid { -pid } else { pid };
+    if unsafe { libc::kill(target, sig) } == 0 {
+        return Ok(());
+    }
+
+    let err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::ESRCH) {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Failed to send {:?} to process {}: {}", signal, pid, err))
+    }
+}
+
+// Windows has no SIGTERM; taskkill without /F asks the process tree to close,
+// with /F it terminates it outright
+#[cfg(target_os = "windows")]
+pub fn signal(pid: u32, signal: Signal) -> Result<()> {
+    use anyhow::Context;
+    use std::process::Command;
+
+    let mut cmd = Command::new("taskkill");
+    cmd.arg("/PID").arg(pid.to_string()).arg("/T");
+    if let Signal::Kill = signal {
+        cmd.arg("/F");
+    }
+
+    let output = cmd.output().context("Failed to execute taskkill")?;
+
+    // taskkill exits with 128 when the process no longer exists
+    if output.status.success() || output.status.code() == Some(128) {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Failed to send {:?} to process {}: {}",
+            signal,
+            pid,
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+}
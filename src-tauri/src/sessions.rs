@@ -0,0 +1,158 @@
+use crate::types::*;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+pub const SESSION_FILE_NAME: &str = "host-sessions.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SessionFile {
+    #[serde(rename = "restoreOnStartup", default)]
+    restore_on_startup: bool,
+    #[serde(default)]
+    sessions: Vec<HostSession>,
+}
+
+// Hosted tunnel sessions persisted to disk so the next app launch knows which
+// `devtunnel host` processes it started. An entry is written when a host is
+// spawned and removed when it is stopped or exits for good; entries left over
+// from a previous run are reconciled at startup.
+pub struct SessionStore {
+    path: PathBuf,
+    file: Mutex<SessionFile>,
+    // Why the file on disk was ignored, until the supervisor reports it
+    load_error: Mutex<Option<String>>,
+}
+
+impl SessionStore {
+    pub fn load(path: PathBuf) -> Self {
+        let mut load_error = None;
+        let file = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                load_error = Some(format!("Ignoring corrupt session file {}: {}", path.display(), e));
+                SessionFile::default()
+            }),
+            Err(_) => SessionFile::default(),
+        };
+
+        Self {
+            path,
+            file: Mutex::new(file),
+            load_error: Mutex::new(load_error),
+        }
+    }
+
+    pub fn take_load_error(&self) -> Option<String> {
+        self.load_error.lock().unwrap().take()
+    }
+
+    pub fn sessions(&self) -> Vec<HostSession> {
+        self.file.lock().unwrap().sessions.clone()
+    }
+
+    // Changes apply in memory even when saving them fails, so a full disk
+    // doesn't break hosting; the error is for the caller to report
+    pub fn upsert(&self, session: HostSession) -> Result<()> {
+        let mut file = self.file.lock().unwrap();
+        file.sessions.retain(|s| s.tunnel_id != session.tunnel_id);
+        file.sessions.push(session);
+        self.save(&file)
+    }
+
+    pub fn remove(&self, tunnel_id: &str) -> Result<()> {
+        let mut file = self.file.lock().unwrap();
+        let before = file.sessions.len();
+        file.sessions.retain(|s| s.tunnel_id != tunnel_id);
+        if file.sessions.len() != before {
+            self.save(&file)?;
+        }
+        Ok(())
+    }
+
+    pub fn restore_on_startup(&self) -> bool {
+        self.file.lock().unwrap().restore_on_startup
+    }
+
+    pub fn set_restore_on_startup(&self, enabled: bool) -> Result<()> {
+        let mut file = self.file.lock().unwrap();
+        file.restore_on_startup = enabled;
+        self.save(&file)
+    }
+
+    fn save(&self, file: &SessionFile) -> Result<()> {
+        self.write(file)
+            .with_context(|| format!("Failed to save session file {}", self.path.display()))
+    }
+
+    fn write(&self, file: &SessionFile) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).context("Failed to create session directory")?;
+        }
+
+        // Write to a temporary file first so a crash never leaves a truncated session file
+        let tmp_path = self.path.with_extension("json.tmp");
+        let content = serde_json::to_string_pretty(file)?;
+        std::fs::write(&tmp_path, content).context("Failed to write session file")?;
+        std::fs::rename(&tmp_path, &self.path).context("Failed to replace session file")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(tunnel_id: &str, pid: u32) -> HostSession {
+        HostSession {
+            tunnel_id: tunnel_id.to_string(),
+            pid,
+            started_at: "2024-01-15T14:30:25+00:00".to_string(),
+            program: "devtunnel".to_string(),
+            args: vec!["host".to_string(), tunnel_id.to_string()],
+            request: HostTunnelRequest {
+                tunnel_id: Some(tunnel_id.to_string()),
+                ports: vec![3000],
+                protocol: None,
+                allow_anonymous: None,
+                expiration: None,
+                restart_policy: None,
+                upstream: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_session_store_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("devtunnel-gui-sessions-{}", std::process::id()))
+            .join(SESSION_FILE_NAME);
+
+        let store = SessionStore::load(path.clone());
+        store.upsert(session("a.usw2", 100)).unwrap();
+        store.upsert(session("b.usw2", 200)).unwrap();
+        store.upsert(session("a.usw2", 300)).unwrap();
+        store.remove("b.usw2").unwrap();
+        store.set_restore_on_startup(true).unwrap();
+
+        let reloaded = SessionStore::load(path.clone());
+        assert!(reloaded.restore_on_startup());
+        assert_eq!(reloaded.sessions().len(), 1);
+        assert_eq!(reloaded.sessions()[0].pid, 300);
+        assert!(reloaded.take_load_error().is_none());
+
+        // A corrupt file is reported once and starts from scratch
+        std::fs::write(&path, "{ not json").unwrap();
+        let corrupt = SessionStore::load(path.clone());
+        assert!(corrupt.sessions().is_empty());
+        assert!(corrupt.take_load_error().unwrap().starts_with("Ignoring corrupt session file"));
+        assert!(corrupt.take_load_error().is_none());
+
+        // Save failures are returned, and the change is still made in memory
+        let blocked = SessionStore::load(path.join(SESSION_FILE_NAME));
+        assert!(blocked.upsert(session("a.usw2", 100)).is_err());
+        assert_eq!(blocked.sessions().len(), 1);
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
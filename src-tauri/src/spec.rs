@@ -0,0 +1,316 @@
+use crate::access;
+use crate::backend::DevTunnelBackend;
+use crate::cache::same_tunnel;
+use crate::types::*;
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::Path;
+
+// YAML for .yaml/.yml files, TOML otherwise
+pub fn load_spec(path: &Path) -> Result<TunnelSpecFile> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read spec file {}", path.display()))?;
+    let format = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    parse_spec(&text, &format)
+}
+
+pub fn parse_spec(text: &str, format: &str) -> Result<TunnelSpecFile> {
+    let spec: TunnelSpecFile = match format {
+        "yaml" | "yml" => serde_yaml::from_str(text).context("Invalid YAML spec")?,
+        _ => toml::from_str(text).context("Invalid TOML spec")?,
+    };
+
+    let mut tunnel_ids = HashSet::new();
+    for tunnel in &spec.tunnels {
+        if !tunnel_ids.insert(tunnel.tunnel_id.as_str()) {
+            return Err(anyhow::anyhow!("Tunnel {} is listed more than once", tunnel.tunnel_id));
+        }
+        let mut ports = HashSet::new();
+        for port in &tunnel.ports {
+            if !ports.insert(port.port_number) {
+                return Err(anyhow::anyhow!(
+                    "Port {} is listed more than once for tunnel {}",
+                    port.port_number,
+                    tunnel.tunnel_id
+                ));
+            }
+        }
+    }
+
+    Ok(spec)
+}
+
+fn sorted(tags: &[String]) -> Vec<String> {
+    let mut tags = tags.to_vec();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+fn protocol_name(protocol: &Protocol) -> &'static str {
+    match protocol {
+        Protocol::Http => "http",
+        Protocol::Https => "https",
+        Protocol::Auto => "auto",
+    }
+}
+
+// Steps that bring the account in line with `spec`. Tunnels missing from the
+// spec are never touched; ports missing from a listed tunnel are deleted.
+pub async fn plan_spec(backend: &dyn DevTunnelBackend, spec: &TunnelSpecFile) -> Result<SpecPlan> {
+    let existing = backend.list_tunnels_light(None).await?;
+    let mut steps = Vec::new();
+
+    for tunnel in &spec.tunnels {
+        match existing.iter().find(|t| same_tunnel(&t.tunnel_id, &tunnel.tunnel_id)) {
+            Some(current) => plan_existing(backend, tunnel, current, &mut steps).await?,
+            None => plan_new(tunnel, &mut steps)?,
+        }
+    }
+
+    Ok(SpecPlan { steps })
+}
+
+fn plan_new(tunnel: &TunnelSpec, steps: &mut Vec<SpecStep>) -> Result<()> {
+    let id = &tunnel.tunnel_id;
+    steps.push(SpecStep {
+        summary: format!("Create tunnel {}", id),
+        action: SpecAction::CreateTunnel {
+            req: CreateTunnelRequest {
+                tunnel_id: Some(id.clone()),
+                description: tunnel.description.clone(),
+                tags: tunnel.tags.clone().filter(|tags| !tags.is_empty()),
+                allow_anonymous: None,
+                expiration: tunnel.expiration.clone(),
+            },
+        },
+    });
+
+    for port in &tunnel.ports {
+        steps.push(create_port_step(id, port));
+    }
+
+    if let Some(desired) = &tunnel.access {
+        plan_access(id, Vec::new(), desired, steps)?;
+    }
+    Ok(())
+}
+
+async fn plan_existing(
+    backend: &dyn DevTunnelBackend,
+    tunnel: &TunnelSpec,
+    current: &TunnelListItem,
+    steps: &mut Vec<SpecStep>,
+) -> Result<()> {
+    let id = &tunnel.tunnel_id;
+
+    // Text `list` output has no labels column; `show` does
+    let current_tags = match &current.tags {
+        Some(tags) => tags.clone(),
+        None if tunnel.tags.is_some() => backend.show_tunnel(Some(id.clone())).await?.tags.unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    let description = tunnel
+        .description
+        .clone()
+        .filter(|d| current.description.as_deref().unwrap_or("") != d);
+    let tags = tunnel
+        .tags
+        .as_ref()
+        .filter(|tags| sorted(tags) != sorted(&current_tags));
+
+    if description.is_some() || tags.is_some() {
+        let mut changed = Vec::new();
+        if description.is_some() {
+            changed.push("description");
+        }
+        if tags.is_some() {
+            changed.push("tags");
+        }
+        steps.push(SpecStep {
+            summary: format!("Update tunnel {} ({})", id, changed.join(", ")),
+            action: SpecAction::UpdateTunnel {
+                req: UpdateTunnelRequest {
+                    tunnel_id: id.clone(),
+                    description,
+                    tags: tags.filter(|tags| !tags.is_empty()).cloned(),
+                    remove_tags: tags.filter(|tags| tags.is_empty()).map(|_| true),
+                    expiration: None,
+                },
+            },
+        });
+    }
+
+    let current_ports = backend.list_ports(id.clone()).await?;
+    for port in &tunnel.ports {
+        let Some(existing) = current_ports.iter().find(|p| p.port_number == port.port_number) else {
+            steps.push(create_port_step(id, port));
+            continue;
+        };
+
+        let protocol = port.protocol.clone().filter(|p| *p != existing.protocol);
+        let description = port
+            .description
+            .clone()
+            .filter(|d| existing.description.as_deref().unwrap_or("") != d);
+        if protocol.is_some() || description.is_some() {
+            steps.push(SpecStep {
+                summary: format!("Update port {} on {}", port.port_number, id),
+                action: SpecAction::UpdatePort {
+                    req: UpdatePortRequest {
+                        tunnel_id: id.clone(),
+                        port_number: port.port_number,
+                        description,
+                        protocol,
+                    },
+                },
+            });
+        }
+    }
+
+    for existing in &current_ports {
+        if !tunnel.ports.iter().any(|p| p.port_number == existing.port_number) {
+            steps.push(SpecStep {
+                summary: format!("Delete port {} from {}", existing.port_number, id),
+                action: SpecAction::DeletePort {
+                    tunnel_id: id.clone(),
+                    port_number: existing.port_number,
+                },
+            });
+        }
+    }
+
+    if let Some(desired) = &tunnel.access {
+        let current_access = backend.list_access_entries(id.clone()).await?;
+        plan_access(id, current_access, desired, steps)?;
+    }
+    Ok(())
+}
+
+fn create_port_step(tunnel_id: &str, port: &PortSpec) -> SpecStep {
+    let protocol = port.protocol.as_ref().map(protocol_name).unwrap_or("auto");
+    SpecStep {
+        summary: format!("Create port {} ({}) on {}", port.port_number, protocol, tunnel_id),
+        action: SpecAction::CreatePort {
+            req: CreatePortRequest {
+                tunnel_id: tunnel_id.to_string(),
+                port_number: port.port_number,
+                protocol: port.protocol.clone(),
+                description: port.description.clone(),
+            },
+        },
+    }
+}
+
+fn plan_access(
+    tunnel_id: &str,
+    current: Vec<AccessEntry>,
+    desired: &[AccessControlEntry],
+    steps: &mut Vec<SpecStep>,
+) -> Result<()> {
+    let plan = access::diff_access(tunnel_id, current, desired)?;
+
+    for (change, summary) in plan.changes.into_iter().zip(plan.commands) {
+        let tunnel_id = tunnel_id.to_string();
+        let action = match change {
+            AccessChange::Create { entry } => SpecAction::CreateAccess {
+                req: CreateAccessRequest { tunnel_id, entry },
+            },
+            AccessChange::Delete { entry } => SpecAction::DeleteAccess {
+                req: DeleteAccessRequest { tunnel_id, entry },
+            },
+            AccessChange::Reset => SpecAction::ResetAccess { tunnel_id },
+        };
+        steps.push(SpecStep { summary, action });
+    }
+    Ok(())
+}
+
+// Runs the steps in order and stops at the first failure, since later steps
+// usually depend on earlier ones (ports on their tunnel)
+pub async fn apply_plan(
+    backend: &dyn DevTunnelBackend,
+    plan: SpecPlan,
+    mut on_step: impl FnMut(&SpecStepResult),
+) -> SpecApplyReport {
+    let mut results = Vec::new();
+    let mut failed = false;
+
+    for step in plan.steps {
+        let result = if failed {
+            SpecStepResult { step, status: SpecStepStatus::Skipped, error: None }
+        } else {
+            match run_step(backend, &step.action).await {
+                Ok(_) => SpecStepResult { step, status: SpecStepStatus::Applied, error: None },
+                Err(e) => {
+                    failed = true;
+                    SpecStepResult { step, status: SpecStepStatus::Failed, error: Some(e.to_string()) }
+                }
+            }
+        };
+        on_step(&result);
+        results.push(result);
+    }
+
+    SpecApplyReport { results, success: !failed }
+}
+
+async fn run_step(backend: &dyn DevTunnelBackend, action: &SpecAction) -> Result<String> {
+    match action.clone() {
+        SpecAction::CreateTunnel { req } => backend.create_tunnel(req).await,
+        SpecAction::UpdateTunnel { req } => backend.update_tunnel(req).await,
+        SpecAction::CreatePort { req } => backend.create_port(req).await,
+        SpecAction::UpdatePort { req } => backend.update_port(req).await,
+        SpecAction::DeletePort { tunnel_id, port_number } => backend.delete_port(tunnel_id, port_number).await,
+        SpecAction::CreateAccess { req } => backend.create_access(req).await,
+        SpecAction::DeleteAccess { req } => backend.delete_access(req).await,
+        SpecAction::ResetAccess { tunnel_id } => backend.reset_access(tunnel_id).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec_formats() {
+        let toml_spec = r#"
+            [[tunnels]]
+            tunnelId = "demo"
+            tags = ["web"]
+
+            [[tunnels.ports]]
+            portNumber = 3000
+            protocol = "http"
+
+            [[tunnels.access]]
+            type = "anonymous"
+            ports = [3000]
+        "#;
+        let yaml_spec = "
+tunnels:
+  - tunnelId: demo
+    tags: [web]
+    ports:
+      - portNumber: 3000
+        protocol: http
+    access:
+      - type: anonymous
+        ports: [3000]
+";
+        for spec in [parse_spec(toml_spec, "toml").unwrap(), parse_spec(yaml_spec, "yaml").unwrap()] {
+            let tunnel = &spec.tunnels[0];
+            assert_eq!(tunnel.tunnel_id, "demo");
+            assert_eq!(tunnel.ports[0].protocol, Some(Protocol::Http));
+            assert_eq!(tunnel.access.as_ref().unwrap()[0].ports, Some(vec![3000]));
+        }
+
+        let duplicate = "tunnels:\n  - tunnelId: demo\n    ports: [{portNumber: 80}, {portNumber: 80}]\n";
+        assert!(parse_spec(duplicate, "yml").is_err());
+    }
+}
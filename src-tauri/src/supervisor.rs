@@ -0,0 +1,801 @@
+use crate::backend::DevTunnelBackend;
+use crate::error;
+use crate::events::{self, EventSink};
+use crate::logfiles::LogFiles;
+use crate::process::{self, Signal};
+use crate::redact::Redactor;
+use crate::sessions::SessionStore;
+use crate::types::*;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Child;
+use tokio::sync::{oneshot, watch};
+
+// Configuration constants
+pub const PROCESS_START_DELAY_MS: u64 = 1500; // Wait time after starting a host or connect process
+const PROCESS_STOP_DELAY_MS: u64 = 500;       // Wait time after stopping a process before restart
+pub const DEFAULT_STOP_GRACE_MS: u64 = 5000;  // SIGTERM grace period before escalating to SIGKILL
+const OUTPUT_BUFFER_LINES: usize = 1000;      // Lines of host output kept per tunnel
+const DEFAULT_INITIAL_BACKOFF_MS: u64 = 1000; // First restart delay
+const DEFAULT_MAX_BACKOFF_MS: u64 = 60_000;   // Upper bound for the exponential restart delay
+const STABLE_RUN_SECS: u64 = 60;              // Runs longer than this reset the retry counter
+const ADOPTED_POLL_MS: u64 = 2000;            // Liveness poll interval for adopted processes
+
+// Ring buffer of captured host output, addressed by a monotonically increasing sequence number
+pub(crate) struct OutputBuffer {
+    lines: VecDeque<HostOutputLine>,
+    next_seq: u64,
+    capacity: usize,
+}
+
+impl OutputBuffer {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::with_capacity(capacity),
+            next_seq: 0,
+            capacity,
+        }
+    }
+
+    pub(crate) fn push(&mut self, stream: OutputStream, line: String) -> &HostOutputLine {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(HostOutputLine {
+            seq: self.next_seq,
+            stream,
+            line,
+            timestamp: Local::now().to_rfc3339(),
+        });
+        self.next_seq += 1;
+        self.lines.back().unwrap()
+    }
+
+    fn since(&self, seq: u64) -> Vec<HostOutputLine> {
+        self.lines.iter().filter(|l| l.seq >= seq).cloned().collect()
+    }
+
+    pub(crate) fn tail(&self, count: usize, stream: OutputStream) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .lines
+            .iter()
+            .rev()
+            .filter(|l| l.stream == stream)
+            .take(count)
+            .map(|l| l.line.clone())
+            .collect();
+        lines.reverse();
+        lines
+    }
+}
+
+// Restart decision for a process that exited on its own. `restarts` counts
+// consecutive restarts already performed since the last stable run.
+fn should_restart(policy: &RestartPolicy, success: bool, restarts: u32) -> bool {
+    let wanted = match policy.mode {
+        RestartMode::Never => false,
+        RestartMode::OnFailure => !success,
+        RestartMode::Always => true,
+    };
+
+    wanted && policy.max_retries.is_none_or(|max| restarts < max)
+}
+
+// Exponential backoff: initial * 2^restarts, capped at the policy maximum
+fn backoff_delay(policy: &RestartPolicy, restarts: u32) -> Duration {
+    let initial = policy.initial_backoff_ms.unwrap_or(DEFAULT_INITIAL_BACKOFF_MS);
+    let max = policy.max_backoff_ms.unwrap_or(DEFAULT_MAX_BACKOFF_MS);
+    let delay = initial.saturating_mul(1u64 << restarts.min(32));
+    Duration::from_millis(delay.min(max))
+}
+
+fn describe_exit(status: &std::io::Result<ExitStatus>) -> (String, bool) {
+    match status {
+        Ok(status) => (status.to_string(), status.success()),
+        Err(e) => (format!("unknown ({})", e), false),
+    }
+}
+
+// The process currently backing a hosted tunnel: either a child we spawned, or
+// a process adopted from a previous app session that we only know by PID
+pub(crate) enum Tracked {
+    Child(Child),
+    Adopted(u32),
+}
+
+impl Tracked {
+    // Wait for the process to exit, returning (description, success)
+    pub(crate) async fn wait(&mut self, tunnel_id: &str) -> (String, bool) {
+        match self {
+            Tracked::Child(child) => describe_exit(&child.wait().await),
+            Tracked::Adopted(pid) => {
+                while process::is_host_process(*pid, tunnel_id) {
+                    tokio::time::sleep(Duration::from_millis(ADOPTED_POLL_MS)).await;
+                }
+                ("exited (adopted process, status unknown)".to_string(), false)
+            }
+        }
+    }
+
+    // Ask the process group to terminate, escalating to a kill once the grace
+    // period runs out. Signals that can't be sent are logged through `events`.
+    pub(crate) async fn terminate(
+        &mut self,
+        tunnel_id: &str,
+        grace: Duration,
+        events: &dyn EventSink,
+    ) -> (String, bool, StopMethod) {
+        let pid = match self {
+            Tracked::Child(child) => child.id(),
+            Tracked::Adopted(pid) => Some(*pid),
+        };
+
+        let Some(pid) = pid else {
+            let (description, success) = self.wait(tunnel_id).await;
+            return (description, success, StopMethod::AlreadyExited);
+        };
+
+        if let Err(e) = process::signal(pid, Signal::Terminate) {
+            events.log(LogLevel::Warn, "supervisor", Some(tunnel_id), &e.to_string());
+        }
+
+        if let Ok((description, success)) = tokio::time::timeout(grace, self.wait(tunnel_id)).await {
+            return (description, success, StopMethod::Graceful);
+        }
+
+        if let Err(e) = process::signal(pid, Signal::Kill) {
+            events.log(LogLevel::Error, "supervisor", Some(tunnel_id), &e.to_string());
+        }
+        let (description, success) = self.wait(tunnel_id).await;
+        (description, success, StopMethod::Forced)
+    }
+}
+
+// Sent from stop() to the watcher task
+pub(crate) struct StopRequest {
+    pub(crate) grace: Duration,
+}
+
+// How a supervised process ended for good. `method` is how a stop request
+// ended it, or AlreadyExited when it went away on its own, so every caller
+// waiting on the exit reports the same Graceful / Forced path.
+#[derive(Debug, Clone)]
+pub(crate) struct Exit {
+    pub(crate) description: String,
+    pub(crate) method: StopMethod,
+}
+
+impl Exit {
+    pub(crate) fn new(description: String, method: StopMethod) -> Self {
+        Self { description, method }
+    }
+}
+
+// Hand a stop to a process's watcher task (unless another stop is already in
+// flight) and wait until the process has exited. Shared by hosts and connections.
+pub(crate) async fn stop_process(
+    stop_tx: &Mutex<Option<oneshot::Sender<StopRequest>>>,
+    exit_status: &watch::Receiver<Option<Exit>>,
+    grace: Duration,
+) -> Result<StopMethod> {
+    if let Some(stop_tx) = stop_tx.lock().unwrap().take() {
+        let _ = stop_tx.send(StopRequest { grace });
+    }
+
+    let mut exit_rx = exit_status.clone();
+    let exit = exit_rx.wait_for(|status| status.is_some()).await?;
+    Ok(exit.as_ref().map_or(StopMethod::AlreadyExited, |exit| exit.method.clone()))
+}
+
+// IDs whose process is being spawned. A reservation is held from the "already
+// running?" check until the new process is in the map, so two concurrent
+// starts for one ID can't both spawn and orphan one of the children.
+#[derive(Default)]
+pub(crate) struct Starting(Mutex<HashSet<String>>);
+
+impl Starting {
+    // None when `id` is already starting or `running()` reports it as running
+    pub(crate) fn reserve(&self, id: &str, running: impl FnOnce() -> bool) -> Option<Reservation<'_>> {
+        let mut ids = self.0.lock().unwrap();
+        if ids.contains(id) || running() {
+            return None;
+        }
+        ids.insert(id.to_string());
+        Some(Reservation { starting: self, id: id.to_string() })
+    }
+}
+
+pub(crate) struct Reservation<'a> {
+    starting: &'a Starting,
+    id: String,
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        self.starting.0.lock().unwrap().remove(&self.id);
+    }
+}
+
+// A single `devtunnel host` process owned by the supervisor. The same entry is
+// reused across automatic restarts, so pid and start time change over its lifetime.
+struct HostedProcess {
+    tunnel_id: String,
+    adopted: bool,
+    pid: Mutex<Option<u32>>,
+    started_at: Mutex<DateTime<Local>>,
+    output: Mutex<OutputBuffer>,
+    // Every captured line is redacted and also appended to the tunnel's log file
+    log_files: Arc<LogFiles>,
+    redactor: Arc<Redactor>,
+    // None while supervised, Some(description) once the process has exited for good
+    exit_status: watch::Receiver<Option<Exit>>,
+    stop_tx: Mutex<Option<oneshot::Sender<StopRequest>>>,
+}
+
+impl HostedProcess {
+    fn is_running(&self) -> bool {
+        self.exit_status.borrow().is_none()
+    }
+
+    fn pid(&self) -> Option<u32> {
+        *self.pid.lock().unwrap()
+    }
+
+    fn record(&self, stream: OutputStream, line: String) {
+        let line = self.redactor.redact(&line).into_owned();
+        let mut output = self.output.lock().unwrap();
+        let line = output.push(stream, line);
+        self.log_files.record_host_line(&self.tunnel_id, line);
+    }
+
+    // Take ownership of a freshly spawned child's pipes and record its identity
+    fn attach(self: &Arc<Self>, child: &mut Child) {
+        *self.pid.lock().unwrap() = child.id();
+        *self.started_at.lock().unwrap() = Local::now();
+
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(pump_output(stdout, OutputStream::Stdout, self.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(pump_output(stderr, OutputStream::Stderr, self.clone()));
+        }
+    }
+
+    fn session(&self, backend: &dyn DevTunnelBackend, req: &HostTunnelRequest) -> HostSession {
+        let cmd = backend.host_command(req);
+        let cmd = cmd.as_std();
+
+        HostSession {
+            tunnel_id: self.tunnel_id.clone(),
+            pid: self.pid().unwrap_or_default(),
+            started_at: self.started_at.lock().unwrap().to_rfc3339(),
+            program: cmd.get_program().to_string_lossy().to_string(),
+            args: cmd.get_args().map(|a| a.to_string_lossy().to_string()).collect(),
+            request: req.clone(),
+        }
+    }
+}
+
+// Shared pieces every watcher task needs
+#[derive(Clone)]
+struct WatchContext {
+    backend: Arc<dyn DevTunnelBackend>,
+    events: Arc<dyn EventSink>,
+    sessions: Arc<SessionStore>,
+    log_files: Arc<LogFiles>,
+    redactor: Arc<Redactor>,
+}
+
+impl WatchContext {
+    // Session file failures don't interrupt hosting; they go to the app log
+    fn report(&self, tunnel_id: &str, saved: Result<()>) {
+        if let Err(e) = saved {
+            self.events.log(LogLevel::Warn, "supervisor", Some(tunnel_id), &format!("{:#}", e));
+        }
+    }
+}
+
+// Long-lived owner of hosted tunnel processes, kept in AppState so the
+// child handles and their output survive across command invocations
+pub struct HostSupervisor {
+    hosts: Mutex<HashMap<String, Arc<HostedProcess>>>,
+    starting: Starting,
+    backend: Arc<dyn DevTunnelBackend>,
+    events: Arc<dyn EventSink>,
+    sessions: Arc<SessionStore>,
+    log_files: Arc<LogFiles>,
+    redactor: Arc<Redactor>,
+}
+
+impl HostSupervisor {
+    pub fn new(
+        backend: Arc<dyn DevTunnelBackend>,
+        events: Arc<dyn EventSink>,
+        sessions: Arc<SessionStore>,
+        log_files: Arc<LogFiles>,
+        redactor: Arc<Redactor>,
+    ) -> Self {
+        Self {
+            hosts: Mutex::new(HashMap::new()),
+            starting: Starting::default(),
+            backend,
+            events,
+            sessions,
+            log_files,
+            redactor,
+        }
+    }
+
+    fn get(&self, tunnel_id: &str) -> Option<Arc<HostedProcess>> {
+        self.hosts.lock().unwrap().get(tunnel_id).cloned()
+    }
+
+    fn context(&self) -> WatchContext {
+        WatchContext {
+            backend: self.backend.clone(),
+            events: self.events.clone(),
+            sessions: self.sessions.clone(),
+            log_files: self.log_files.clone(),
+            redactor: self.redactor.clone(),
+        }
+    }
+
+    // Register a process entry and start its watcher task
+    fn track(
+        &self,
+        ctx: WatchContext,
+        req: HostTunnelRequest,
+        tunnel_id: String,
+        tracked: Tracked,
+        started_at: DateTime<Local>,
+    ) -> Arc<HostedProcess> {
+        let (exit_tx, exit_rx) = watch::channel(None);
+        let (stop_tx, stop_rx) = oneshot::channel::<StopRequest>();
+        let adopted = matches!(tracked, Tracked::Adopted(_));
+
+        let process = Arc::new(HostedProcess {
+            tunnel_id: tunnel_id.clone(),
+            adopted,
+            pid: Mutex::new(None),
+            started_at: Mutex::new(started_at),
+            output: Mutex::new(OutputBuffer::new(OUTPUT_BUFFER_LINES)),
+            log_files: ctx.log_files.clone(),
+            redactor: ctx.redactor.clone(),
+            exit_status: exit_rx,
+            stop_tx: Mutex::new(Some(stop_tx)),
+        });
+
+        let tracked = match tracked {
+            Tracked::Child(mut child) => {
+                process.attach(&mut child);
+                ctx.report(&tunnel_id, ctx.sessions.upsert(process.session(ctx.backend.as_ref(), &req)));
+                Tracked::Child(child)
+            }
+            Tracked::Adopted(pid) => {
+                *process.pid.lock().unwrap() = Some(pid);
+                process.record(
+                    OutputStream::Stderr,
+                    format!(
+                        "Adopted running host process (pid {}) from a previous session; its output is not available",
+                        pid
+                    ),
+                );
+                Tracked::Adopted(pid)
+            }
+        };
+
+        tokio::spawn(watch_host(tracked, req, process.clone(), ctx, stop_rx, exit_tx));
+
+        self.hosts.lock().unwrap().insert(tunnel_id, process.clone());
+        process
+    }
+
+    pub fn is_running(&self, tunnel_id: &str) -> bool {
+        self.get(tunnel_id).map(|h| h.is_running()).unwrap_or(false)
+    }
+
+    // Spawn `devtunnel host` with piped output and keep the child under supervision
+    pub async fn host(&self, req: HostTunnelRequest) -> Result<u32> {
+        let tunnel_id = req
+            .tunnel_id
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Tunnel ID is required for hosting"))?;
+
+        let Some(_reservation) = self.starting.reserve(&tunnel_id, || self.is_running(&tunnel_id)) else {
+            return Err(anyhow::anyhow!("Tunnel {} is already being hosted", tunnel_id));
+        };
+
+        let child = spawn_host(self.backend.as_ref(), &req)?;
+        let process = self.track(
+            self.context(),
+            req,
+            tunnel_id,
+            Tracked::Child(child),
+            Local::now(),
+        );
+
+        // Give it a moment to start
+        tokio::time::sleep(tokio::time::Duration::from_millis(PROCESS_START_DELAY_MS)).await;
+
+        if let Some(status) = process.exit_status.borrow().as_ref().map(|exit| exit.description.clone()) {
+            let stderr = process.output.lock().unwrap().tail(5, OutputStream::Stderr);
+            return Err(anyhow::anyhow!(
+                "devtunnel host process exited immediately with status: {}{}",
+                status,
+                if stderr.is_empty() {
+                    String::new()
+                } else {
+                    format!("\n{}", stderr.join("\n"))
+                }
+            ));
+        }
+
+        Ok(process.pid().unwrap_or_default())
+    }
+
+    // Stop a supervised host process: SIGTERM, wait up to `grace`, then SIGKILL
+    pub async fn stop(&self, tunnel_id: &str, grace: Duration) -> Result<StopResult> {
+        let started = Instant::now();
+
+        let process = match self.get(tunnel_id) {
+            Some(p) if p.is_running() => p,
+            _ => {
+                return Ok(StopResult {
+                    tunnel_id: tunnel_id.to_string(),
+                    method: StopMethod::NotRunning,
+                    pid: None,
+                    elapsed_ms: 0,
+                })
+            }
+        };
+        let pid = process.pid();
+
+        let method = stop_process(&process.stop_tx, &process.exit_status, grace)
+            .await
+            .context("Host process waiter disappeared")?;
+
+        Ok(StopResult {
+            tunnel_id: tunnel_id.to_string(),
+            method,
+            pid,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    // Restart tunnel by stopping and restarting
+    pub async fn restart(&self, req: HostTunnelRequest) -> Result<u32> {
+        let tunnel_id = req
+            .tunnel_id
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Tunnel ID is required for restart"))?;
+
+        let stopped = self
+            .stop(&tunnel_id, Duration::from_millis(DEFAULT_STOP_GRACE_MS))
+            .await?;
+
+        if stopped.method != StopMethod::NotRunning {
+            // Wait a moment for the service to notice the host went away
+            tokio::time::sleep(tokio::time::Duration::from_millis(PROCESS_STOP_DELAY_MS)).await;
+        }
+
+        self.host(req).await
+    }
+
+    // Start time in the same format `ps -o lstart` reports
+    pub fn start_time(&self, tunnel_id: &str) -> Option<String> {
+        self.get(tunnel_id)
+            .filter(|p| p.is_running())
+            .map(|p| p.started_at.lock().unwrap().format("%a %b %e %H:%M:%S %Y").to_string())
+    }
+
+    pub fn output(&self, tunnel_id: &str, since: Option<u64>) -> Option<HostOutput> {
+        let process = self.get(tunnel_id)?;
+        let exit_status = process.exit_status.borrow().as_ref().map(|exit| exit.description.clone());
+        let buffer = process.output.lock().unwrap();
+
+        Some(HostOutput {
+            tunnel_id: tunnel_id.to_string(),
+            lines: buffer.since(since.unwrap_or(0)),
+            next_seq: buffer.next_seq,
+            running: exit_status.is_none(),
+            pid: process.pid(),
+            exit_status,
+        })
+    }
+
+    // Called once at startup: adopt session processes that are still alive.
+    // Sessions whose process is gone stay on disk as restorable until re-hosted or forgotten.
+    pub fn reconcile_sessions(&self) -> Vec<String> {
+        let mut adopted = Vec::new();
+        if let Some(error) = self.sessions.take_load_error() {
+            self.events.log(LogLevel::Warn, "supervisor", None, &error);
+        }
+
+        for session in self.sessions.sessions() {
+            if self.is_running(&session.tunnel_id) {
+                continue;
+            }
+            if !process::is_host_process(session.pid, &session.tunnel_id) {
+                continue;
+            }
+
+            let started_at = DateTime::parse_from_rfc3339(&session.started_at)
+                .map(|t| t.with_timezone(&Local))
+                .unwrap_or_else(|_| Local::now());
+
+            self.track(
+                self.context(),
+                session.request.clone(),
+                session.tunnel_id.clone(),
+                Tracked::Adopted(session.pid),
+                started_at,
+            );
+            adopted.push(session.tunnel_id);
+        }
+
+        adopted
+    }
+
+    pub fn sessions_report(&self) -> HostSessionsReport {
+        let sessions = self
+            .sessions
+            .sessions()
+            .into_iter()
+            .map(|session| {
+                let state = match self.get(&session.tunnel_id) {
+                    Some(p) if p.is_running() && p.adopted => HostSessionState::Adopted,
+                    Some(p) if p.is_running() => HostSessionState::Running,
+                    _ => HostSessionState::Stopped,
+                };
+                HostSessionInfo { session, state }
+            })
+            .collect();
+
+        HostSessionsReport {
+            restore_on_startup: self.sessions.restore_on_startup(),
+            sessions,
+        }
+    }
+
+    // Re-host sessions that were running when the app was last closed.
+    // Returns (tunnel_id, result) for each attempted session.
+    pub async fn restore_sessions(
+        &self,
+        tunnel_ids: Option<Vec<String>>,
+    ) -> Vec<(String, Result<u32>)> {
+        let mut results = Vec::new();
+
+        for session in self.sessions.sessions() {
+            if let Some(ids) = &tunnel_ids {
+                if !ids.contains(&session.tunnel_id) {
+                    continue;
+                }
+            }
+            if self.is_running(&session.tunnel_id) {
+                continue;
+            }
+
+            let result = self.host(session.request.clone()).await;
+            results.push((session.tunnel_id, result));
+        }
+
+        results
+    }
+
+    pub fn forget_session(&self, tunnel_id: &str) -> Result<()> {
+        if !self.is_running(tunnel_id) {
+            self.sessions.remove(tunnel_id)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_restore_on_startup(&self, enabled: bool) -> Result<()> {
+        self.sessions.set_restore_on_startup(enabled)
+    }
+
+    pub fn restore_on_startup(&self) -> bool {
+        self.sessions.restore_on_startup()
+    }
+}
+
+fn spawn_host(backend: &dyn DevTunnelBackend, req: &HostTunnelRequest) -> Result<Child> {
+    spawn_piped(backend.host_command(req), "Failed to spawn devtunnel host")
+}
+
+// Spawn a long-running CLI process with piped output for a watcher task to own
+pub(crate) fn spawn_piped(mut cmd: tokio::process::Command, context: &'static str) -> Result<Child> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // Make it its own process group leader so stop signals reach anything it spawns
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    cmd.spawn().map_err(|e| error::spawn_error(context, e))
+}
+
+// Watcher task: owns the process, applies the restart policy when it exits on
+// its own, and kills it when a stop is requested
+async fn watch_host(
+    mut tracked: Tracked,
+    req: HostTunnelRequest,
+    process: Arc<HostedProcess>,
+    ctx: WatchContext,
+    mut stop_rx: oneshot::Receiver<StopRequest>,
+    exit_tx: watch::Sender<Option<Exit>>,
+) {
+    let policy = req.restart_policy.clone().unwrap_or_default();
+    let mut restarts: u32 = 0;
+    let tunnel_id = process.tunnel_id.clone();
+
+    loop {
+        let run_started = Instant::now();
+        let ((description, success), stopped) = tokio::select! {
+            exit = tracked.wait(&tunnel_id) => (exit, None),
+            stop = &mut stop_rx => {
+                let grace = stop
+                    .map(|s| s.grace)
+                    .unwrap_or(Duration::from_millis(DEFAULT_STOP_GRACE_MS));
+                let (description, success, method) = tracked.terminate(&tunnel_id, grace, ctx.events.as_ref()).await;
+                ((description, success), Some(method))
+            }
+        };
+        let requested = stopped.is_some();
+
+        if run_started.elapsed() >= Duration::from_secs(STABLE_RUN_SECS) {
+            restarts = 0;
+        }
+        let will_restart = !requested && should_restart(&policy, success, restarts);
+
+        events::emit(ctx.events.as_ref(), "host-exited", &HostExitedEvent {
+            tunnel_id: tunnel_id.clone(),
+            pid: process.pid(),
+            exit_status: description.clone(),
+            success,
+            requested,
+            will_restart,
+        });
+
+        if !will_restart {
+            ctx.report(&tunnel_id, ctx.sessions.remove(&tunnel_id));
+            let _ = exit_tx.send(Some(Exit::new(description, stopped.unwrap_or(StopMethod::AlreadyExited))));
+            return;
+        }
+
+        // Keep retrying until a process spawns, the policy gives up, or a stop arrives
+        loop {
+            let delay = backoff_delay(&policy, restarts);
+            restarts += 1;
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = &mut stop_rx => {
+                    ctx.report(&tunnel_id, ctx.sessions.remove(&tunnel_id));
+                    let _ = exit_tx.send(Some(Exit::new(description, StopMethod::AlreadyExited)));
+                    return;
+                }
+            }
+
+            match spawn_host(ctx.backend.as_ref(), &req) {
+                Ok(mut child) => {
+                    process.attach(&mut child);
+                    ctx.report(&tunnel_id, ctx.sessions.upsert(process.session(ctx.backend.as_ref(), &req)));
+                    tracked = Tracked::Child(child);
+
+                    events::emit(ctx.events.as_ref(), "host-restarted", &HostRestartedEvent {
+                        tunnel_id: tunnel_id.clone(),
+                        pid: process.pid(),
+                        attempt: restarts,
+                        delay_ms: delay.as_millis() as u64,
+                    });
+                    break;
+                }
+                Err(e) => {
+                    process.record(OutputStream::Stderr, format!("Restart failed: {}", e));
+
+                    if !should_restart(&policy, false, restarts) {
+                        ctx.report(&tunnel_id, ctx.sessions.remove(&tunnel_id));
+                        let _ = exit_tx.send(Some(Exit::new(format!("restart failed: {}", e), StopMethod::AlreadyExited)));
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Drain a child stream line by line into the process output buffer
+async fn pump_output<R>(reader: R, stream: OutputStream, process: Arc<HostedProcess>)
+where
+    R: AsyncRead + Unpin,
+{
+    read_lines(reader, |line| process.record(stream.clone(), line)).await;
+}
+
+// Hand every line of a child's output to `on_line` until EOF or a read error.
+// Invalid UTF-8 (a localized or garbled message) is replaced rather than
+// ending the loop: dropping the pipe would fail the child's next write.
+pub(crate) async fn read_lines<R>(reader: R, mut on_line: impl FnMut(String))
+where
+    R: AsyncRead + Unpin,
+{
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buf);
+                on_line(line.trim_end_matches(['\n', '\r']).to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(mode: RestartMode, max_retries: Option<u32>) -> RestartPolicy {
+        RestartPolicy {
+            mode,
+            max_retries,
+            initial_backoff_ms: Some(500),
+            max_backoff_ms: Some(4000),
+        }
+    }
+
+    #[test]
+    fn test_should_restart_follows_policy_mode() {
+        assert!(!should_restart(&policy(RestartMode::Never, None), false, 0));
+        assert!(should_restart(&policy(RestartMode::OnFailure, None), false, 0));
+        assert!(!should_restart(&policy(RestartMode::OnFailure, None), true, 0));
+        assert!(should_restart(&policy(RestartMode::Always, None), true, 0));
+        assert!(!should_restart(&policy(RestartMode::Always, Some(3)), true, 3));
+    }
+
+    #[test]
+    fn test_backoff_delay_doubles_and_caps() {
+        let p = policy(RestartMode::Always, None);
+        assert_eq!(backoff_delay(&p, 0), Duration::from_millis(500));
+        assert_eq!(backoff_delay(&p, 2), Duration::from_millis(2000));
+        assert_eq!(backoff_delay(&p, 10), Duration::from_millis(4000));
+    }
+
+    #[test]
+    fn test_output_buffer_wraps_and_keeps_sequence() {
+        let mut buffer = OutputBuffer::new(3);
+        for i in 0..5 {
+            buffer.push(OutputStream::Stdout, format!("line {}", i));
+        }
+
+        let lines = buffer.since(0);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].seq, 2);
+        assert_eq!(lines[0].line, "line 2");
+        assert_eq!(buffer.since(4).len(), 1);
+        assert_eq!(buffer.next_seq, 5);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_utf8_does_not_end_output() {
+        let output: &[u8] = b"Hosting port 3000\r\nF\xfchler: \xff\nReady to accept connections\nno newline";
+        let mut lines = Vec::new();
+        read_lines(output, |line| lines.push(line)).await;
+        assert_eq!(lines, vec!["Hosting port 3000", "F\u{fffd}hler: \u{fffd}", "Ready to accept connections", "no newline"]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_binary_is_reported_as_such() {
+        let cmd = tokio::process::Command::new("/nonexistent/devtunnel");
+        let e = spawn_piped(cmd, "Failed to spawn devtunnel host").unwrap_err();
+        assert_eq!(error::error_code(&e), ErrorCode::BinaryMissing);
+    }
+}
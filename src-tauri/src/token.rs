@@ -0,0 +1,138 @@
+// Tunnel access tokens. `devtunnel token` hands back a JWT; its claims say
+// which tunnel, scopes and (optionally) port it is good for and when it runs
+// out, so the GUI can show that without another CLI round trip.
+use crate::error::DevTunnelError;
+use crate::types::*;
+use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::time::Duration;
+
+// Scopes `devtunnel token --scopes` accepts
+pub const TOKEN_SCOPES: &[&str] = &["connect", "host", "manage", "manage:ports", "inspect"];
+
+// Tokens closer than this to their expiry are reported as expiring soon
+pub const EXPIRY_WARNING: Duration = Duration::from_secs(60 * 60);
+
+pub fn validate_scopes(scopes: &[String]) -> Result<()> {
+    if scopes.is_empty() {
+        return Err(DevTunnelError::new(ErrorCode::InvalidArgument, "At least one scope is required").into());
+    }
+    if let Some(scope) = scopes.iter().find(|s| !TOKEN_SCOPES.contains(&s.as_str())) {
+        return Err(DevTunnelError::new(
+            ErrorCode::InvalidArgument,
+            format!("Unknown scope '{}', expected one of: {}", scope, TOKEN_SCOPES.join(", ")),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// Build the typed token from the raw JWT. Claims the token doesn't carry fall
+// back to what was asked for.
+pub fn decode(token: &str, tunnel_id: &str, scopes: &[String], port: Option<u16>) -> Result<TunnelToken> {
+    let claims = claims(token)?;
+    let time = |name: &str| {
+        claims
+            .get(name)
+            .and_then(Value::as_i64)
+            .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+    };
+    let expires = time("exp");
+
+    // "scp" is space separated in tunnel tokens; accept an array too
+    let claimed_scopes: Vec<String> = match claims.get("scp") {
+        Some(Value::String(s)) => s.split_whitespace().map(str::to_string).collect(),
+        Some(Value::Array(items)) => items.iter().filter_map(|s| s.as_str().map(str::to_string)).collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(TunnelToken {
+        token: token.to_string(),
+        tunnel_id: claims
+            .get("tunnelId")
+            .and_then(Value::as_str)
+            .unwrap_or(tunnel_id)
+            .to_string(),
+        cluster_id: claims.get("clusterId").and_then(Value::as_str).map(str::to_string),
+        scopes: if claimed_scopes.is_empty() { scopes.to_vec() } else { claimed_scopes },
+        port: claims
+            .get("port")
+            .and_then(Value::as_u64)
+            .and_then(|p| u16::try_from(p).ok())
+            .or(port),
+        issued_at: time("iat").or(time("nbf")).map(|t| t.to_rfc3339()),
+        expires_at: expires.map(|t| t.to_rfc3339()),
+        expiring_soon: expires.is_some_and(|t| until(t) <= EXPIRY_WARNING),
+    })
+}
+
+// Time left before `expires_at` (RFC 3339), zero once it has passed
+pub fn remaining(expires_at: &str) -> Option<Duration> {
+    DateTime::parse_from_rfc3339(expires_at).ok().map(|t| until(t.with_timezone(&Utc)))
+}
+
+fn until(t: DateTime<Utc>) -> Duration {
+    (t - Utc::now()).to_std().unwrap_or_default()
+}
+
+fn claims(token: &str) -> Result<Value> {
+    let invalid = |reason: &str| DevTunnelError::new(ErrorCode::Unknown, format!("Unexpected token format: {}", reason));
+
+    let mut parts = token.trim().split('.');
+    let payload = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(_), Some(payload), Some(_), None) => payload,
+        _ => return Err(invalid("not a JWT").into()),
+    };
+    let bytes = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|e| invalid(&e.to_string()))?;
+    serde_json::from_slice(&bytes).map_err(|e| invalid(&e.to_string()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(claims: Value) -> String {
+        format!(
+            "{}.{}.c2ln",
+            URL_SAFE_NO_PAD.encode(br#"{"alg":"ES256","typ":"JWT"}"#),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        )
+    }
+
+    #[test]
+    fn test_decode_claims() {
+        let now = Utc::now().timestamp();
+        let token = jwt(serde_json::json!({
+            "clusterId": "usw2",
+            "tunnelId": "demo",
+            "scp": "connect host",
+            "iat": now,
+            "exp": now + 24 * 60 * 60,
+        }));
+
+        let decoded = decode(&token, "demo.usw2", &["connect".to_string()], Some(3000)).unwrap();
+        assert_eq!(decoded.tunnel_id, "demo");
+        assert_eq!(decoded.cluster_id.as_deref(), Some("usw2"));
+        assert_eq!(decoded.scopes, vec!["connect", "host"]);
+        assert_eq!(decoded.port, Some(3000));
+        assert!(!decoded.expiring_soon);
+        let left = remaining(decoded.expires_at.as_deref().unwrap()).unwrap();
+        assert!(left > Duration::from_secs(23 * 60 * 60));
+
+        let short = jwt(serde_json::json!({ "scp": ["manage"], "exp": now + 60 }));
+        let decoded = decode(&short, "demo.usw2", &["connect".to_string()], None).unwrap();
+        assert_eq!(decoded.tunnel_id, "demo.usw2");
+        assert_eq!(decoded.scopes, vec!["manage"]);
+        assert!(decoded.expiring_soon);
+
+        assert!(decode("not-a-token", "demo", &[], None).is_err());
+        assert!(validate_scopes(&["connect".to_string(), "manage:ports".to_string()]).is_ok());
+        assert!(validate_scopes(&["admin".to_string()]).is_err());
+        assert!(validate_scopes(&[]).is_err());
+    }
+}
@@ -24,7 +24,7 @@ pub enum AuthProvider {
     GitHub,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Port {
     #[serde(rename = "portNumber")]
     pub port_number: u16,
@@ -44,6 +44,57 @@ pub struct PingResult {
     #[serde(rename = "responseTimeMs")]
     pub response_time_ms: u64,
     pub error: Option<String>,
+    #[serde(default)]
+    pub timings: PingTimings,
+    #[serde(rename = "remoteAddress")]
+    pub remote_address: Option<String>,
+    // None when the check has no body expectation
+    #[serde(rename = "bodyMatched")]
+    pub body_matched: Option<bool>,
+}
+
+// Duration of each phase of a health check on its own, in ms. Phases that
+// didn't happen (no DNS for an IP, no TLS for http) or weren't reached are None.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PingTimings {
+    #[serde(rename = "dnsMs")]
+    pub dns_ms: Option<u64>,
+    #[serde(rename = "connectMs")]
+    pub connect_ms: Option<u64>,
+    #[serde(rename = "tlsMs")]
+    pub tls_ms: Option<u64>,
+    // From sending the request to the response status line
+    #[serde(rename = "firstByteMs")]
+    pub first_byte_ms: Option<u64>,
+}
+
+// What a ping_port health check sends and what counts as healthy. Without
+// options any HTTP response counts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HealthCheckOptions {
+    // GET when omitted
+    #[serde(default)]
+    pub method: Option<String>,
+    // Replaces the URL's path (and query), e.g. "/healthz"
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub headers: std::collections::BTreeMap<String, String>,
+    // Sent as "X-Tunnel-Authorization: tunnel <token>" for private tunnels
+    #[serde(rename = "tunnelToken", default)]
+    pub tunnel_token: Option<String>,
+    // Codes or inclusive ranges, e.g. ["200-299", "304"]
+    #[serde(rename = "expectedStatus", default)]
+    pub expected_status: Vec<String>,
+    #[serde(rename = "bodyContains", default)]
+    pub body_contains: Option<String>,
+    #[serde(rename = "bodyRegex", default)]
+    pub body_regex: Option<String>,
+    // Certificate and host name checks; true when omitted
+    #[serde(rename = "verifyTls", default)]
+    pub verify_tls: Option<bool>,
+    #[serde(rename = "timeoutMs", default)]
+    pub timeout_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +114,13 @@ pub struct Tunnel {
     pub expires_at: Option<String>,
     #[serde(rename = "allowAnonymous")]
     pub allow_anonymous: Option<bool>,
+    #[serde(rename = "hostConnections")]
+    pub host_connections: Option<u32>,
+    #[serde(rename = "clientConnections")]
+    pub client_connections: Option<u32>,
+    // Tunnel-level access control entries, e.g. "+Anonymous [connect]"
+    #[serde(rename = "accessControl")]
+    pub access_control: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +133,13 @@ pub struct TunnelListItem {
     pub status: TunnelStatus,
     #[serde(rename = "expiresAt")]
     pub expires_at: Option<String>,
+    // Expiration as the CLI printed it, e.g. "30 days"; kept to tell real
+    // changes from the drift of relative values
+    #[serde(skip)]
+    pub expiration: Option<String>,
+    // Why ports/tags are missing: `show` failed for this tunnel while enriching
+    #[serde(rename = "detailsError", default, skip_serializing_if = "Option::is_none")]
+    pub details_error: Option<ItemError>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +162,43 @@ pub struct HostTunnelRequest {
     #[serde(rename = "allowAnonymous")]
     pub allow_anonymous: Option<bool>,
     pub expiration: Option<String>,
+    #[serde(rename = "restartPolicy")]
+    pub restart_policy: Option<RestartPolicy>,
+    // Check of the local services behind `ports` before hosting
+    pub upstream: Option<UpstreamOptions>,
+}
+
+// Ports are always checked for a listener on localhost; these options add
+// an HTTP probe and waiting for the services to come up
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpstreamOptions {
+    // Sent to http://localhost:<port>; a port that fails it is not ready
+    pub probe: Option<HealthCheckOptions>,
+    // Wait up to this long for every port to be ready, failing the host if
+    // they aren't. Without it hosting goes ahead with a warning.
+    #[serde(rename = "waitMs")]
+    pub wait_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartMode {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestartPolicy {
+    pub mode: RestartMode,
+    // Consecutive restarts allowed before giving up (unlimited when omitted)
+    #[serde(rename = "maxRetries")]
+    pub max_retries: Option<u32>,
+    #[serde(rename = "initialBackoffMs")]
+    pub initial_backoff_ms: Option<u64>,
+    #[serde(rename = "maxBackoffMs")]
+    pub max_backoff_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -140,11 +242,72 @@ pub struct Cluster {
     pub latency: Option<u64>,
 }
 
+// Machine-readable failure class; see error.rs for how CLI output maps to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorCode {
+    AuthRequired,
+    NotFound,
+    Conflict,
+    QuotaExceeded,
+    InvalidArgument,
+    BinaryMissing,
+    Timeout,
+    Network,
+    Cancelled,
+    Unknown,
+}
+
+// How long each backend operation may run before its CLI process is killed.
+// Keys are operation names ("login", "list_tunnels", "show_tunnel", ...).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CliTimeouts {
+    #[serde(rename = "defaultMs")]
+    pub default_ms: u64,
+    #[serde(default)]
+    pub operations: std::collections::HashMap<String, u64>,
+}
+
+// Parallelism and throttling retries for devtunnel CLI invocations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CliLimits {
+    // CLI processes allowed to run at once across the whole app
+    #[serde(rename = "maxParallel")]
+    pub max_parallel: usize,
+    // Extra attempts after the service answers "too many requests"
+    #[serde(rename = "maxRetries")]
+    pub max_retries: u32,
+    // First backoff; doubled per attempt, plus up to as much again in jitter
+    #[serde(rename = "retryBaseMs")]
+    pub retry_base_ms: u64,
+}
+
+// Failure of one item in a batch that otherwise succeeded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemError {
+    pub error: String,
+    #[serde(rename = "errorCode")]
+    pub error_code: ErrorCode,
+}
+
+impl ItemError {
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        Self {
+            error: error.to_string(),
+            error_code: crate::error::error_code(error),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    #[serde(rename = "errorCode", default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<ErrorCode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
 }
 
 impl<T> CommandResponse<T> {
@@ -153,14 +316,28 @@ impl<T> CommandResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            error_code: None,
+            hint: None,
         }
     }
 
+    // Classified from the message alone; prefer `failure` when there is an error value
     pub fn error(message: String) -> Self {
+        let code = crate::error::classify(&message);
+        Self::coded(code, message)
+    }
+
+    pub fn failure(error: &anyhow::Error) -> Self {
+        Self::coded(crate::error::error_code(error), error.to_string())
+    }
+
+    pub fn coded(code: ErrorCode, message: String) -> Self {
         Self {
             success: false,
             data: None,
             error: Some(message),
+            error_code: Some(code),
+            hint: crate::error::hint(code).map(str::to_string),
         }
     }
 }
@@ -183,7 +360,7 @@ pub struct UpdateTunnelRequest {
     pub expiration: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AccessControlEntry {
     #[serde(rename = "type")]
     pub entry_type: String,
@@ -192,6 +369,8 @@ pub struct AccessControlEntry {
     pub ports: Option<Vec<u16>>,
     #[serde(rename = "organizationId")]
     pub organization_id: Option<String>,
+    // Deny instead of allow (`--deny`)
+    pub deny: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -201,10 +380,693 @@ pub struct CreateAccessRequest {
     pub entry: AccessControlEntry,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteAccessRequest {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub entry: AccessControlEntry,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessSubjectType {
+    Anonymous,
+    Organization,
+    Tenant,
+    Repository,
+    User,
+    #[serde(other)]
+    Unknown,
+}
+
+// Access token from `devtunnel token`. Everything but `token` comes from its
+// claims, decoded locally; the signature is not checked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TunnelToken {
+    pub token: String,
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    #[serde(rename = "clusterId")]
+    pub cluster_id: Option<String>,
+    pub scopes: Vec<String>,
+    pub port: Option<u16>,
+    #[serde(rename = "issuedAt")]
+    pub issued_at: Option<String>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<String>,
+    // Expires within the warning window (or already has)
+    #[serde(rename = "expiringSoon")]
+    pub expiring_soon: bool,
+}
+
+// Emitted as "token-expiring" shortly before an issued token runs out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenExpiringEvent {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub scopes: Vec<String>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: String,
+}
+
+// One access control entry as reported by `access list`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AccessEntry {
+    #[serde(rename = "type")]
+    pub entry_type: AccessSubjectType,
+    // Organization, tenant, repository or user ID; None for anonymous
+    pub subject: Option<String>,
+    pub provider: Option<String>,
+    #[serde(rename = "isDeny")]
+    pub is_deny: bool,
+    // Inherited from the tunnel rather than set on the port
+    #[serde(rename = "isInherited")]
+    pub is_inherited: bool,
+    pub scopes: Vec<String>,
+    // Empty for tunnel-level entries
+    pub ports: Vec<u16>,
+    pub expiration: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum AccessChange {
+    Create { entry: AccessControlEntry },
+    Delete { entry: AccessControlEntry },
+    Reset,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessPlan {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub current: Vec<AccessEntry>,
+    pub changes: Vec<AccessChange>,
+    // CLI invocations matching `changes`, for display
+    pub commands: Vec<String>,
+}
+
+// Declarative tunnel spec (TOML or YAML); field names follow the request types
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TunnelSpecFile {
+    #[serde(default)]
+    pub tunnels: Vec<TunnelSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TunnelSpec {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    // Only used when the tunnel is created
+    pub expiration: Option<String>,
+    #[serde(default)]
+    pub ports: Vec<PortSpec>,
+    // The access list is left alone when omitted
+    pub access: Option<Vec<AccessControlEntry>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortSpec {
+    #[serde(rename = "portNumber")]
+    pub port_number: u16,
+    pub protocol: Option<Protocol>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum SpecAction {
+    CreateTunnel { req: CreateTunnelRequest },
+    UpdateTunnel { req: UpdateTunnelRequest },
+    CreatePort { req: CreatePortRequest },
+    UpdatePort { req: UpdatePortRequest },
+    DeletePort {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: String,
+        #[serde(rename = "portNumber")]
+        port_number: u16,
+    },
+    CreateAccess { req: CreateAccessRequest },
+    DeleteAccess { req: DeleteAccessRequest },
+    ResetAccess {
+        #[serde(rename = "tunnelId")]
+        tunnel_id: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecStep {
+    pub summary: String,
+    #[serde(flatten)]
+    pub action: SpecAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecPlan {
+    pub steps: Vec<SpecStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SpecStepStatus {
+    Applied,
+    Failed,
+    // Not attempted because an earlier step failed
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecStepResult {
+    pub step: SpecStep,
+    pub status: SpecStepStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecApplyReport {
+    pub results: Vec<SpecStepResult>,
+    pub success: bool,
+}
+
+// JSON-RPC 2.0 messages on the local control socket (control.rs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcRequest {
+    pub jsonrpc: String,
+    // None for a notification, which gets no response. An explicit `"id": null`
+    // is still a request, hence the custom deserializer.
+    #[serde(default, deserialize_with = "rpc_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<serde_json::Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+fn rpc_id<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<serde_json::Value>, D::Error> {
+    serde_json::Value::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    pub id: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
+    // Increases by one per entry; use as `sinceId` to fetch only newer ones
+    pub id: u64,
     pub timestamp: String,
-    pub level: String,
+    pub level: LogLevel,
     pub message: String,
+    // Command or background task that logged it, e.g. "create_port", "poller"
     pub source: Option<String>,
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: Option<String>,
+}
+
+// Every field narrows the result; an empty filter returns everything kept
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogFilter {
+    // Minimum level
+    #[serde(default)]
+    pub level: Option<LogLevel>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(rename = "tunnelId", default)]
+    pub tunnel_id: Option<String>,
+    // Case-insensitive substring of the message
+    #[serde(default)]
+    pub search: Option<String>,
+    #[serde(rename = "sinceId", default)]
+    pub since_id: Option<u64>,
+    // Most recent N matches
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+// Rotation and retention of the log files on disk. A file is rotated once it
+// reaches maxFileBytes or a new day starts; rotated files beyond maxFiles (per
+// log) or older than retentionDays (0 = no age limit) are deleted. Exported
+// archives are pruned by the same limits.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogFileSettings {
+    #[serde(rename = "maxFileBytes")]
+    pub max_file_bytes: u64,
+    #[serde(rename = "maxFiles")]
+    pub max_files: usize,
+    #[serde(rename = "retentionDays")]
+    pub retention_days: u32,
+}
+
+// Extra regexes masked in logs and captured host output, on top of the
+// built-in JWT / bearer / X-Tunnel-Authorization / URL token / email rules
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RedactionSettings {
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+// Time window for export_logs, RFC 3339; either end may be left open
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogRange {
+    #[serde(default)]
+    pub since: Option<String>,
+    #[serde(default)]
+    pub until: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostOutputLine {
+    pub seq: u64,
+    pub stream: OutputStream,
+    pub line: String,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostOutput {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub lines: Vec<HostOutputLine>,
+    #[serde(rename = "nextSeq")]
+    pub next_seq: u64,
+    pub running: bool,
+    pub pid: Option<u32>,
+    #[serde(rename = "exitStatus")]
+    pub exit_status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostExitedEvent {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub pid: Option<u32>,
+    #[serde(rename = "exitStatus")]
+    pub exit_status: String,
+    pub success: bool,
+    // True when the exit was caused by stop_tunnel / restart_tunnel
+    pub requested: bool,
+    #[serde(rename = "willRestart")]
+    pub will_restart: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostRestartedEvent {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub pid: Option<u32>,
+    pub attempt: u32,
+    #[serde(rename = "delayMs")]
+    pub delay_ms: u64,
+}
+
+// A port health-checked on a schedule by the monitor (monitor.rs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortMonitor {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    #[serde(rename = "portNumber")]
+    pub port_number: u16,
+    // Usually the port's forwarding URI
+    pub url: String,
+    #[serde(rename = "intervalMs")]
+    pub interval_ms: u64,
+    #[serde(default)]
+    pub check: HealthCheckOptions,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonitorSettings {
+    #[serde(default)]
+    pub monitors: Vec<PortMonitor>,
+}
+
+// One scheduled check, as kept in the monitor's history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthSample {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub up: bool,
+    #[serde(rename = "latencyMs")]
+    pub latency_ms: u64,
+    #[serde(rename = "statusCode")]
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+// Uptime and latency of a monitored port over a time window. Percentiles
+// only count successful checks; all stats are None until there are samples.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortHealth {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    #[serde(rename = "portNumber")]
+    pub port_number: u16,
+    pub url: String,
+    // Result of the latest check
+    pub up: Option<bool>,
+    #[serde(rename = "lastCheckedAt")]
+    pub last_checked_at: Option<String>,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    pub samples: usize,
+    #[serde(rename = "uptimePercent")]
+    pub uptime_percent: Option<f64>,
+    #[serde(rename = "latencyP50Ms")]
+    pub latency_p50_ms: Option<u64>,
+    #[serde(rename = "latencyP95Ms")]
+    pub latency_p95_ms: Option<u64>,
+    #[serde(rename = "latencyP99Ms")]
+    pub latency_p99_ms: Option<u64>,
+}
+
+// Emitted as "port-health-changed" when a monitored port goes up or down
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortHealthChangedEvent {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    #[serde(rename = "portNumber")]
+    pub port_number: u16,
+    pub up: bool,
+    pub sample: HealthSample,
+}
+
+// Whether a local service is behind one port of a hosted tunnel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpstreamStatus {
+    #[serde(rename = "portNumber")]
+    pub port_number: u16,
+    // Something accepts TCP connections on localhost
+    pub listening: bool,
+    // Listening and, with a probe, passing it
+    pub ready: bool,
+    #[serde(rename = "statusCode")]
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+// Latest upstream check of a hosted tunnel; emitted as "upstream-changed"
+// when a port becomes ready or stops being ready
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpstreamReport {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub ready: bool,
+    pub ports: Vec<UpstreamStatus>,
+    #[serde(rename = "checkedAt")]
+    pub checked_at: String,
+}
+
+// A local service found listening, offered as a port to add to a tunnel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveredPort {
+    #[serde(rename = "portNumber")]
+    pub port_number: u16,
+    // Addresses it is bound on, e.g. "127.0.0.1", "::"
+    pub addresses: Vec<String>,
+    // Only known for processes of the current user
+    pub pid: Option<u32>,
+    pub process: Option<String>,
+    // Guessed by probing; None when it answered neither HTTPS nor HTTP
+    pub protocol: Option<Protocol>,
+}
+
+// Background reconciliation of tunnel state; an interval of 0 turns it off
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PollerSettings {
+    #[serde(rename = "intervalMs")]
+    pub interval_ms: u64,
+}
+
+// Emitted by the poller as "tunnel-added"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TunnelAddedEvent {
+    pub tunnel: TunnelListItem,
+}
+
+// Emitted by the poller as "tunnel-removed"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TunnelRemovedEvent {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    // camelCase name as in Tunnel, e.g. "description", "hostConnections"
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+// Emitted by the poller as "tunnel-updated"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TunnelUpdatedEvent {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub changes: Vec<FieldChange>,
+}
+
+// Emitted by the poller as "port-changed"; `updated` holds the new values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortChangedEvent {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub added: Vec<Port>,
+    pub removed: Vec<u16>,
+    pub updated: Vec<Port>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostSession {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub pid: u32,
+    #[serde(rename = "startedAt")]
+    pub started_at: String,
+    pub program: String,
+    pub args: Vec<String>,
+    pub request: HostTunnelRequest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HostSessionState {
+    // Spawned by this app instance
+    Running,
+    // Still alive from a previous app session, tracked by PID only
+    Adopted,
+    // Was running when the app was last closed but no longer is
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostSessionInfo {
+    #[serde(flatten)]
+    pub session: HostSession,
+    pub state: HostSessionState,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostSessionsReport {
+    #[serde(rename = "restoreOnStartup")]
+    pub restore_on_startup: bool,
+    pub sessions: Vec<HostSessionInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum StopMethod {
+    // Exited within the grace period after SIGTERM
+    Graceful,
+    // Still alive after the grace period and was killed
+    Forced,
+    // Exited on its own before the stop signal was delivered
+    AlreadyExited,
+    // No supervised host process for this tunnel
+    NotRunning,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopResult {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub method: StopMethod,
+    pub pid: Option<u32>,
+    #[serde(rename = "elapsedMs")]
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectTunnelRequest {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    // Needed for tunnels the signed-in user can't access, e.g. another team's
+    #[serde(rename = "accessToken")]
+    pub access_token: Option<String>,
+    // Connect even if some of the tunnel's ports are taken locally; the CLI
+    // then forwards those from a different local port
+    #[serde(rename = "allowPortRemap")]
+    pub allow_port_remap: Option<bool>,
+}
+
+// One "Forwarding from 127.0.0.1:3000 to host port 3000." line of `devtunnel connect`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PortMapping {
+    #[serde(rename = "localAddress")]
+    pub local_address: String,
+    #[serde(rename = "localPort")]
+    pub local_port: u16,
+    #[serde(rename = "remotePort")]
+    pub remote_port: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectState {
+    // Spawned, no forwarding reported yet
+    Starting,
+    Connected,
+    Exited,
+}
+
+// A supervised `devtunnel connect` process; also the "connect-status" event payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectSession {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub pid: Option<u32>,
+    #[serde(rename = "startedAt")]
+    pub started_at: String,
+    pub state: ConnectState,
+    pub mappings: Vec<PortMapping>,
+    #[serde(rename = "exitStatus")]
+    pub exit_status: Option<String>,
+}
+
+// JSON output of the devtunnel CLI (`-j`). Older CLI versions only print text
+// tables; parser.rs turns those into the same models as a fallback.
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CliUser {
+    pub status: Option<String>,
+    pub provider: Option<String>,
+    pub username: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CliTunnelList {
+    #[serde(default)]
+    pub tunnels: Vec<CliTunnelSummary>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CliTunnelSummary {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(rename = "portCount")]
+    pub port_count: Option<u32>,
+    #[serde(rename = "hostConnections")]
+    pub host_connections: Option<u32>,
+    #[serde(rename = "tunnelExpiration", alias = "expiration")]
+    pub tunnel_expiration: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CliTunnelShow {
+    pub tunnel: CliTunnelDetails,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CliTunnelDetails {
+    #[serde(rename = "tunnelId")]
+    pub tunnel_id: String,
+    #[serde(rename = "clusterId")]
+    pub cluster_id: Option<String>,
+    pub domain: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    // Entries as the CLI prints them, e.g. "+Anonymous [connect]"
+    #[serde(rename = "accessControl", default)]
+    pub access_control: Vec<String>,
+    #[serde(rename = "hostConnections")]
+    pub host_connections: Option<u32>,
+    #[serde(rename = "clientConnections")]
+    pub client_connections: Option<u32>,
+    #[serde(rename = "createdAt", alias = "created")]
+    pub created_at: Option<String>,
+    #[serde(rename = "tunnelExpiration", alias = "expiration")]
+    pub tunnel_expiration: Option<String>,
+    #[serde(default)]
+    pub ports: Vec<CliTunnelPort>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CliTunnelPort {
+    #[serde(rename = "portNumber")]
+    pub port_number: u16,
+    pub protocol: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "portUri")]
+    pub port_uri: Option<String>,
+    #[serde(rename = "portForwardingUris", default)]
+    pub port_forwarding_uris: Vec<String>,
+    #[serde(rename = "inspectUri")]
+    pub inspect_uri: Option<String>,
+    #[serde(rename = "clientConnections")]
+    pub client_connections: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CliAccessList {
+    #[serde(rename = "accessControlEntries", default)]
+    pub access_control_entries: Vec<CliAccessEntry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CliAccessEntry {
+    // "Anonymous", "Organizations", "Users", "Repositories", ...
+    #[serde(rename = "type")]
+    pub entry_type: String,
+    pub provider: Option<String>,
+    #[serde(rename = "isDeny", default)]
+    pub is_deny: bool,
+    #[serde(rename = "isInherited", default)]
+    pub is_inherited: bool,
+    #[serde(default)]
+    pub subjects: Vec<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    pub expiration: Option<String>,
 }
@@ -0,0 +1,213 @@
+// Readiness of the local services behind a hosted tunnel's ports. A port is
+// ready when something accepts TCP connections on localhost and, with a
+// probe, answers it as expected. host_tunnel checks (or waits for) the ports
+// before hosting; afterwards a background task keeps checking every hosted
+// tunnel and reports changes as "upstream-changed".
+use crate::health;
+use crate::types::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+
+pub const UPSTREAM_CHECK_INTERVAL_MS: u64 = 10_000; // Background check of hosted tunnels
+const CONNECT_TIMEOUT_MS: u64 = 1000;
+const WAIT_RETRY_MS: u64 = 500; // Delay between checks while waiting for readiness
+
+// Check one port on localhost, trying every address it resolves to
+pub async fn check_port(port_number: u16, probe: Option<&HealthCheckOptions>) -> UpstreamStatus {
+    let mut status = UpstreamStatus {
+        port_number,
+        listening: false,
+        ready: false,
+        status_code: None,
+        error: None,
+    };
+
+    let connect = TcpStream::connect(("localhost", port_number));
+    match tokio::time::timeout(Duration::from_millis(CONNECT_TIMEOUT_MS), connect).await {
+        Ok(Ok(_)) => status.listening = true,
+        Ok(Err(e)) => status.error = Some(format!("Nothing listening on localhost:{}: {}", port_number, e)),
+        Err(_) => status.error = Some(format!("Connecting to localhost:{} timed out", port_number)),
+    }
+    if !status.listening {
+        return status;
+    }
+
+    match probe {
+        None => status.ready = true,
+        Some(probe) => match health::check(&format!("http://localhost:{}/", port_number), probe).await {
+            Ok(result) => {
+                status.ready = result.success;
+                status.status_code = result.status_code;
+                status.error = result.error;
+            }
+            Err(e) => status.error = Some(e.to_string()),
+        },
+    }
+    status
+}
+
+// Check all ports side by side; results are in port order
+pub async fn check_ports(ports: &[u16], probe: Option<&HealthCheckOptions>) -> Vec<UpstreamStatus> {
+    let mut checks = JoinSet::new();
+    for &port in ports {
+        let probe = probe.cloned();
+        checks.spawn(async move { check_port(port, probe.as_ref()).await });
+    }
+
+    let mut statuses = checks.join_all().await;
+    statuses.sort_by_key(|s| s.port_number);
+    statuses
+}
+
+// Check until every port is ready or `timeout` has passed; returns the last check
+pub async fn wait_ready(ports: &[u16], probe: Option<&HealthCheckOptions>, timeout: Duration) -> Vec<UpstreamStatus> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let statuses = check_ports(ports, probe).await;
+        if statuses.iter().all(|s| s.ready) || Instant::now() >= deadline {
+            return statuses;
+        }
+        tokio::time::sleep(Duration::from_millis(WAIT_RETRY_MS).min(deadline - Instant::now())).await;
+    }
+}
+
+// "3000 (Nothing listening on localhost:3000: ...), 8080 (...)"
+pub fn describe_not_ready(statuses: &[UpstreamStatus]) -> String {
+    statuses
+        .iter()
+        .filter(|s| !s.ready)
+        .map(|s| format!("{} ({})", s.port_number, s.error.as_deref().unwrap_or("not ready")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Latest report per hosted tunnel, kept in AppState
+#[derive(Default)]
+pub struct UpstreamWatch {
+    reports: Mutex<HashMap<String, UpstreamReport>>,
+}
+
+impl UpstreamWatch {
+    // Store a new check of the tunnel. Returns the report when a port became
+    // ready or stopped being ready since the previous check (or on the first).
+    pub fn update(&self, tunnel_id: &str, ports: Vec<UpstreamStatus>) -> Option<UpstreamReport> {
+        let report = UpstreamReport {
+            tunnel_id: tunnel_id.to_string(),
+            ready: ports.iter().all(|s| s.ready),
+            ports,
+            checked_at: chrono::Local::now().to_rfc3339(),
+        };
+
+        let readiness = |r: &UpstreamReport| r.ports.iter().map(|s| (s.port_number, s.ready)).collect::<Vec<_>>();
+        let previous = self.reports.lock().unwrap().insert(tunnel_id.to_string(), report.clone());
+        match previous {
+            Some(previous) if readiness(&previous) == readiness(&report) => None,
+            _ => Some(report),
+        }
+    }
+
+    // Drop reports of tunnels that are no longer hosted
+    pub fn retain(&self, hosted: &[String]) {
+        self.reports.lock().unwrap().retain(|tunnel_id, _| hosted.contains(tunnel_id));
+    }
+
+    pub fn reports(&self) -> Vec<UpstreamReport> {
+        let mut reports: Vec<UpstreamReport> = self.reports.lock().unwrap().values().cloned().collect();
+        reports.sort_by(|a, b| a.tunnel_id.cmp(&b.tunnel_id));
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // A local service answering every request with `status`
+    async fn serve(status: u16) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                let response = format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        port
+    }
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+    }
+
+    #[tokio::test]
+    async fn test_listening_and_probe() {
+        let ok = serve(200).await;
+        let failing = serve(502).await;
+        let closed = free_port();
+        let probe = HealthCheckOptions {
+            expected_status: vec!["2xx".to_string()],
+            ..Default::default()
+        };
+
+        let statuses = check_ports(&[failing, ok, closed], None).await;
+        assert_eq!(statuses.len(), 3);
+        assert!(statuses.iter().find(|s| s.port_number == ok).unwrap().ready);
+        assert!(statuses.iter().find(|s| s.port_number == failing).unwrap().ready);
+        let closed_status = statuses.iter().find(|s| s.port_number == closed).unwrap();
+        assert!(!closed_status.listening && !closed_status.ready);
+        assert!(describe_not_ready(&statuses).starts_with(&format!("{} (Nothing listening", closed)));
+
+        let probed = check_port(failing, Some(&probe)).await;
+        assert!(probed.listening && !probed.ready);
+        assert_eq!(probed.status_code, Some(502));
+        assert_eq!(check_port(ok, Some(&probe)).await.status_code, Some(200));
+    }
+
+    #[tokio::test]
+    async fn test_wait_ready() {
+        let port = free_port();
+        let started = Instant::now();
+        let statuses = wait_ready(&[port], None, Duration::from_millis(300)).await;
+        assert!(!statuses[0].ready);
+        assert!(started.elapsed() >= Duration::from_millis(300));
+
+        // The service comes up while waiting
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            let listener = TcpListener::bind(("127.0.0.1", port)).await.unwrap();
+            loop {
+                let _ = listener.accept().await;
+            }
+        });
+        let statuses = wait_ready(&[port], None, Duration::from_secs(5)).await;
+        assert!(statuses[0].ready);
+    }
+
+    #[test]
+    fn test_watch_reports_changes() {
+        let watch = UpstreamWatch::default();
+        let status = |ready| UpstreamStatus {
+            port_number: 3000,
+            listening: ready,
+            ready,
+            status_code: None,
+            error: None,
+        };
+
+        assert!(watch.update("demo.usw2", vec![status(false)]).is_some());
+        assert!(watch.update("demo.usw2", vec![status(false)]).is_none());
+        let report = watch.update("demo.usw2", vec![status(true)]).unwrap();
+        assert!(report.ready);
+        assert_eq!(watch.reports().len(), 1);
+
+        watch.retain(&[]);
+        assert!(watch.reports().is_empty());
+    }
+}
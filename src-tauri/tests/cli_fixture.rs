@@ -0,0 +1,233 @@
+// DevTunnelClient driven against tests/fixtures/fake-devtunnel, the shell
+// stand-in for the real CLI. Kept in its own test binary because it sets
+// FAKE_DEVTUNNEL_* variables for the spawned processes.
+#![cfg(unix)]
+
+use devtunnel_gui_lib::backend::DevTunnelBackend;
+use devtunnel_gui_lib::commands::*;
+use devtunnel_gui_lib::devtunnel::DevTunnelClient;
+use devtunnel_gui_lib::types::*;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::test::mock_app;
+use tauri::Manager;
+
+// Both modes must produce the same results: JSON on current CLIs, text
+// scraping once the client sees `-j` rejected by an older one
+#[tokio::test]
+async fn test_client_against_fake_cli() {
+    let json_entries = run_scenario("json", false).await;
+    let legacy_entries = run_scenario("legacy", true).await;
+    assert_eq!(json_entries, legacy_entries);
+}
+
+async fn run_scenario(name: &str, legacy: bool) -> Vec<AccessEntry> {
+    let root = std::env::temp_dir().join(format!("devtunnel-gui-fake-cli-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::env::set_var("FAKE_DEVTUNNEL_HOME", root.join("cli"));
+    if legacy {
+        std::env::set_var("FAKE_DEVTUNNEL_LEGACY", "1");
+    } else {
+        std::env::remove_var("FAKE_DEVTUNNEL_LEGACY");
+    }
+
+    let binary = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/fake-devtunnel");
+    let client = Arc::new(DevTunnelClient::new(binary.to_string()));
+    let app = mock_app();
+    app.manage(AppState::with_backend(
+        client.clone(),
+        Arc::new(app.handle().clone()),
+        &root.join("data"),
+    ));
+    let app = app.handle().clone();
+
+    assert_eq!(get_user_info(app.clone()).await.error_code, Some(ErrorCode::AuthRequired));
+    assert!(login_devtunnel(app.clone(), "github".to_string(), false, None).await.success);
+    assert_eq!(get_user_info(app.clone()).await.data.unwrap().user_name.as_deref(), Some("fake-user"));
+
+    let created = create_tunnel(
+        app.clone(),
+        CreateTunnelRequest {
+            tunnel_id: Some("demo".to_string()),
+            description: Some("fixture tunnel".to_string()),
+            tags: Some(vec!["web".to_string(), "prod".to_string()]),
+            allow_anonymous: None,
+            expiration: None,
+        },
+    ).await;
+    assert!(created.success, "{:?}", created.error);
+
+    for port_number in [3000, 8080] {
+        let port = create_port(
+            app.clone(),
+            CreatePortRequest {
+                tunnel_id: "demo".to_string(),
+                port_number,
+                protocol: Some(Protocol::Http),
+                description: None,
+            },
+        ).await;
+        assert!(port.success, "{:?}", port.error);
+    }
+
+    let tunnels = list_tunnels(app.clone(), None, None, None).await.data.unwrap();
+    assert_eq!(tunnels.len(), 1);
+    assert_eq!(tunnels[0].tunnel_id, "demo.usw2");
+    assert_eq!(tunnels[0].description.as_deref(), Some("fixture tunnel"));
+    assert_eq!(tunnels[0].ports, vec![3000, 8080]);
+    assert!(tunnels[0].expires_at.is_some());
+    if !legacy {
+        // The text table has no reliable column for labels
+        assert_eq!(tunnels[0].tags, Some(vec!["web".to_string(), "prod".to_string()]));
+
+        // Output that doesn't decode falls back to text for that call only
+        std::env::set_var("FAKE_DEVTUNNEL_GARBLED", "list");
+        assert_eq!(client.list_tunnels_light(None).await.unwrap()[0].tags, None);
+        std::env::set_var("FAKE_DEVTUNNEL_GARBLED", "port");
+        assert!(client.list_ports("demo".to_string()).await.is_err());
+        std::env::remove_var("FAKE_DEVTUNNEL_GARBLED");
+        assert!(client.list_tunnels_light(None).await.unwrap()[0].tags.is_some());
+    }
+
+    // CLI stderr is classified into error codes
+    let missing = show_tunnel(app.clone(), Some("nope".to_string()), None, None).await;
+    assert_eq!(missing.error_code, Some(ErrorCode::NotFound));
+    assert!(missing.hint.is_some());
+
+    let details = show_tunnel(app.clone(), Some("demo".to_string()), None, None).await.data.unwrap();
+    assert_eq!(details.description.as_deref(), Some("fixture tunnel"));
+    assert_eq!(details.tags, Some(vec!["web".to_string(), "prod".to_string()]));
+    assert_eq!(details.cluster_id.as_deref(), Some("usw2"));
+    assert_eq!(details.host_connections, Some(0));
+    assert_eq!(
+        details.ports[1].port_forwarding_uris,
+        Some(vec!["https://demo-8080.usw2.devtunnels.ms/".to_string()])
+    );
+    assert_eq!(
+        details.ports[1].inspect_uri.as_deref(),
+        Some("https://demo-8080-inspect.usw2.devtunnels.ms/")
+    );
+
+    let access = CreateAccessRequest {
+        tunnel_id: "demo".to_string(),
+        entry: AccessControlEntry {
+            entry_type: "organization".to_string(),
+            scopes: None,
+            expiration: None,
+            ports: None,
+            organization_id: Some("1234".to_string()),
+            deny: None,
+        },
+    };
+    assert!(create_access(app.clone(), access.clone()).await.success);
+    let entries = client.list_access_entries("demo".to_string()).await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].entry_type, AccessSubjectType::Organization);
+    assert_eq!(entries[0].subject.as_deref(), Some("1234"));
+
+    // Swap the org entry for tenant access
+    let tenant = AccessControlEntry {
+        entry_type: "tenant".to_string(),
+        organization_id: None,
+        ..access.entry.clone()
+    };
+    let plan = diff_access(app.clone(), "demo".to_string(), vec![tenant.clone()]).await.data.unwrap();
+    assert_eq!(
+        plan.commands,
+        vec!["devtunnel access reset demo", "devtunnel access create demo --tenant"]
+    );
+    let deleted = delete_access(
+        app.clone(),
+        DeleteAccessRequest { tunnel_id: "demo".to_string(), entry: access.entry },
+    ).await;
+    assert!(deleted.success, "{:?}", deleted.error);
+    assert!(create_access(app.clone(), CreateAccessRequest { tunnel_id: "demo".to_string(), entry: tenant.clone() }).await.success);
+    let tenant_entries = list_access_entries(app.clone(), "demo".to_string(), None).await.data.unwrap();
+    assert_eq!(tenant_entries[0].entry_type, AccessSubjectType::Tenant);
+    let plan = diff_access(app.clone(), "demo".to_string(), vec![tenant]).await.data.unwrap();
+    assert!(plan.changes.is_empty());
+
+    let ports = list_ports(app.clone(), "demo".to_string(), None).await.data.unwrap();
+    assert_eq!(ports.len(), 2);
+    assert_eq!(show_port(app.clone(), "demo".to_string(), 3000).await.data.unwrap().protocol, Protocol::Http);
+    assert_eq!(list_clusters(app.clone(), false, None).await.data.unwrap().len(), 2);
+
+    let req = HostTunnelRequest {
+        tunnel_id: Some("demo.usw2".to_string()),
+        ports: vec![3000, 8080],
+        protocol: None,
+        allow_anonymous: None,
+        expiration: None,
+        restart_policy: None,
+        upstream: None,
+    };
+    let hosted = host_tunnel(app.clone(), req).await;
+    assert!(hosted.success, "{:?}", hosted.error);
+
+    let output = get_host_output(app.clone(), "demo.usw2".to_string(), None).data.unwrap();
+    assert!(output
+        .lines
+        .iter()
+        .any(|l| l.line == "Ready to accept connections for tunnel: demo.usw2"));
+
+    let stopped = stop_tunnel(app.clone(), "demo.usw2".to_string(), Some(2000)).await.data.unwrap();
+    assert_eq!(stopped.method, StopMethod::Graceful);
+
+    assert!(delete_tunnel(app.clone(), "demo".to_string()).await.success);
+    let missing = show_tunnel(app, Some("demo".to_string()), None, None).await;
+    assert!(missing.error.unwrap().contains("Tunnel not found: demo"));
+
+    let _ = std::fs::remove_dir_all(&root);
+    entries
+}
+
+// A CLI that never answers: the call times out, the child is killed, and an
+// operation started with an opId can be cancelled from another task
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_stalled_cli_is_killed() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+
+    let root = std::env::temp_dir().join(format!("devtunnel-gui-stalled-cli-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let pid_file = root.join("pid");
+    let binary = root.join("devtunnel");
+    std::fs::write(&binary, format!("#!/bin/sh\necho $$ > {}\nexec sleep 30\n", pid_file.display())).unwrap();
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let app = mock_app();
+    let client = Arc::new(DevTunnelClient::new(binary.to_string_lossy().to_string()));
+    app.manage(AppState::with_backend(client, Arc::new(app.handle().clone()), &root.join("data")));
+    let app = app.handle().clone();
+
+    let mut timeouts = get_cli_timeouts(app.clone()).data.unwrap();
+    timeouts.operations.insert("list_tunnels_light".to_string(), 300);
+    assert!(set_cli_timeouts(app.clone(), timeouts).success);
+
+    let timed_out = list_tunnels_light(app.clone(), None, None, None).await;
+    assert_eq!(timed_out.error_code, Some(ErrorCode::Timeout));
+
+    // kill_on_drop took the sleeping child down with the abandoned call
+    let pid = std::fs::read_to_string(&pid_file).unwrap().trim().to_string();
+    let proc_dir = Path::new("/proc").join(&pid);
+    let mut gone = false;
+    for _ in 0..50 {
+        let state = std::fs::read_to_string(proc_dir.join("stat")).unwrap_or_default();
+        if state.is_empty() || state.contains(") Z ") {
+            gone = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(gone, "devtunnel child {} still running", pid);
+
+    let pending = tokio::spawn(show_tunnel(app.clone(), Some("demo".to_string()), Some("op-1".to_string()), None));
+    while !cancel_operation(app.clone(), "op-1".to_string()).data.unwrap() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(pending.await.unwrap().error_code, Some(ErrorCode::Cancelled));
+
+    let _ = std::fs::remove_dir_all(&root);
+}
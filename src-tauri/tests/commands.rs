@@ -0,0 +1,579 @@
+// Command-level tests against the in-memory FakeBackend
+use devtunnel_gui_lib::commands::*;
+use devtunnel_gui_lib::fake::FakeBackend;
+use devtunnel_gui_lib::types::*;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::test::{mock_app, MockRuntime};
+use tauri::{AppHandle, Manager};
+
+fn data_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("devtunnel-gui-test-{}-{}", name, std::process::id()))
+}
+
+fn setup(name: &str, backend: Arc<FakeBackend>) -> AppHandle<MockRuntime> {
+    let app = mock_app();
+    let dir = data_dir(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    app.manage(AppState::with_backend(backend, Arc::new(app.handle().clone()), &dir));
+    app.handle().clone()
+}
+
+fn create_req(tunnel_id: &str) -> CreateTunnelRequest {
+    CreateTunnelRequest {
+        tunnel_id: Some(tunnel_id.to_string()),
+        description: Some("test tunnel".to_string()),
+        tags: Some(vec!["web".to_string()]),
+        allow_anonymous: None,
+        expiration: None,
+    }
+}
+
+fn port_req(tunnel_id: &str, port_number: u16) -> CreatePortRequest {
+    CreatePortRequest {
+        tunnel_id: tunnel_id.to_string(),
+        port_number,
+        protocol: Some(Protocol::Http),
+        description: None,
+    }
+}
+
+fn host_req(tunnel_id: &str) -> HostTunnelRequest {
+    HostTunnelRequest {
+        tunnel_id: Some(tunnel_id.to_string()),
+        ports: vec![3000],
+        protocol: None,
+        allow_anonymous: None,
+        expiration: None,
+        restart_policy: None,
+        upstream: None,
+    }
+}
+
+#[tokio::test]
+async fn test_login_and_user_info() {
+    let app = setup("login", Arc::new(FakeBackend::logged_out()));
+
+    let before = get_user_info(app.clone()).await;
+    assert!(!before.success);
+    assert_eq!(before.error.as_deref(), Some("Not authenticated"));
+
+    assert!(login_devtunnel(app.clone(), "github".to_string(), false, None).await.success);
+    let info = get_user_info(app.clone()).await.data.unwrap();
+    assert_eq!(info.user_name.as_deref(), Some("fake-user"));
+    assert!(matches!(info.provider, AuthProvider::GitHub));
+
+    assert!(logout_devtunnel(app.clone()).await.success);
+    let listed = list_tunnels_light(app, None, None, None).await;
+    assert!(!listed.success);
+    assert_eq!(listed.error_code, Some(ErrorCode::AuthRequired));
+}
+
+#[tokio::test]
+async fn test_tunnel_and_port_lifecycle() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("lifecycle", backend.clone());
+
+    assert!(create_tunnel(app.clone(), create_req("demo")).await.success);
+    assert!(create_tunnel(app.clone(), create_req("other")).await.success);
+    assert_eq!(backend.tunnel_ids(), vec!["demo.usw2", "other.usw2"]);
+
+    assert!(create_port(app.clone(), port_req("demo", 3000)).await.success);
+    assert!(create_port(app.clone(), port_req("demo.usw2", 8080)).await.success);
+    assert!(!create_port(app.clone(), port_req("demo", 3000)).await.success);
+
+    let light = list_tunnels_light(app.clone(), None, None, None).await.data.unwrap();
+    assert!(light.iter().all(|t| t.ports.is_empty()));
+
+    let enriched = enrich_tunnel_details(app.clone(), vec!["demo.usw2".to_string()], None, None).await.data.unwrap();
+    let demo = enriched.iter().find(|t| t.tunnel_id == "demo.usw2").unwrap();
+    assert_eq!(demo.ports, vec![3000, 8080]);
+
+    let updated = update_port(
+        app.clone(),
+        UpdatePortRequest {
+            tunnel_id: "demo".to_string(),
+            port_number: 8080,
+            description: Some("api".to_string()),
+            protocol: Some(Protocol::Https),
+        },
+    ).await;
+    assert!(updated.success);
+    let port = show_port(app.clone(), "demo".to_string(), 8080).await.data.unwrap();
+    assert_eq!(port.protocol, Protocol::Https);
+    assert_eq!(port.description.as_deref(), Some("api"));
+
+    assert!(delete_port(app.clone(), "demo".to_string(), 3000).await.success);
+    assert_eq!(list_ports(app.clone(), "demo".to_string(), None).await.data.unwrap().len(), 1);
+
+    assert!(delete_tunnel(app.clone(), "other".to_string()).await.success);
+    assert!(!show_tunnel(app.clone(), Some("other".to_string()), None, None).await.success);
+    assert_eq!(list_tunnels(app, None, None, None).await.data.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_tag_filters() {
+    let app = setup("tags", Arc::new(FakeBackend::new()));
+
+    let mut tagged = create_req("a");
+    tagged.tags = Some(vec!["web".to_string(), "prod".to_string()]);
+    create_tunnel(app.clone(), tagged).await;
+    create_tunnel(app.clone(), create_req("b")).await;
+
+    let filter = |all| ListTunnelsRequest {
+        tags: Some(vec!["web".to_string(), "prod".to_string()]),
+        all_tags: Some(all),
+    };
+    assert_eq!(list_tunnels_light(app.clone(), Some(filter(false)), None, None).await.data.unwrap().len(), 2);
+    assert_eq!(list_tunnels_light(app, Some(filter(true)), None, None).await.data.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_reads_are_cached_until_invalidated() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("cache", backend.clone());
+    let count = |op: &str| backend.calls().iter().filter(|c| *c == op).count();
+
+    create_tunnel(app.clone(), create_req("demo")).await;
+    create_tunnel(app.clone(), create_req("other")).await;
+    create_port(app.clone(), port_req("demo", 3000)).await;
+
+    list_tunnels_light(app.clone(), None, None, None).await;
+    list_tunnels_light(app.clone(), None, None, None).await;
+    assert_eq!(count("list_tunnels_light"), 1);
+
+    // enrich reuses the cached light list
+    let ids = vec!["demo.usw2".to_string(), "other.usw2".to_string()];
+    enrich_tunnel_details(app.clone(), ids.clone(), None, None).await;
+    enrich_tunnel_details(app.clone(), ids, None, None).await;
+    assert_eq!(count("list_tunnels_light"), 1);
+    assert_eq!(count("show_tunnel"), 2);
+
+    list_ports(app.clone(), "demo".to_string(), None).await;
+    list_ports(app.clone(), "other".to_string(), None).await;
+    assert_eq!(count("list_ports"), 2);
+
+    // Only the tunnel that changed is re-read
+    create_port(app.clone(), port_req("demo", 8080)).await;
+    assert_eq!(list_ports(app.clone(), "demo".to_string(), None).await.data.unwrap().len(), 2);
+    list_ports(app.clone(), "other".to_string(), None).await;
+    assert_eq!(count("list_ports"), 3);
+    let tunnels = list_tunnels(app.clone(), None, None, None).await.data.unwrap();
+    assert_eq!(tunnels.iter().find(|t| t.tunnel_id == "demo.usw2").unwrap().ports, vec![3000, 8080]);
+    assert_eq!(count("show_tunnel"), 3);
+
+    list_ports(app.clone(), "other".to_string(), Some(true)).await;
+    assert_eq!(count("list_ports"), 4);
+}
+
+#[tokio::test]
+async fn test_access_entries() {
+    let app = setup("access", Arc::new(FakeBackend::new()));
+    create_tunnel(app.clone(), create_req("demo")).await;
+
+    let entry = AccessControlEntry {
+        entry_type: "anonymous".to_string(),
+        scopes: None,
+        expiration: None,
+        ports: Some(vec![3000]),
+        organization_id: None,
+        deny: None,
+    };
+    assert!(create_access(app.clone(), CreateAccessRequest { tunnel_id: "demo".to_string(), entry: entry.clone() }).await.success);
+
+    let listed = list_access(app.clone(), "demo".to_string()).await.data.unwrap();
+    assert!(listed.contains("+Anonymous [connect] (ports: 3000)"));
+
+    let entries = list_access_entries(app.clone(), "demo".to_string(), None).await.data.unwrap();
+    assert_eq!(entries[0].entry_type, AccessSubjectType::Anonymous);
+    assert_eq!(entries[0].ports, vec![3000]);
+
+    // Opening the tunnel to everyone keeps nothing of the port-level entry
+    let mut desired = vec![AccessControlEntry { ports: None, ..entry.clone() }];
+    let plan = diff_access(app.clone(), "demo".to_string(), desired.clone()).await.data.unwrap();
+    assert_eq!(plan.changes.len(), 2);
+    assert_eq!(plan.changes[0], AccessChange::Reset);
+
+    // Adding a deny entry next to the existing one only needs a create
+    desired = vec![
+        entry.clone(),
+        AccessControlEntry {
+            entry_type: "organization".to_string(),
+            organization_id: Some("contoso".to_string()),
+            deny: Some(true),
+            ..entry.clone()
+        },
+    ];
+    let plan = diff_access(app.clone(), "demo".to_string(), desired).await.data.unwrap();
+    assert_eq!(plan.commands, vec!["devtunnel access create demo --org contoso -p 3000 --deny"]);
+
+    assert!(delete_access(app.clone(), DeleteAccessRequest { tunnel_id: "demo".to_string(), entry: entry.clone() }).await.success);
+    assert!(!delete_access(app.clone(), DeleteAccessRequest { tunnel_id: "demo".to_string(), entry: entry.clone() }).await.success);
+    assert!(create_access(app.clone(), CreateAccessRequest { tunnel_id: "demo".to_string(), entry: entry.clone() }).await.success);
+
+    assert!(reset_access(app.clone(), "demo".to_string()).await.success);
+    let listed = list_access(app, "demo".to_string()).await.data.unwrap();
+    assert!(listed.starts_with("Found 0 access control entries."));
+}
+
+#[tokio::test]
+async fn test_enrich_reports_failed_tunnels() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("enrich-errors", backend.clone());
+
+    create_tunnel(app.clone(), create_req("demo")).await;
+    create_tunnel(app.clone(), create_req("other")).await;
+    create_port(app.clone(), port_req("demo", 3000)).await;
+    create_port(app.clone(), port_req("other", 3000)).await;
+
+    // Exactly one of the parallel `show` calls fails
+    backend.fail_next("show_tunnel", "Tunnel not found");
+    let ids = vec!["demo.usw2".to_string(), "other.usw2".to_string()];
+    let tunnels = enrich_tunnel_details(app.clone(), ids, None, None).await.data.unwrap();
+    assert_eq!(tunnels.len(), 2);
+
+    let (failed, ok): (Vec<_>, Vec<_>) = tunnels.iter().partition(|t| t.details_error.is_some());
+    assert_eq!((failed.len(), ok.len()), (1, 1));
+    assert_eq!(failed[0].details_error.as_ref().unwrap().error_code, ErrorCode::NotFound);
+    assert!(failed[0].ports.is_empty());
+    assert_eq!(ok[0].ports, vec![3000]);
+}
+
+#[tokio::test]
+async fn test_issue_token() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("token", backend.clone());
+    assert!(create_tunnel(app.clone(), create_req("demo")).await.success);
+    assert!(create_port(app.clone(), port_req("demo", 3000)).await.success);
+
+    let scopes = vec!["connect".to_string(), "host".to_string()];
+    let issued = issue_token(app.clone(), "demo".to_string(), scopes.clone(), Some(3000)).await;
+    let token = issued.data.unwrap();
+    assert_eq!(token.tunnel_id, "demo");
+    assert_eq!(token.cluster_id.as_deref(), Some("usw2"));
+    assert_eq!(token.scopes, scopes);
+    assert_eq!(token.port, Some(3000));
+    assert!(token.expires_at.is_some());
+    assert!(!token.expiring_soon);
+
+    // The token itself never reaches the log
+    let logs = get_logs(app.clone(), None).data.unwrap();
+    assert!(logs.iter().all(|e| !e.message.contains(&token.token)));
+
+    let bad = issue_token(app.clone(), "demo".to_string(), vec!["admin".to_string()], None).await;
+    assert_eq!(bad.error_code, Some(ErrorCode::InvalidArgument));
+    let missing = issue_token(app, "nope".to_string(), vec!["connect".to_string()], None).await;
+    assert_eq!(missing.error_code, Some(ErrorCode::NotFound));
+}
+
+#[tokio::test]
+async fn test_scripted_failure_is_reported() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("failure", backend.clone());
+
+    backend.fail_next("create_tunnel", "Tunnel quota exceeded");
+    let failed = create_tunnel(app.clone(), create_req("demo")).await;
+    assert!(!failed.success);
+    assert_eq!(failed.error.as_deref(), Some("Tunnel quota exceeded"));
+    assert_eq!(failed.error_code, Some(ErrorCode::QuotaExceeded));
+    assert!(failed.hint.is_some());
+
+    // Only the next call fails
+    assert!(create_tunnel(app, create_req("demo")).await.success);
+    assert_eq!(backend.calls(), vec!["create_tunnel", "create_tunnel"]);
+}
+
+#[tokio::test]
+async fn test_logs_are_structured_and_filterable() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("logs", backend.clone());
+
+    assert!(create_tunnel(app.clone(), create_req("demo")).await.success);
+    assert!(create_tunnel(app.clone(), create_req("other")).await.success);
+    backend.fail_next("create_port", "Port 3000 already exists");
+    assert!(!create_port(app.clone(), port_req("demo", 3000)).await.success);
+    assert!(create_port(app.clone(), port_req("other", 8080)).await.success);
+
+    let errors = get_logs(
+        app.clone(),
+        Some(LogFilter {
+            level: Some(LogLevel::Error),
+            ..LogFilter::default()
+        }),
+    )
+    .data
+    .unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].source.as_deref(), Some("create_port"));
+    assert_eq!(errors[0].tunnel_id.as_deref(), Some("demo"));
+    assert!(errors[0].message.contains("already exists"));
+
+    // "demo.usw2" matches entries logged under the short ID
+    let demo = get_logs(
+        app.clone(),
+        Some(LogFilter {
+            tunnel_id: Some("demo.usw2".to_string()),
+            ..LogFilter::default()
+        }),
+    )
+    .data
+    .unwrap();
+    assert!(!demo.is_empty());
+    assert!(demo.iter().all(|e| e.tunnel_id.as_deref() == Some("demo")));
+
+    // Incremental reads pick up after the last ID seen
+    let all = get_logs(app.clone(), None).data.unwrap();
+    let newer = get_logs(
+        app,
+        Some(LogFilter {
+            since_id: Some(all[all.len() - 2].id),
+            ..LogFilter::default()
+        }),
+    )
+    .data
+    .unwrap();
+    assert_eq!(newer.len(), 1);
+    assert_eq!(newer[0].id, all[all.len() - 1].id);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_host_and_stop_fake_process() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("host", backend.clone());
+    create_tunnel(app.clone(), create_req("demo")).await;
+    create_port(app.clone(), port_req("demo", 3000)).await;
+
+    // Two concurrent requests must not both spawn a host process
+    let (first, second) = tokio::join!(
+        host_tunnel(app.clone(), host_req("demo.usw2")),
+        host_tunnel(app.clone(), host_req("demo.usw2")),
+    );
+    assert!(first.success != second.success, "{:?} / {:?}", first.error, second.error);
+
+    let output = get_host_output(app.clone(), "demo.usw2".to_string(), None).data.unwrap();
+    assert!(output.running);
+    let lines: Vec<&str> = output.lines.iter().map(|l| l.line.as_str()).collect();
+    assert!(lines.contains(&"Connect via browser: https://demo-3000.usw2.devtunnels.ms/"));
+
+    assert!(get_tunnel_start_time(app.clone(), "demo.usw2".to_string()).success);
+    // A prefix of a hosted tunnel's ID is a different tunnel
+    let other = get_tunnel_start_time(app.clone(), "demo".to_string());
+    assert_eq!(other.error_code, Some(ErrorCode::NotFound));
+
+    let sessions = get_host_sessions(app.clone()).data.unwrap();
+    assert_eq!(sessions.sessions.len(), 1);
+    assert_eq!(sessions.sessions[0].state, HostSessionState::Running);
+
+    let stopped = stop_tunnel(app.clone(), "demo.usw2".to_string(), Some(2000)).await.data.unwrap();
+    assert_eq!(stopped.method, StopMethod::Graceful);
+    assert!(get_host_sessions(app).data.unwrap().sessions.is_empty());
+
+    let _ = std::fs::remove_dir_all(data_dir("host"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_host_that_exits_immediately_fails() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("host-exit", backend.clone());
+    create_tunnel(app.clone(), create_req("demo")).await;
+    backend.set_host_script("echo \"Tunnel $1 is already hosted elsewhere\" >&2\nexit 1");
+
+    let hosted = host_tunnel(app.clone(), host_req("demo.usw2")).await;
+    assert!(!hosted.success);
+    let error = hosted.error.unwrap();
+    assert!(error.contains("exited immediately"), "{}", error);
+    assert!(error.contains("Tunnel demo.usw2 is already hosted elsewhere"), "{}", error);
+
+    let _ = std::fs::remove_dir_all(data_dir("host-exit"));
+}
+
+// A second stop during the grace period reports the same path as the first
+#[cfg(unix)]
+#[tokio::test]
+async fn test_concurrent_stops_agree() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("concurrent-stop", backend.clone());
+    create_tunnel(app.clone(), create_req("demo")).await;
+    backend.set_host_script("trap '' TERM\nwhile true; do sleep 1; done");
+    assert!(host_tunnel(app.clone(), host_req("demo.usw2")).await.success);
+
+    let (first, second) = tokio::join!(
+        stop_tunnel(app.clone(), "demo.usw2".to_string(), Some(300)),
+        stop_tunnel(app.clone(), "demo.usw2".to_string(), Some(300)),
+    );
+    assert_eq!(first.data.unwrap().method, StopMethod::Forced);
+    assert_eq!(second.data.unwrap().method, StopMethod::Forced);
+
+    let _ = std::fs::remove_dir_all(data_dir("concurrent-stop"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_host_checks_upstream() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("upstream", backend.clone());
+    create_tunnel(app.clone(), create_req("demo")).await;
+
+    let free = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let mut req = host_req("demo.usw2");
+    req.ports = vec![free];
+    req.upstream = Some(UpstreamOptions { probe: None, wait_ms: Some(300) });
+
+    let timed_out = host_tunnel(app.clone(), req.clone()).await;
+    assert_eq!(timed_out.error_code, Some(ErrorCode::Timeout));
+    assert!(timed_out.error.unwrap().contains(&free.to_string()));
+    let reports = get_upstream_status(app.clone()).data.unwrap();
+    assert!(!reports[0].ready);
+    assert!(!reports[0].ports[0].listening);
+
+    let _listener = std::net::TcpListener::bind(("127.0.0.1", free)).unwrap();
+    assert!(host_tunnel(app.clone(), req).await.success);
+    assert!(get_upstream_status(app.clone()).data.unwrap()[0].ready);
+    stop_tunnel(app, "demo.usw2".to_string(), Some(2000)).await;
+
+    let _ = std::fs::remove_dir_all(data_dir("upstream"));
+}
+
+#[tokio::test]
+async fn test_connect_reports_mappings_and_conflicts() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("connect", backend.clone());
+    create_tunnel(app.clone(), create_req("demo")).await;
+
+    // A port nothing listens on, then one that is taken
+    let free = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    create_port(app.clone(), port_req("demo", free)).await;
+    let req = |allow_port_remap| ConnectTunnelRequest {
+        tunnel_id: "demo.usw2".to_string(),
+        access_token: None,
+        allow_port_remap,
+    };
+
+    let connected = connect_tunnel(app.clone(), req(None)).await;
+    assert!(connected.success, "{:?}", connected.error);
+    let session = connected.data.unwrap();
+    assert_eq!(session.state, ConnectState::Connected);
+    assert_eq!(session.mappings, vec![PortMapping {
+        local_address: "127.0.0.1".to_string(),
+        local_port: free,
+        remote_port: free,
+    }]);
+    assert_eq!(list_connections(app.clone()).data.unwrap().len(), 1);
+    assert!(!connect_tunnel(app.clone(), req(None)).await.success);
+
+    let stopped = stop_connection(app.clone(), "demo.usw2".to_string(), Some(2000)).await.data.unwrap();
+    assert_eq!(stopped.method, StopMethod::Graceful);
+    assert!(list_connections(app.clone()).data.unwrap().is_empty());
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let busy = listener.local_addr().unwrap().port();
+    create_port(app.clone(), port_req("demo", busy)).await;
+
+    let conflict = connect_tunnel(app.clone(), req(None)).await;
+    assert_eq!(conflict.error_code, Some(ErrorCode::Conflict));
+    assert!(conflict.error.unwrap().contains(&busy.to_string()));
+
+    assert!(connect_tunnel(app.clone(), req(Some(true))).await.success);
+    stop_connection(app, "demo.usw2".to_string(), Some(2000)).await;
+
+    let _ = std::fs::remove_dir_all(data_dir("connect"));
+}
+
+#[tokio::test]
+async fn test_monitor_settings_and_health() {
+    let app = setup("monitor", Arc::new(FakeBackend::new()));
+    let monitor = |interval_ms| PortMonitor {
+        tunnel_id: "demo.usw2".to_string(),
+        port_number: 3000,
+        url: "https://demo-3000.usw2.devtunnels.ms/".to_string(),
+        interval_ms,
+        check: HealthCheckOptions::default(),
+    };
+
+    let too_fast = set_monitor_settings(app.clone(), MonitorSettings { monitors: vec![monitor(100)] });
+    assert_eq!(too_fast.error_code, Some(ErrorCode::InvalidArgument));
+    assert!(set_monitor_settings(app.clone(), MonitorSettings { monitors: vec![monitor(30_000)] }).success);
+    assert_eq!(get_monitor_settings(app.clone()).data.unwrap().monitors.len(), 1);
+
+    // Monitored but not checked yet
+    let health = get_port_health(app.clone(), None).data.unwrap();
+    assert_eq!(health.len(), 1);
+    assert_eq!(health[0].samples, 0);
+    assert_eq!(health[0].up, None);
+
+    let history = get_port_health_history(app.clone(), "demo.usw2".to_string(), 3000, Some("2026-01-01T00:00:00Z".to_string()));
+    assert!(history.data.unwrap().is_empty());
+    let invalid = get_port_health_history(app, "demo.usw2".to_string(), 3000, Some("yesterday".to_string()));
+    assert_eq!(invalid.error_code, Some(ErrorCode::InvalidArgument));
+
+    let _ = std::fs::remove_dir_all(data_dir("monitor"));
+}
+
+#[tokio::test]
+async fn test_plan_and_apply_spec() {
+    let backend = Arc::new(FakeBackend::new());
+    let app = setup("spec", backend.clone());
+    create_tunnel(app.clone(), create_req("api")).await;
+    create_port(app.clone(), port_req("api", 8080)).await;
+    create_port(app.clone(), port_req("api", 9090)).await;
+
+    std::fs::create_dir_all(data_dir("spec")).unwrap();
+    let path = data_dir("spec").join("tunnels.yaml");
+    std::fs::write(
+        &path,
+        "
+tunnels:
+  - tunnelId: api
+    description: test tunnel
+    tags: [web, prod]
+    ports:
+      - portNumber: 8080
+        protocol: http
+  - tunnelId: web
+    ports:
+      - portNumber: 3000
+    access:
+      - type: anonymous
+",
+    )
+    .unwrap();
+    let path = path.to_string_lossy().to_string();
+
+    let plan = plan_spec(app.clone(), path.clone(), None).await.data.unwrap();
+    let summaries: Vec<&str> = plan.steps.iter().map(|s| s.summary.as_str()).collect();
+    assert_eq!(
+        summaries,
+        vec![
+            "Update tunnel api (tags)",
+            "Delete port 9090 from api",
+            "Create tunnel web",
+            "Create port 3000 (auto) on web",
+            "devtunnel access create web --anonymous",
+        ]
+    );
+
+    backend.fail_next("create_port", "Port quota exceeded");
+    let report = apply_spec(app.clone(), path.clone(), None).await.data.unwrap();
+    assert!(!report.success);
+    let statuses: Vec<SpecStepStatus> = report.results.iter().map(|r| r.status.clone()).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            SpecStepStatus::Applied,
+            SpecStepStatus::Applied,
+            SpecStepStatus::Applied,
+            SpecStepStatus::Failed,
+            SpecStepStatus::Skipped,
+        ]
+    );
+
+    // A second run only picks up what is still missing
+    let report = apply_spec(app.clone(), path.clone(), None).await.data.unwrap();
+    assert!(report.success);
+    assert_eq!(report.results.len(), 2);
+    assert!(plan_spec(app, path, None).await.data.unwrap().steps.is_empty());
+
+    let _ = std::fs::remove_dir_all(data_dir("spec"));
+}
@@ -0,0 +1,172 @@
+// Control socket round trips against the in-memory FakeBackend
+#![cfg(unix)]
+
+use devtunnel_gui_lib::backend::DevTunnelBackend;
+use devtunnel_gui_lib::commands::*;
+use devtunnel_gui_lib::control::{self, ControlClient};
+use devtunnel_gui_lib::fake::FakeBackend;
+use devtunnel_gui_lib::types::*;
+use serde_json::json;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::test::mock_app;
+use tauri::Manager;
+
+fn temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("devtunnel-gui-control-{}-{}", name, std::process::id()))
+}
+
+// Serve a fake-backed app on a fresh socket and return its path
+async fn start(name: &str, backend: Arc<FakeBackend>) -> PathBuf {
+    let dir = temp_dir(name);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let app = mock_app();
+    app.manage(AppState::with_backend(backend, Arc::new(app.handle().clone()), &dir));
+    let handle = app.handle().clone();
+
+    let socket = dir.join("control.sock");
+    let path = socket.clone();
+    tokio::spawn(async move { control::serve(handle, &path).await });
+
+    for _ in 0..50 {
+        if socket.exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    socket
+}
+
+// The client is blocking, so keep it off the runtime the server runs on
+async fn call<T: serde::de::DeserializeOwned + Send + 'static>(
+    socket: &Path,
+    method: &'static str,
+    params: serde_json::Value,
+) -> anyhow::Result<T> {
+    let socket = socket.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut client = ControlClient::connect(&socket)?.expect("app is listening");
+        client.call(method, params)
+    })
+    .await?
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_host_and_stop_over_socket() {
+    let backend = Arc::new(FakeBackend::new());
+    let socket = start("host", backend.clone()).await;
+    let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    let tunnels: CommandResponse<Vec<TunnelListItem>> = call(&socket, "list_tunnels", json!({})).await.unwrap();
+    assert!(tunnels.data.unwrap().is_empty());
+
+    backend
+        .create_tunnel(CreateTunnelRequest {
+            tunnel_id: Some("demo".to_string()),
+            description: None,
+            tags: None,
+            allow_anonymous: None,
+            expiration: None,
+        })
+        .await
+        .unwrap();
+    let req = json!({ "req": { "tunnelId": "demo.usw2", "ports": [3000] } });
+    let hosted: CommandResponse<String> = call(&socket, "host_tunnel", req).await.unwrap();
+    assert!(hosted.success, "{:?}", hosted.error);
+
+    let sessions: CommandResponse<HostSessionsReport> =
+        call(&socket, "get_host_sessions", serde_json::Value::Null).await.unwrap();
+    assert_eq!(sessions.data.unwrap().sessions[0].state, HostSessionState::Running);
+
+    let stopped: CommandResponse<StopResult> =
+        call(&socket, "stop_tunnel", json!({ "tunnelId": "demo.usw2", "gracePeriodMs": 2000 })).await.unwrap();
+    assert_eq!(stopped.data.unwrap().method, StopMethod::Graceful);
+
+    let _ = std::fs::remove_dir_all(temp_dir("host"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tunnel_and_port_methods() {
+    let backend = Arc::new(FakeBackend::new());
+    let socket = start("ports", backend.clone()).await;
+
+    let req = json!({ "req": { "tunnelId": "editor", "description": "from a script" } });
+    let created: CommandResponse<String> = call(&socket, "create_tunnel", req).await.unwrap();
+    assert!(created.success, "{:?}", created.error);
+
+    let req = json!({ "req": { "tunnelId": "editor.usw2", "portNumber": 5173, "protocol": "http" } });
+    let port: CommandResponse<String> = call(&socket, "create_port", req).await.unwrap();
+    assert!(port.success, "{:?}", port.error);
+
+    let tunnel: CommandResponse<Tunnel> = call(&socket, "show_tunnel", json!({ "tunnelId": "editor.usw2" })).await.unwrap();
+    let tunnel = tunnel.data.unwrap();
+    assert_eq!(tunnel.description.as_deref(), Some("from a script"));
+    assert_eq!(tunnel.ports.iter().map(|p| p.port_number).collect::<Vec<_>>(), vec![5173]);
+
+    let deleted: CommandResponse<String> =
+        call(&socket, "delete_port", json!({ "tunnelId": "editor.usw2", "port": 5173 })).await.unwrap();
+    assert!(deleted.success);
+    assert!(backend.list_ports("editor.usw2".to_string()).await.unwrap().is_empty());
+
+    // Failures come back as the command's own CommandResponse, not RPC errors
+    let missing: CommandResponse<Tunnel> = call(&socket, "show_tunnel", json!({ "tunnelId": "nope" })).await.unwrap();
+    assert!(!missing.success);
+    assert!(missing.error.is_some());
+
+    let _ = std::fs::remove_dir_all(temp_dir("ports"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rejects_shared_socket_dir() {
+    let dir = temp_dir("shared");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+
+    let app = mock_app();
+    app.manage(AppState::with_backend(Arc::new(FakeBackend::new()), Arc::new(app.handle().clone()), &dir));
+    let err = control::serve(app.handle().clone(), &dir.join("control.sock")).await.unwrap_err();
+    assert!(err.to_string().contains("writable by other users"), "{}", err);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rpc_errors() {
+    let socket = start("errors", Arc::new(FakeBackend::new())).await;
+
+    let unknown = call::<serde_json::Value>(&socket, "format_disk", json!({})).await.unwrap_err();
+    assert!(unknown.to_string().contains("-32601"), "{}", unknown);
+
+    let invalid = call::<serde_json::Value>(&socket, "stop_tunnel", json!({ "tunnelId": 7 })).await.unwrap_err();
+    assert!(invalid.to_string().contains("-32602"), "{}", invalid);
+
+    // Notifications (no id) are not answered, not even with an error
+    let raw = socket.clone();
+    let reply = tokio::task::spawn_blocking(move || {
+        use std::io::{BufRead, Write};
+        let mut stream = std::os::unix::net::UnixStream::connect(&raw).unwrap();
+        stream
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"format_disk\"}\n{\"jsonrpc\":\"2.0\",\"id\":null,\"method\":\"format_disk\"}\n")
+            .unwrap();
+        let mut reply = String::new();
+        std::io::BufReader::new(stream).read_line(&mut reply).unwrap();
+        reply
+    })
+    .await
+    .unwrap();
+    let reply: RpcResponse = serde_json::from_str(&reply).unwrap();
+    assert_eq!(reply.id, serde_json::Value::Null);
+    assert_eq!(reply.error.unwrap().code, control::METHOD_NOT_FOUND);
+
+    // A second app instance must not steal the socket
+    let app = mock_app();
+    let backend = Arc::new(FakeBackend::new());
+    app.manage(AppState::with_backend(backend, Arc::new(app.handle().clone()), &temp_dir("errors")));
+    assert!(control::serve(app.handle().clone(), &socket).await.is_err());
+
+    let _ = std::fs::remove_dir_all(temp_dir("errors"));
+}
@@ -1,13 +1,16 @@
 import { useEffect, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import { useTunnelStore } from './stores/tunnelStore';
-import { authApi } from './lib/api';
+import { authApi, pollerApi } from './lib/api';
 import Dashboard from './components/Dashboard/Dashboard';
+import ConnectionsView from './components/Connections/ConnectionsView';
 import LogsViewer from './components/Logs/LogsViewer';
 import Settings from './components/Settings/Settings';
 import LoginScreen from './components/Auth/LoginScreen';
 import Sidebar from './components/Layout/Sidebar';
 import Header from './components/Layout/Header';
-import { ToastContainer } from './components/Toast';
+import { ToastContainer, toast } from './components/Toast';
+import type { PortHealthChangedEvent, TokenExpiringEvent } from './types/devtunnel';
 import './index.css';
 
 function App() {
@@ -18,6 +21,42 @@ function App() {
     checkAuthStatus();
   }, []);
 
+  // Background refresh only runs while signed in and the window is visible
+  useEffect(() => {
+    const update = () => {
+      pollerApi.setPaused(!isAuthenticated || document.hidden).catch(() => {});
+    };
+    update();
+    document.addEventListener('visibilitychange', update);
+    return () => document.removeEventListener('visibilitychange', update);
+  }, [isAuthenticated]);
+
+  // Issued tokens warn shortly before they run out, whichever view is open
+  useEffect(() => {
+    const unlisten = listen<TokenExpiringEvent>('token-expiring', (event) => {
+      const { tunnelId, scopes, expiresAt } = event.payload;
+      toast.warning(`Token for ${tunnelId} (${scopes.join(', ')}) expires at ${new Date(expiresAt).toLocaleString()}`);
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  // Monitored ports going down or coming back
+  useEffect(() => {
+    const unlisten = listen<PortHealthChangedEvent>('port-health-changed', (event) => {
+      const { tunnelId, portNumber, up, sample } = event.payload;
+      if (up) {
+        toast.success(`Port ${portNumber} of ${tunnelId} is up again`);
+      } else {
+        toast.error(`Port ${portNumber} of ${tunnelId} is down: ${sample.error || 'not reachable'}`);
+      }
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
   const checkAuthStatus = async () => {
     try {
       const userInfo = await authApi.getUserInfo();
@@ -48,6 +87,8 @@ function App() {
     switch (activeTab) {
       case 'dashboard':
         return <Dashboard />;
+      case 'connections':
+        return <ConnectionsView />;
       case 'logs':
         return <LogsViewer />;
       case 'settings':
@@ -0,0 +1,139 @@
+import { useState, useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { connectApi, ApiError } from '../../lib/api';
+import { toast } from '../Toast';
+import type { ConnectSession } from '../../types/devtunnel';
+
+// Tunnels hosted elsewhere, forwarded to localhost with `devtunnel connect`
+export default function ConnectionsView() {
+  const [connections, setConnections] = useState<ConnectSession[]>([]);
+  const [tunnelId, setTunnelId] = useState('');
+  const [accessToken, setAccessToken] = useState('');
+  const [allowPortRemap, setAllowPortRemap] = useState(false);
+  const [isConnecting, setIsConnecting] = useState(false);
+
+  useEffect(() => {
+    connectApi.list().then(setConnections).catch((error) => {
+      console.error('Failed to load connections:', error);
+    });
+
+    const unlisten = listen<ConnectSession>('connect-status', (event) => {
+      const session = event.payload;
+      if (session.state === 'exited') {
+        toast.info(`Disconnected from ${session.tunnelId} (${session.exitStatus})`);
+        setConnections(prev => prev.filter(c => c.tunnelId !== session.tunnelId || c.pid !== session.pid));
+      } else {
+        setConnections(prev => [...prev.filter(c => c.tunnelId !== session.tunnelId), session]
+          .sort((a, b) => a.tunnelId.localeCompare(b.tunnelId)));
+      }
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  const handleConnect = async () => {
+    if (!tunnelId.trim()) return;
+    setIsConnecting(true);
+    try {
+      const session = await connectApi.connect({
+        tunnelId: tunnelId.trim(),
+        accessToken: accessToken.trim() || undefined,
+        allowPortRemap,
+      });
+      toast.success(`Connected to ${session.tunnelId}`);
+      setTunnelId('');
+      setAccessToken('');
+    } catch (error) {
+      if (error instanceof ApiError && error.code === 'conflict') {
+        toast.error(`${error.message}. Stop what is using them or allow remapping.`);
+      } else {
+        toast.error(`Failed to connect: ${error}`);
+      }
+    } finally {
+      setIsConnecting(false);
+    }
+  };
+
+  const handleDisconnect = async (id: string) => {
+    try {
+      await connectApi.stop(id);
+    } catch (error) {
+      toast.error(`Failed to disconnect: ${error}`);
+    }
+  };
+
+  return (
+    <div className="p-8 space-y-6">
+      <div>
+        <h2 className="text-3xl font-semibold text-white tracking-tight">Connections</h2>
+        <p className="text-zinc-400 text-sm mt-1">Forward ports of remote tunnels to this machine</p>
+      </div>
+
+      <div className="card space-y-3">
+        <div className="flex gap-2">
+          <input
+            type="text"
+            value={tunnelId}
+            onChange={(e) => setTunnelId(e.target.value)}
+            placeholder="Tunnel ID, e.g. demo.usw2"
+            className="input-field flex-1"
+          />
+          <input
+            type="password"
+            value={accessToken}
+            onChange={(e) => setAccessToken(e.target.value)}
+            placeholder="Access token (optional)"
+            className="input-field flex-1"
+          />
+          <button onClick={handleConnect} disabled={isConnecting || !tunnelId.trim()} className="btn-primary">
+            {isConnecting ? 'Connecting...' : 'Connect'}
+          </button>
+        </div>
+        <label className="flex items-center gap-2 text-sm text-zinc-400">
+          <input
+            type="checkbox"
+            checked={allowPortRemap}
+            onChange={(e) => setAllowPortRemap(e.target.checked)}
+            className="w-4 h-4"
+          />
+          Use other local ports when the tunnel's ports are already taken
+        </label>
+      </div>
+
+      {connections.length === 0 ? (
+        <div className="card text-center text-gray-500 py-8">No active connections.</div>
+      ) : (
+        connections.map(c => (
+          <div key={c.tunnelId} className="card">
+            <div className="flex items-center justify-between mb-3">
+              <div>
+                <code className="text-white">{c.tunnelId}</code>
+                <span className={`ml-3 text-xs ${c.state === 'connected' ? 'text-green-400' : 'text-yellow-400'}`}>
+                  {c.state}
+                </span>
+                {c.pid && <span className="ml-3 text-xs text-zinc-500">pid {c.pid}</span>}
+              </div>
+              <button onClick={() => handleDisconnect(c.tunnelId)} className="btn-secondary">
+                Disconnect
+              </button>
+            </div>
+            {c.mappings.length === 0 ? (
+              <p className="text-sm text-zinc-500">Waiting for port forwarding...</p>
+            ) : (
+              <div className="space-y-1">
+                {c.mappings.map(m => (
+                  <div key={`${m.localAddress}:${m.localPort}`} className="text-sm font-mono text-gray-300">
+                    {m.localAddress}:{m.localPort}
+                    <span className="text-zinc-500"> → host port </span>
+                    {m.remotePort}
+                  </div>
+                ))}
+              </div>
+            )}
+          </div>
+        ))
+      )}
+    </div>
+  );
+}
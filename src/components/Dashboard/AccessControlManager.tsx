@@ -1,11 +1,13 @@
 import { useState, useEffect } from 'react';
 import { accessApi } from '../../lib/api';
-import { ACCESS_PRESETS, type AccessPresetType } from '../../types/devtunnel';
+import { ACCESS_PRESETS, type AccessPresetType, type TokenScope, type Tunnel, type TunnelToken } from '../../types/devtunnel';
 import { toast } from '../Toast';
 
+const TOKEN_SCOPES: TokenScope[] = ['connect', 'host', 'manage', 'manage:ports', 'inspect'];
+
 interface AccessControlManagerProps {
   tunnelId: string;
-  tunnelDetails?: string; // Pre-loaded tunnel details to avoid redundant API calls
+  tunnelDetails?: Tunnel; // Pre-loaded tunnel details to avoid redundant API calls
   onAccessChanged?: () => void;
 }
 
@@ -16,19 +18,24 @@ export default function AccessControlManager({ tunnelId, tunnelDetails: propTunn
   const [customExpiration, setCustomExpiration] = useState('24h');
   const [customOrgId, setCustomOrgId] = useState('');
   const [selectedPorts, setSelectedPorts] = useState<string>('');
+  const [tokenScopes, setTokenScopes] = useState<TokenScope[]>(['connect']);
+  const [tokenPort, setTokenPort] = useState('');
+  const [issuedToken, setIssuedToken] = useState<TunnelToken | null>(null);
+  const [isIssuing, setIsIssuing] = useState(false);
 
   useEffect(() => {
     loadAccessInfo();
   }, [tunnelId, propTunnelDetails]);
 
+  useEffect(() => {
+    setIssuedToken(null);
+  }, [tunnelId]);
+
   const loadAccessInfo = async () => {
-    // If we have pre-loaded tunnel details, parse access control from it
+    // If we have pre-loaded tunnel details, use its access control summary
     if (propTunnelDetails) {
-      const accessMatch = propTunnelDetails.match(/Access control\s*:\s*(.+?)(?:\n|$)/i);
-      if (accessMatch) {
-        setAccessInfo(`Access control list for tunnel ${tunnelId}:\n  ${accessMatch[1]}`);
-        return;
-      }
+      setAccessInfo(`Access control list for tunnel ${tunnelId}:\n  {${propTunnelDetails.accessControl.join(', ')}}`);
+      return;
     }
 
     // Otherwise fetch from API
@@ -88,6 +95,28 @@ export default function AccessControlManager({ tunnelId, tunnelDetails: propTunn
     }
   };
 
+  const toggleTokenScope = (scope: TokenScope) => {
+    setTokenScopes(prev => prev.includes(scope) ? prev.filter(s => s !== scope) : [...prev, scope]);
+  };
+
+  const handleIssueToken = async () => {
+    setIsIssuing(true);
+    try {
+      const port = tokenPort.trim() ? parseInt(tokenPort.trim()) : undefined;
+      const token = await accessApi.issueToken(tunnelId, tokenScopes, port);
+      setIssuedToken(token);
+      if (token.expiringSoon) {
+        toast.warning('Token expires within the hour');
+      } else {
+        toast.success('Token issued');
+      }
+    } catch (error) {
+      toast.error(`Failed to issue token: ${error}`);
+    } finally {
+      setIsIssuing(false);
+    }
+  };
+
   return (
     <div className="space-y-6">
       {/* Presets */}
@@ -166,6 +195,65 @@ export default function AccessControlManager({ tunnelId, tunnelDetails: propTunn
         </div>
       </div>
 
+      {/* Access Tokens */}
+      <div className="card bg-gray-750">
+        <h3 className="text-lg font-semibold text-white mb-4">Access Token</h3>
+        <p className="text-sm text-gray-400 mb-3">
+          Share a private tunnel with a CI job or script, which sends it as the X-Tunnel-Authorization header.
+        </p>
+
+        <div className="flex flex-wrap gap-4 mb-3">
+          {TOKEN_SCOPES.map((scope) => (
+            <label key={scope} className="flex items-center gap-2 text-sm text-gray-300">
+              <input
+                type="checkbox"
+                checked={tokenScopes.includes(scope)}
+                onChange={() => toggleTokenScope(scope)}
+              />
+              {scope}
+            </label>
+          ))}
+        </div>
+
+        <div className="flex gap-3">
+          <input
+            type="number"
+            value={tokenPort}
+            onChange={(e) => setTokenPort(e.target.value)}
+            placeholder="Port (optional)"
+            className="input-field w-40"
+          />
+          <button
+            onClick={handleIssueToken}
+            disabled={isIssuing || tokenScopes.length === 0}
+            className="btn-primary flex-1"
+          >
+            {isIssuing ? 'Issuing...' : 'Issue Token'}
+          </button>
+        </div>
+
+        {issuedToken && (
+          <div className="mt-4 space-y-2">
+            <div className="flex gap-2">
+              <code className="flex-1 bg-gray-800 px-3 py-2 rounded text-xs text-gray-300 break-all">
+                {issuedToken.token}
+              </code>
+              <button
+                onClick={() => navigator.clipboard.writeText(issuedToken.token)}
+                className="btn-secondary text-sm"
+              >
+                Copy
+              </button>
+            </div>
+            <p className={`text-sm ${issuedToken.expiringSoon ? 'text-yellow-400' : 'text-gray-400'}`}>
+              Scopes: {issuedToken.scopes.join(', ')}
+              {issuedToken.port !== undefined && issuedToken.port !== null && ` · Port ${issuedToken.port}`}
+              {issuedToken.expiresAt && ` · Expires ${new Date(issuedToken.expiresAt).toLocaleString()}`}
+            </p>
+          </div>
+        )}
+      </div>
+
       {/* Current Access Info */}
       <div>
         <div className="flex items-center justify-between mb-4">
@@ -1,4 +1,5 @@
-import { useEffect, useState } from 'react';
+import { useEffect, useRef, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import { useTunnelStore } from '../../stores/tunnelStore';
 import { tunnelApi } from '../../lib/api';
 import TunnelCard from './TunnelCard';
@@ -17,6 +18,27 @@ export default function Dashboard() {
     }
   }, []);
 
+  // Changes picked up by the backend poller (other machines, the CLI). One pass
+  // can emit several events, so reload once after they settle.
+  const reloadTimer = useRef<ReturnType<typeof setTimeout> | undefined>(undefined);
+  useEffect(() => {
+    const reload = () => {
+      clearTimeout(reloadTimer.current);
+      reloadTimer.current = setTimeout(() => {
+        invalidateTunnelList();
+        loadTunnels(true);
+      }, 200);
+    };
+    const unlisteners = ['tunnel-added', 'tunnel-removed', 'tunnel-updated', 'port-changed'].map(
+      (event) => listen(event, reload)
+    );
+
+    return () => {
+      clearTimeout(reloadTimer.current);
+      unlisteners.forEach((unlisten) => unlisten.then(fn => fn()));
+    };
+  }, []);
+
   const loadTunnels = async (forceRefresh = false) => {
     // Check cache before loading
     if (!forceRefresh && isTunnelListCacheValid()) {
@@ -1,12 +1,13 @@
 import { useState, useEffect } from 'react';
-import { portApi, tunnelApi } from '../../lib/api';
-import type { Port, Protocol } from '../../types/devtunnel';
+import { listen } from '@tauri-apps/api/event';
+import { monitorApi, portApi, tunnelApi } from '../../lib/api';
+import type { DiscoveredPort, HealthCheckOptions, MonitorSettings, PingResult, Port, PortHealth, Protocol, Tunnel } from '../../types/devtunnel';
 import { toast } from '../Toast';
 
 interface PortManagerProps {
   tunnelId: string;
   onPortsChanged?: () => void;
-  tunnelDetails?: string; // Pre-loaded tunnel details to avoid redundant API calls
+  tunnelDetails?: Tunnel; // Pre-loaded tunnel details to avoid redundant API calls
 }
 
 export default function PortManager({ tunnelId, onPortsChanged, tunnelDetails: propTunnelDetails }: PortManagerProps) {
@@ -20,76 +21,49 @@ export default function PortManager({ tunnelId, onPortsChanged, tunnelDetails: p
   const [editDescription, setEditDescription] = useState('');
   const [editProtocol, setEditProtocol] = useState<Protocol>('auto');
   const [pingingPort, setPingingPort] = useState<number | null>(null);
-  const [pingResults, setPingResults] = useState<Map<number, { success: boolean; time: number; status?: number }>>(new Map());
+  const [pingResults, setPingResults] = useState<Map<number, PingResult>>(new Map());
+  const [healthPath, setHealthPath] = useState('');
+  const [expectedStatus, setExpectedStatus] = useState('');
+  const [bodyContains, setBodyContains] = useState('');
+  const [verifyTls, setVerifyTls] = useState(true);
   const [loadingDetails, setLoadingDetails] = useState<Set<number>>(new Set());
   const [expandedPorts, setExpandedPorts] = useState<Set<number>>(new Set());
+  const [monitorSettings, setMonitorSettings] = useState<MonitorSettings>({ monitors: [] });
+  const [portHealth, setPortHealth] = useState<Map<number, PortHealth>>(new Map());
+  const [suggestions, setSuggestions] = useState<DiscoveredPort[] | null>(null);
+  const [isDiscovering, setIsDiscovering] = useState(false);
 
   useEffect(() => {
     loadPorts();
   }, [tunnelId, propTunnelDetails]);
 
+  useEffect(() => {
+    monitorApi.getSettings().then(setMonitorSettings).catch((error) => {
+      console.error('Failed to load monitor settings:', error);
+    });
+    loadHealth();
+
+    const unlisten = listen('port-health-changed', () => loadHealth());
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, [tunnelId]);
+
+  const loadHealth = async () => {
+    try {
+      const health = await monitorApi.health();
+      setPortHealth(new Map(health.filter(h => h.tunnelId === tunnelId).map(h => [h.portNumber, h])));
+    } catch (error) {
+      console.error('Failed to load port health:', error);
+    }
+  };
+
   const loadPorts = async () => {
     setIsLoading(true);
     try {
       // Use pre-loaded tunnel details if available, otherwise fetch
       const details = propTunnelDetails || await tunnelApi.show(tunnelId);
-
-      // Parse ports from the raw details output
-      interface ParsedPort {
-        portNumber: number;
-        protocol: Protocol;
-        url?: string;
-      }
-      const parsedPorts: ParsedPort[] = [];
-      const lines = details.split('\n');
-      let inPortsSection = false;
-
-      for (const line of lines) {
-        const trimmed = line.trim();
-
-        // Check if we're entering the Ports section
-        if (trimmed.startsWith('Ports') && trimmed.includes(':')) {
-          inPortsSection = true;
-          continue;
-        }
-
-        // If in ports section and line starts with a number
-        if (inPortsSection && trimmed.match(/^\d+/)) {
-          // Try to match: portNumber  protocol  url (optional)
-          const matchWithUrl = trimmed.match(/^(\d+)\s+(\w+)\s+(https?:\/\/[^\s]+)/);
-          if (matchWithUrl) {
-            const [, portNum, protocol, uri] = matchWithUrl;
-            parsedPorts.push({
-              portNumber: parseInt(portNum),
-              protocol: protocol.toLowerCase() as Protocol,
-              url: uri,
-            });
-          } else {
-            // Try to match: portNumber  protocol (no url - tunnel not hosted)
-            const matchWithoutUrl = trimmed.match(/^(\d+)\s+(\w+)\s*$/);
-            if (matchWithoutUrl) {
-              const [, portNum, protocol] = matchWithoutUrl;
-              parsedPorts.push({
-                portNumber: parseInt(portNum),
-                protocol: protocol.toLowerCase() as Protocol,
-              });
-            }
-          }
-        } else if (inPortsSection && trimmed.match(/^Tunnel/)) {
-          // End of ports section
-          break;
-        }
-      }
-
-      // Create basic port objects without descriptions (lazy load on demand)
-      const portList = parsedPorts.map((parsed) => ({
-        portNumber: parsed.portNumber,
-        protocol: parsed.protocol,
-        portForwardingUris: parsed.url ? [parsed.url] : [],
-        description: undefined,
-      }));
-
-      setPorts(portList);
+      setPorts(details.ports);
     } catch (error) {
       console.error('Failed to load ports:', error);
     } finally {
@@ -97,6 +71,25 @@ export default function PortManager({ tunnelId, onPortsChanged, tunnelDetails: p
     }
   };
 
+  const handleDiscover = async () => {
+    setIsDiscovering(true);
+    try {
+      setSuggestions(await portApi.discover(tunnelId));
+    } catch (error) {
+      toast.error(`Failed to discover local ports: ${error}`);
+    } finally {
+      setIsDiscovering(false);
+    }
+  };
+
+  // Fill the form from a suggestion; the user still confirms with Add
+  const handleUseSuggestion = (suggestion: DiscoveredPort) => {
+    setNewPort(String(suggestion.portNumber));
+    setNewProtocol(suggestion.protocol ?? 'auto');
+    setNewDescription(suggestion.process ?? '');
+    setSuggestions(prev => prev?.filter(s => s.portNumber !== suggestion.portNumber) ?? null);
+  };
+
   const handleAddPort = async () => {
     if (!newPort) return;
 
@@ -246,6 +239,57 @@ export default function PortManager({ tunnelId, onPortsChanged, tunnelDetails: p
     }
   };
 
+  const formatTimings = (result: PingResult) => {
+    const { dnsMs, connectMs, tlsMs, firstByteMs } = result.timings;
+    return [
+      dnsMs !== undefined && `DNS ${dnsMs}ms`,
+      connectMs !== undefined && `connect ${connectMs}ms`,
+      tlsMs !== undefined && `TLS ${tlsMs}ms`,
+      firstByteMs !== undefined && `first byte ${firstByteMs}ms`,
+      result.remoteAddress,
+    ].filter(Boolean).join(' · ');
+  };
+
+  const isMonitored = (portNumber: number) =>
+    monitorSettings.monitors.some(m => m.tunnelId === tunnelId && m.portNumber === portNumber);
+
+  // Checks every minute with the same options as Ping
+  const handleToggleMonitor = async (port: Port) => {
+    const others = monitorSettings.monitors.filter(m => m.tunnelId !== tunnelId || m.portNumber !== port.portNumber);
+    const monitors = isMonitored(port.portNumber) || !port.portForwardingUris?.length
+      ? others
+      : [...others, {
+          tunnelId,
+          portNumber: port.portNumber,
+          url: port.portForwardingUris[0],
+          intervalMs: 60000,
+          check: {
+            path: healthPath.trim() || undefined,
+            expectedStatus: expectedStatus.split(',').map(s => s.trim()).filter(s => s.length > 0),
+            bodyContains: bodyContains || undefined,
+            verifyTls,
+          },
+        }];
+
+    try {
+      setMonitorSettings(await monitorApi.setSettings({ monitors }));
+      loadHealth();
+    } catch (error) {
+      toast.error(`Failed to update monitoring: ${error}`);
+    }
+  };
+
+  const formatHealth = (health: PortHealth) => {
+    if (health.samples === 0) return 'Monitored, not checked yet';
+    return [
+      health.up ? '● Up' : '● Down',
+      `${health.uptimePercent!.toFixed(1)}% uptime (24h)`,
+      health.latencyP50Ms !== undefined && `p50 ${health.latencyP50Ms}ms`,
+      health.latencyP95Ms !== undefined && `p95 ${health.latencyP95Ms}ms`,
+      health.latencyP99Ms !== undefined && `p99 ${health.latencyP99Ms}ms`,
+    ].filter(Boolean).join(' · ');
+  };
+
   const handlePing = async (port: Port) => {
     if (!port.portForwardingUris || port.portForwardingUris.length === 0) {
       toast.warning('No URL available to ping. Make sure the tunnel is hosting.');
@@ -255,13 +299,15 @@ export default function PortManager({ tunnelId, onPortsChanged, tunnelDetails: p
     setPingingPort(port.portNumber);
     try {
       const url = port.portForwardingUris[0];
-      const result = await portApi.ping(url);
+      const check: HealthCheckOptions = {
+        path: healthPath.trim() || undefined,
+        expectedStatus: expectedStatus.split(',').map(s => s.trim()).filter(s => s.length > 0),
+        bodyContains: bodyContains || undefined,
+        verifyTls,
+      };
+      const result = await portApi.ping(url, check);
 
-      setPingResults(prev => new Map(prev).set(port.portNumber, {
-        success: result.success,
-        time: result.responseTimeMs,
-        status: result.statusCode,
-      }));
+      setPingResults(prev => new Map(prev).set(port.portNumber, result));
 
       if (result.success) {
         console.log(`✓ Port ${port.portNumber} is reachable (${result.responseTimeMs}ms, HTTP ${result.statusCode})`);
@@ -272,7 +318,9 @@ export default function PortManager({ tunnelId, onPortsChanged, tunnelDetails: p
       console.error('Failed to ping port:', error);
       setPingResults(prev => new Map(prev).set(port.portNumber, {
         success: false,
-        time: 0,
+        responseTimeMs: 0,
+        error: String(error),
+        timings: {},
       }));
     } finally {
       setPingingPort(null);
@@ -283,7 +331,32 @@ export default function PortManager({ tunnelId, onPortsChanged, tunnelDetails: p
     <div className="space-y-6">
       {/* Add Port Form */}
       <div className="card bg-gray-750">
-        <h3 className="text-lg font-semibold text-white mb-4">Add New Port</h3>
+        <div className="flex items-center justify-between mb-4">
+          <h3 className="text-lg font-semibold text-white">Add New Port</h3>
+          <button onClick={handleDiscover} disabled={isDiscovering} className="btn-secondary text-xs">
+            {isDiscovering ? 'Scanning...' : 'Suggest local ports'}
+          </button>
+        </div>
+        {suggestions && (
+          <div className="flex flex-wrap gap-2 mb-4">
+            {suggestions.length === 0 ? (
+              <span className="text-sm text-zinc-500">No other listening ports found.</span>
+            ) : (
+              suggestions.map(s => (
+                <button
+                  key={s.portNumber}
+                  onClick={() => handleUseSuggestion(s)}
+                  title={`Listening on ${s.addresses.join(', ')}${s.pid ? ` (pid ${s.pid})` : ''}`}
+                  className="text-xs font-mono bg-gray-700 hover:bg-gray-600 text-gray-200 px-2 py-1 rounded"
+                >
+                  {s.portNumber}
+                  {s.process && <span className="text-zinc-400"> {s.process}</span>}
+                  {s.protocol && <span className="text-primary-300"> {s.protocol}</span>}
+                </button>
+              ))
+            )}
+          </div>
+        )}
         <div className="grid grid-cols-1 md:grid-cols-4 gap-3">
           <div>
             <label className="block text-sm text-gray-400 mb-1">Port Number</label>
@@ -334,6 +407,34 @@ export default function PortManager({ tunnelId, onPortsChanged, tunnelDetails: p
       {/* Port List */}
       <div>
         <h3 className="text-lg font-semibold text-white mb-4">Active Ports</h3>
+        <div className="flex items-center gap-2 flex-wrap mb-4 text-sm text-gray-400">
+          <span>Ping checks</span>
+          <input
+            type="text"
+            value={healthPath}
+            onChange={(e) => setHealthPath(e.target.value)}
+            placeholder="Path, e.g. /healthz"
+            className="input-field w-40"
+          />
+          <input
+            type="text"
+            value={expectedStatus}
+            onChange={(e) => setExpectedStatus(e.target.value)}
+            placeholder="Status, e.g. 200-299"
+            className="input-field w-40"
+          />
+          <input
+            type="text"
+            value={bodyContains}
+            onChange={(e) => setBodyContains(e.target.value)}
+            placeholder="Body contains"
+            className="input-field w-40"
+          />
+          <label className="flex items-center gap-1">
+            <input type="checkbox" checked={verifyTls} onChange={(e) => setVerifyTls(e.target.checked)} />
+            Verify TLS
+          </label>
+        </div>
         {isLoading ? (
           <div className="text-center text-gray-400 py-8">Loading ports...</div>
         ) : ports.length === 0 ? (
@@ -451,14 +552,24 @@ export default function PortManager({ tunnelId, onPortsChanged, tunnelDetails: p
                           }`}>
                             {pingResults.get(port.portNumber)!.success ? (
                               <>
-                                ✓ Reachable ({pingResults.get(port.portNumber)!.time}ms)
-                                {pingResults.get(port.portNumber)!.status && (
-                                  <> - HTTP {pingResults.get(port.portNumber)!.status}</>
+                                ✓ Reachable ({pingResults.get(port.portNumber)!.responseTimeMs}ms)
+                                {pingResults.get(port.portNumber)!.statusCode && (
+                                  <> - HTTP {pingResults.get(port.portNumber)!.statusCode}</>
                                 )}
                               </>
                             ) : (
-                              '✗ Not reachable'
+                              <>✗ {pingResults.get(port.portNumber)!.error || 'Not reachable'}</>
                             )}
+                            <div className="text-gray-400 mt-1">
+                              {formatTimings(pingResults.get(port.portNumber)!)}
+                            </div>
+                          </div>
+                        )}
+                        {isMonitored(port.portNumber) && portHealth.has(port.portNumber) && (
+                          <div className={`mt-2 text-xs ${
+                            portHealth.get(port.portNumber)!.up === false ? 'text-red-400' : 'text-gray-400'
+                          }`}>
+                            {formatHealth(portHealth.get(port.portNumber)!)}
                           </div>
                         )}
                       </div>
@@ -486,6 +597,11 @@ export default function PortManager({ tunnelId, onPortsChanged, tunnelDetails: p
                         {pingingPort === port.portNumber ? 'Pinging...' : 'Ping'}
                       </button>
                     )}
+                    {port.portForwardingUris && port.portForwardingUris.length > 0 && (
+                      <button onClick={() => handleToggleMonitor(port)} className="btn-secondary">
+                        {isMonitored(port.portNumber) ? 'Unmonitor' : 'Monitor'}
+                      </button>
+                    )}
                     <button
                       onClick={() => handleDeletePort(port.portNumber)}
                       className="btn-danger"
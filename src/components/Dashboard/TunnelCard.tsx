@@ -93,6 +93,12 @@ function TunnelCard({ tunnel, onRefresh }: TunnelCardProps) {
           </div>
         </div>
 
+        {tunnel.detailsError && (
+          <div className="mb-4 text-xs text-yellow-400" title={tunnel.detailsError.error}>
+            Could not load ports: {tunnel.detailsError.error}
+          </div>
+        )}
+
         {/* Ports */}
         {tunnel.ports.length > 0 && (
           <div className="mb-4">
@@ -1,12 +1,15 @@
 import { useState, useEffect, useCallback } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import { useTunnelStore } from '../../stores/tunnelStore';
-import { tunnelApi } from '../../lib/api';
+import { ApiError, tunnelApi } from '../../lib/api';
+import type { HostExitedEvent, HostRestartedEvent, PortChangedEvent, Tunnel, TunnelUpdatedEvent, UpstreamReport } from '../../types/devtunnel';
 import { toast } from '../Toast';
 import PortManager from './PortManager';
 import AccessControlManager from './AccessControlManager';
 
 // Configuration constants
 const TUNNEL_REFRESH_DELAY_MS = 2000; // Wait time before refreshing tunnel details after host/restart
+const UPSTREAM_WAIT_MS = 30000; // How long Start Host waits for the local service when asked to
 
 // Chevron Down Icon Component
 function ChevronDownIcon({ className = '' }: { className?: string }) {
@@ -24,20 +27,19 @@ interface TunnelDetailPanelProps {
 export default function TunnelDetailPanel({ onRefresh: _onRefresh }: TunnelDetailPanelProps) {
   const { selectedTunnel, selectTunnel, getTunnelDetails, setTunnelDetails: setCachedTunnelDetails, invalidateTunnelDetails } = useTunnelStore();
   const [activeTab, setActiveTab] = useState<'info' | 'ports' | 'access'>('info');
-  const [tunnelDetails, setTunnelDetails] = useState<string>('');
+  const [tunnelDetails, setTunnelDetails] = useState<Tunnel | null>(null);
   const [_isLoading, setIsLoading] = useState(false);
   const [isHosting, setIsHosting] = useState(false);
   const [isAlreadyHosted, setIsAlreadyHosted] = useState(false);
   const [startTime, setStartTime] = useState<string | null>(null);
   const [isRestarting, setIsRestarting] = useState(false);
   const [isRawDetailsOpen, setIsRawDetailsOpen] = useState(false);
+  const [upstream, setUpstream] = useState<UpstreamReport | null>(null);
+  const [waitForUpstream, setWaitForUpstream] = useState(false);
 
-  const parseTunnelDetailsState = useCallback((details: string) => {
-    // Check if tunnel is already hosted by looking for "Host connections"
-    const hostConnectionsMatch = details.match(/Host connections\s*:\s*(\d+)/);
-    if (hostConnectionsMatch) {
-      const hostConnections = parseInt(hostConnectionsMatch[1]);
-      const isHosted = hostConnections > 0;
+  const parseTunnelDetailsState = useCallback((details: Tunnel) => {
+    if (details.hostConnections !== undefined && details.hostConnections !== null) {
+      const isHosted = details.hostConnections > 0;
       setIsAlreadyHosted(isHosted);
 
       // If tunnel is hosted, try to get start time
@@ -73,7 +75,7 @@ export default function TunnelDetailPanel({ onRefresh: _onRefresh }: TunnelDetai
 
     setIsLoading(true);
     try {
-      const details = await tunnelApi.show(selectedTunnel.tunnelId);
+      const details = await tunnelApi.show(selectedTunnel.tunnelId, undefined, forceRefresh);
       setTunnelDetails(details);
       setCachedTunnelDetails(selectedTunnel.tunnelId, details);
       parseTunnelDetailsState(details);
@@ -90,6 +92,63 @@ export default function TunnelDetailPanel({ onRefresh: _onRefresh }: TunnelDetai
     }
   }, [selectedTunnel, loadTunnelDetails]);
 
+  // Readiness of the local services behind the hosted ports
+  useEffect(() => {
+    if (!selectedTunnel) return;
+    setUpstream(null);
+    tunnelApi.getUpstreamStatus()
+      .then(reports => setUpstream(reports.find(r => r.tunnelId === selectedTunnel.tunnelId) ?? null))
+      .catch(error => console.error('Failed to load local service status:', error));
+
+    const unlisten = listen<UpstreamReport>('upstream-changed', (event) => {
+      if (event.payload.tunnelId === selectedTunnel.tunnelId) {
+        setUpstream(event.payload);
+      }
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, [selectedTunnel]);
+
+  // Refresh when the backend supervisor reports the host process exiting or being restarted
+  useEffect(() => {
+    if (!selectedTunnel) return;
+
+    const unlistenExited = listen<HostExitedEvent>('host-exited', (event) => {
+      if (event.payload.tunnelId !== selectedTunnel.tunnelId) return;
+      if (!event.payload.requested && !event.payload.willRestart) {
+        toast.error(`Tunnel host ${event.payload.tunnelId} exited: ${event.payload.exitStatus}`);
+      }
+      invalidateTunnelDetails(selectedTunnel.tunnelId);
+      loadTunnelDetails(true);
+    });
+
+    const unlistenRestarted = listen<HostRestartedEvent>('host-restarted', (event) => {
+      if (event.payload.tunnelId !== selectedTunnel.tunnelId) return;
+      toast.warning(`Tunnel host ${event.payload.tunnelId} restarted (attempt ${event.payload.attempt})`);
+      invalidateTunnelDetails(selectedTunnel.tunnelId);
+      setTimeout(() => {
+        loadTunnelDetails(true);
+      }, TUNNEL_REFRESH_DELAY_MS);
+    });
+
+    // Edits from elsewhere; the poller already refreshed the backend cache
+    const refreshOnChange = (event: { payload: TunnelUpdatedEvent | PortChangedEvent }) => {
+      if (event.payload.tunnelId !== selectedTunnel.tunnelId) return;
+      invalidateTunnelDetails(selectedTunnel.tunnelId);
+      loadTunnelDetails();
+    };
+    const unlistenUpdated = listen<TunnelUpdatedEvent>('tunnel-updated', refreshOnChange);
+    const unlistenPorts = listen<PortChangedEvent>('port-changed', refreshOnChange);
+
+    return () => {
+      unlistenExited.then(fn => fn());
+      unlistenRestarted.then(fn => fn());
+      unlistenUpdated.then(fn => fn());
+      unlistenPorts.then(fn => fn());
+    };
+  }, [selectedTunnel, invalidateTunnelDetails, loadTunnelDetails]);
+
   const handleStartHost = async () => {
     if (!selectedTunnel || selectedTunnel.ports.length === 0) {
       toast.warning('Please add at least one port before hosting the tunnel');
@@ -102,6 +161,7 @@ export default function TunnelDetailPanel({ onRefresh: _onRefresh }: TunnelDetai
         tunnelId: selectedTunnel.tunnelId,
         ports: selectedTunnel.ports,
         allowAnonymous: true,
+        upstream: waitForUpstream ? { waitMs: UPSTREAM_WAIT_MS } : undefined,
       });
 
       toast.success(`Tunnel ${selectedTunnel.tunnelId} is now hosting in the background!`);
@@ -112,7 +172,11 @@ export default function TunnelDetailPanel({ onRefresh: _onRefresh }: TunnelDetai
         loadTunnelDetails(true);
       }, TUNNEL_REFRESH_DELAY_MS);
     } catch (error) {
-      toast.error(`Failed to host tunnel: ${error}`);
+      if (error instanceof ApiError && error.code === 'timeout') {
+        toast.error(`${error.message}. Start the local service and try again.`);
+      } else {
+        toast.error(`Failed to host tunnel: ${error}`);
+      }
     } finally {
       setIsHosting(false);
     }
@@ -221,6 +285,13 @@ export default function TunnelDetailPanel({ onRefresh: _onRefresh }: TunnelDetai
                         : 'Ready to host'}
                     </p>
                   )}
+                  {upstream && (
+                    <p className={`text-xs mt-1 ${upstream.ready ? 'text-zinc-500' : 'text-yellow-400'}`}>
+                      Local service: {upstream.ports.map(p =>
+                        `${p.portNumber} ${p.ready ? '✓' : '✗'}`
+                      ).join(', ')}
+                    </p>
+                  )}
                 </div>
                 <div className="flex gap-2">
                   {isAlreadyHosted ? (
@@ -232,13 +303,23 @@ export default function TunnelDetailPanel({ onRefresh: _onRefresh }: TunnelDetai
                       {isRestarting ? 'Restarting...' : 'Restart'}
                     </button>
                   ) : (
-                    <button
-                      onClick={handleStartHost}
-                      disabled={isHosting || selectedTunnel.ports.length === 0}
-                      className="btn-primary text-xs"
-                    >
-                      {isHosting ? 'Starting...' : 'Start Host'}
-                    </button>
+                    <>
+                      <label className="flex items-center gap-1 text-xs text-zinc-400">
+                        <input
+                          type="checkbox"
+                          checked={waitForUpstream}
+                          onChange={(e) => setWaitForUpstream(e.target.checked)}
+                        />
+                        Wait for local service
+                      </label>
+                      <button
+                        onClick={handleStartHost}
+                        disabled={isHosting || selectedTunnel.ports.length === 0}
+                        className="btn-primary text-xs"
+                      >
+                        {isHosting ? 'Starting...' : 'Start Host'}
+                      </button>
+                    </>
                   )}
                 </div>
               </div>
@@ -275,6 +356,20 @@ export default function TunnelDetailPanel({ onRefresh: _onRefresh }: TunnelDetai
                     {selectedTunnel.ports.length > 0 ? selectedTunnel.ports.join(', ') : 'None'}
                   </p>
                 </div>
+                {tunnelDetails && (
+                  <div>
+                    <label className="text-xs text-zinc-500 uppercase tracking-wider">Connections</label>
+                    <p className="text-sm text-white mt-1">
+                      {tunnelDetails.hostConnections ?? 0} host / {tunnelDetails.clientConnections ?? 0} client
+                    </p>
+                  </div>
+                )}
+                {tunnelDetails?.clusterId && (
+                  <div>
+                    <label className="text-xs text-zinc-500 uppercase tracking-wider">Cluster</label>
+                    <p className="text-sm text-white mt-1 font-mono">{tunnelDetails.clusterId}</p>
+                  </div>
+                )}
               </div>
 
               {/* Tags */}
@@ -308,7 +403,7 @@ export default function TunnelDetailPanel({ onRefresh: _onRefresh }: TunnelDetai
                   {isRawDetailsOpen && (
                     <div className="p-4 bg-dark-900">
                       <pre className="text-xs text-zinc-400 overflow-x-auto font-mono leading-relaxed">
-                        {tunnelDetails}
+                        {JSON.stringify(tunnelDetails, null, 2)}
                       </pre>
                     </div>
                   )}
@@ -324,14 +419,14 @@ export default function TunnelDetailPanel({ onRefresh: _onRefresh }: TunnelDetai
                 invalidateTunnelDetails(selectedTunnel.tunnelId);
                 loadTunnelDetails(true);
               }}
-              tunnelDetails={tunnelDetails}
+              tunnelDetails={tunnelDetails ?? undefined}
             />
           )}
 
           {activeTab === 'access' && (
             <AccessControlManager
               tunnelId={selectedTunnel.tunnelId}
-              tunnelDetails={tunnelDetails}
+              tunnelDetails={tunnelDetails ?? undefined}
               onAccessChanged={() => {
                 invalidateTunnelDetails(selectedTunnel.tunnelId);
                 loadTunnelDetails(true);
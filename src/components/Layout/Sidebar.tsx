@@ -5,6 +5,7 @@ export default function Sidebar() {
 
   const menuItems = [
     { id: 'dashboard', name: 'Dashboard' },
+    { id: 'connections', name: 'Connections' },
     { id: 'logs', name: 'Logs' },
     { id: 'settings', name: 'Settings' },
   ];
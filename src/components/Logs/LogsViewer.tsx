@@ -1,53 +1,74 @@
 import { useState, useEffect, useRef } from 'react';
 import { listen } from '@tauri-apps/api/event';
-
-interface LogEntry {
-  timestamp: string;
-  level: 'INFO' | 'WARN' | 'ERROR' | 'DEBUG';
-  message: string;
-}
+import { logApi } from '../../lib/api';
+import { toast } from '../Toast';
+import type { LogEntry, LogLevel } from '../../types/devtunnel';
 
 // Maximum number of logs to keep in memory to prevent memory leaks
 const MAX_LOGS = 1000;
 
+const LEVELS: LogLevel[] = ['DEBUG', 'INFO', 'WARN', 'ERROR'];
+
+// "demo" and "demo.usw2" name the same tunnel
+const tunnelName = (id: string) => id.split('.')[0];
+
 export default function LogsViewer() {
-  const [logs, setLogs] = useState<LogEntry[]>([
-    { timestamp: new Date().toISOString(), level: 'INFO', message: 'DevTunnel GUI started' },
-    { timestamp: new Date().toISOString(), level: 'INFO', message: 'Checking authentication status...' },
-    { timestamp: new Date().toISOString(), level: 'INFO', message: 'User authenticated successfully' },
-  ]);
+  const [logs, setLogs] = useState<LogEntry[]>([]);
+  const [minLevel, setMinLevel] = useState<LogLevel>('DEBUG');
+  const [tunnelFilter, setTunnelFilter] = useState('');
   const [autoScroll, setAutoScroll] = useState(true);
   const logsEndRef = useRef<HTMLDivElement>(null);
 
   useEffect(() => {
-    // Listen for log events from Tauri backend
-    const unlisten = listen<string>('devtunnel-log', (event) => {
-      const message = event.payload;
-      const logEntry: LogEntry = {
-        timestamp: new Date().toISOString(),
-        level: message.includes('ERROR') ? 'ERROR' :
-               message.includes('WARN') ? 'WARN' :
-               message.includes('DEBUG') ? 'DEBUG' : 'INFO',
-        message: message,
-      };
-
+    const append = (entries: LogEntry[]) => {
       // Keep only the most recent MAX_LOGS entries to prevent memory leaks
       setLogs(prev => {
-        const newLogs = [...prev, logEntry];
+        const lastId = prev.length > 0 ? prev[prev.length - 1].id : -1;
+        const newLogs = [...prev, ...entries.filter(e => e.id > lastId)];
         return newLogs.length > MAX_LOGS ? newLogs.slice(-MAX_LOGS) : newLogs;
       });
-    });
+    };
+
+    // Listen for log events from Tauri backend
+    const unlisten = listen<LogEntry>('log-entry', (event) => append([event.payload]));
+
+    // Entries logged before this view was opened
+    logApi.get({ limit: MAX_LOGS })
+      .then(entries => setLogs(prev => {
+        const firstId = prev.length > 0 ? prev[0].id : Infinity;
+        return [...entries.filter(e => e.id < firstId), ...prev];
+      }))
+      .catch(err => console.error('Failed to load logs:', err));
 
     return () => {
       unlisten.then(fn => fn());
     };
   }, []);
 
+  const visibleLogs = logs.filter(log =>
+    LEVELS.indexOf(log.level) >= LEVELS.indexOf(minLevel) &&
+    (!tunnelFilter || (log.tunnelId !== undefined && log.tunnelId !== null &&
+      tunnelName(log.tunnelId) === tunnelName(tunnelFilter.trim())))
+  );
+
+  const tunnelIds = Array.from(new Set(
+    logs.flatMap(log => log.tunnelId ? [tunnelName(log.tunnelId)] : [])
+  )).sort();
+
   useEffect(() => {
     if (autoScroll && logsEndRef.current) {
       logsEndRef.current.scrollIntoView({ behavior: 'smooth' });
     }
-  }, [logs, autoScroll]);
+  }, [visibleLogs.length, autoScroll]);
+
+  const handleExportLogs = async () => {
+    try {
+      const path = await logApi.export(undefined, tunnelFilter || undefined);
+      toast.success(`Logs exported to ${path}`);
+    } catch (error) {
+      toast.error(`Failed to export logs: ${error}`);
+    }
+  };
 
   const handleClearLogs = () => {
     setLogs([]);
@@ -81,7 +102,26 @@ export default function LogsViewer() {
           <h2 className="text-3xl font-semibold text-white tracking-tight">Logs</h2>
           <p className="text-zinc-400 text-sm mt-1">Real-time application and tunnel logs</p>
         </div>
-        <div className="flex gap-2">
+        <div className="flex gap-2 items-center">
+          <select
+            value={minLevel}
+            onChange={(e) => setMinLevel(e.target.value as LogLevel)}
+            className="input-field"
+          >
+            {LEVELS.map(level => (
+              <option key={level} value={level}>{level === 'DEBUG' ? 'All levels' : `${level} and above`}</option>
+            ))}
+          </select>
+          <select
+            value={tunnelFilter}
+            onChange={(e) => setTunnelFilter(e.target.value)}
+            className="input-field"
+          >
+            <option value="">All tunnels</option>
+            {tunnelIds.map(id => (
+              <option key={id} value={id}>{id}</option>
+            ))}
+          </select>
           <label className="flex items-center gap-2 text-sm text-zinc-400">
             <input
               type="checkbox"
@@ -91,6 +131,9 @@ export default function LogsViewer() {
             />
             Auto-scroll
           </label>
+          <button onClick={handleExportLogs} className="btn-secondary">
+            Export
+          </button>
           <button onClick={handleClearLogs} className="btn-secondary">
             Clear Logs
           </button>
@@ -99,19 +142,27 @@ export default function LogsViewer() {
 
       <div className="card flex-1 flex flex-col min-h-0">
         <div className="bg-dark-950 p-4 rounded font-mono text-sm flex-1 overflow-y-auto">
-          {logs.length === 0 ? (
+          {visibleLogs.length === 0 ? (
             <div className="text-gray-500 py-4 text-center">
-              No logs yet. Logs will appear here in real-time.
+              {logs.length === 0
+                ? 'No logs yet. Logs will appear here in real-time.'
+                : 'No logs match the current filters.'}
             </div>
           ) : (
-            logs.map((log, idx) => (
-              <div key={idx} className="py-1 border-b border-gray-800/50 hover:bg-gray-900/30">
+            visibleLogs.map((log) => (
+              <div key={log.id} className="py-1 border-b border-gray-800/50 hover:bg-gray-900/30">
                 <span className="text-gray-600">{formatTimestamp(log.timestamp)}</span>
                 {' '}
                 <span className={`font-semibold ${getLevelColor(log.level)}`}>
                   [{log.level}]
                 </span>
                 {' '}
+                {log.source && (
+                  <>
+                    <span className="text-zinc-500">{log.source}{log.tunnelId ? `@${log.tunnelId}` : ''}</span>
+                    {' '}
+                  </>
+                )}
                 <span className="text-gray-300">{log.message}</span>
               </div>
             ))
@@ -121,7 +172,7 @@ export default function LogsViewer() {
       </div>
 
       <div className="mt-4 text-xs text-zinc-500 flex-shrink-0">
-        <p>📊 Total logs: {logs.length}</p>
+        <p>📊 Showing {visibleLogs.length} of {logs.length} logs</p>
         <p>✅ Real-time logging is active. All DevTunnel operations will be logged here.</p>
       </div>
     </div>
@@ -1,20 +1,89 @@
 import { useState, useEffect } from 'react';
 import { getVersion } from '@tauri-apps/api/app';
-import { systemApi, type DevTunnelInfo } from '../../lib/api';
+import { systemApi, sessionApi, pollerApi, logApi, type DevTunnelInfo } from '../../lib/api';
 import { toast } from '../../components/Toast';
 import { useTunnelStore } from '../../stores/tunnelStore';
+import type { HostSessionsReport, LogFileSettings, PollerSettings, RedactionSettings } from '../../types/devtunnel';
 
 export default function Settings() {
   const { getDevTunnelInfo, setDevTunnelInfo: setCachedDevTunnelInfo } = useTunnelStore();
   const [devTunnelInfo, setDevTunnelInfo] = useState<DevTunnelInfo | null>(null);
   const [isLoading, setIsLoading] = useState(true);
   const [appVersion, setAppVersion] = useState<string>('Loading...');
+  const [sessions, setSessions] = useState<HostSessionsReport | null>(null);
+  const [poller, setPoller] = useState<PollerSettings | null>(null);
+  const [logFiles, setLogFiles] = useState<LogFileSettings | null>(null);
+  const [redaction, setRedaction] = useState<RedactionSettings | null>(null);
 
   useEffect(() => {
     checkInstallation();
     loadAppVersion();
+    loadSessions();
+    pollerApi.getSettings().then(setPoller).catch((error) => {
+      console.error('Failed to load refresh settings:', error);
+    });
+    logApi.getFileSettings().then(setLogFiles).catch((error) => {
+      console.error('Failed to load log file settings:', error);
+    });
+    logApi.getRedactionSettings().then(setRedaction).catch((error) => {
+      console.error('Failed to load redaction settings:', error);
+    });
   }, []);
 
+  const saveRedactionPatterns = async (text: string) => {
+    const patterns = text.split('\n').map(p => p.trim()).filter(p => p.length > 0);
+    try {
+      setRedaction(await logApi.setRedactionSettings({ patterns }));
+    } catch (error) {
+      toast.error(`Failed to update setting: ${error}`);
+    }
+  };
+
+  const saveLogFiles = async (changes: Partial<LogFileSettings>) => {
+    if (!logFiles) return;
+    try {
+      setLogFiles(await logApi.setFileSettings({ ...logFiles, ...changes }));
+    } catch (error) {
+      toast.error(`Failed to update setting: ${error}`);
+    }
+  };
+
+  const savePollInterval = async (seconds: number) => {
+    try {
+      setPoller(await pollerApi.setSettings({ intervalMs: Math.max(0, Math.round(seconds)) * 1000 }));
+    } catch (error) {
+      toast.error(`Failed to update setting: ${error}`);
+    }
+  };
+
+  const loadSessions = async () => {
+    try {
+      setSessions(await sessionApi.list());
+    } catch (error) {
+      console.error('Failed to load host sessions:', error);
+    }
+  };
+
+  const toggleRestoreOnStartup = async (enabled: boolean) => {
+    try {
+      await sessionApi.setRestoreOnStartup(enabled);
+      await loadSessions();
+    } catch (error) {
+      toast.error(`Failed to update setting: ${error}`);
+    }
+  };
+
+  const restoreSessions = async () => {
+    try {
+      const restored = await sessionApi.restore();
+      toast.success(`Restored ${restored.length} tunnel host(s)`);
+    } catch (error) {
+      toast.error(`Failed to restore tunnel hosts: ${error}`);
+    } finally {
+      await loadSessions();
+    }
+  };
+
   const loadAppVersion = async () => {
     try {
       const version = await getVersion();
@@ -160,6 +229,109 @@ export default function Settings() {
           )}
         </div>
 
+        {/* Hosted Sessions */}
+        <div className="card">
+          <h2 className="text-xl font-semibold text-white mb-4">Hosted Sessions</h2>
+          <label className="flex items-center gap-3 text-gray-300 mb-4">
+            <input
+              type="checkbox"
+              checked={sessions?.restoreOnStartup ?? false}
+              onChange={(e) => toggleRestoreOnStartup(e.target.checked)}
+            />
+            Re-host tunnels that were running when the app was last closed
+          </label>
+
+          {sessions && sessions.sessions.some(s => s.state === 'stopped') && (
+            <div className="space-y-2">
+              <p className="text-sm text-gray-400">Not running since the last session:</p>
+              {sessions.sessions.filter(s => s.state === 'stopped').map(s => (
+                <div key={s.tunnelId} className="flex items-center justify-between bg-gray-800 rounded px-3 py-2">
+                  <code className="text-white">{s.tunnelId}</code>
+                  <button
+                    onClick={() => sessionApi.forget(s.tunnelId).then(loadSessions)}
+                    className="text-sm text-gray-400 hover:text-white"
+                  >
+                    Forget
+                  </button>
+                </div>
+              ))}
+              <button onClick={restoreSessions} className="btn-secondary">
+                Restore All
+              </button>
+            </div>
+          )}
+        </div>
+
+        {/* Background Refresh */}
+        <div className="card">
+          <h2 className="text-xl font-semibold text-white mb-4">Background Refresh</h2>
+          <label className="flex items-center gap-3 text-gray-300">
+            Check for changes made elsewhere every
+            <input
+              type="number"
+              min={0}
+              className="input-field w-24"
+              defaultValue={poller ? poller.intervalMs / 1000 : undefined}
+              key={poller?.intervalMs}
+              onBlur={(e) => savePollInterval(Number(e.target.value))}
+            />
+            seconds (0 = off)
+          </label>
+        </div>
+
+        {/* Log Files */}
+        <div className="card">
+          <h2 className="text-xl font-semibold text-white mb-4">Log Files</h2>
+          <div className="space-y-3 text-gray-300">
+            <label className="flex items-center gap-3">
+              Start a new file after
+              <input
+                type="number"
+                min={1}
+                className="input-field w-24"
+                defaultValue={logFiles ? Math.round(logFiles.maxFileBytes / (1024 * 1024)) : undefined}
+                key={`size-${logFiles?.maxFileBytes}`}
+                onBlur={(e) => saveLogFiles({ maxFileBytes: Math.max(1, Math.round(Number(e.target.value))) * 1024 * 1024 })}
+              />
+              MB (and every day)
+            </label>
+            <label className="flex items-center gap-3">
+              Keep at most
+              <input
+                type="number"
+                min={1}
+                className="input-field w-24"
+                defaultValue={logFiles?.maxFiles}
+                key={`files-${logFiles?.maxFiles}`}
+                onBlur={(e) => saveLogFiles({ maxFiles: Math.max(1, Math.round(Number(e.target.value))) })}
+              />
+              old files per log, for
+              <input
+                type="number"
+                min={0}
+                className="input-field w-24"
+                defaultValue={logFiles?.retentionDays}
+                key={`days-${logFiles?.retentionDays}`}
+                onBlur={(e) => saveLogFiles({ retentionDays: Math.max(0, Math.round(Number(e.target.value))) })}
+              />
+              days (0 = no limit)
+            </label>
+            <div>
+              <p className="text-sm mb-2">
+                Tokens, bearer credentials, X-Tunnel-Authorization values and email addresses are always masked.
+                Additional patterns to mask (one regular expression per line):
+              </p>
+              <textarea
+                className="input-field w-full font-mono text-sm"
+                rows={3}
+                defaultValue={redaction?.patterns.join('\n')}
+                key={redaction?.patterns.join('\n')}
+                onBlur={(e) => saveRedactionPatterns(e.target.value)}
+              />
+            </div>
+          </div>
+        </div>
+
         {/* About */}
         <div className="card">
           <h2 className="text-xl font-semibold text-white mb-4">About</h2>
@@ -1,28 +1,68 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
   CommandResponse,
+  CliLimits,
+  CliTimeouts,
+  PollerSettings,
+  MonitorSettings,
+  PortHealth,
+  HealthSample,
+  LogEntry,
+  LogFilter,
+  LogFileSettings,
+  LogRange,
+  RedactionSettings,
+  TokenScope,
+  TunnelToken,
+  ErrorCode,
   CreateTunnelRequest,
   HostTunnelRequest,
+  Tunnel,
   TunnelListItem,
   ListTunnelsRequest,
   UpdateTunnelRequest,
   CreatePortRequest,
   UpdatePortRequest,
   Port,
+  DiscoveredPort,
   CreateAccessRequest,
+  DeleteAccessRequest,
+  AccessControlEntry,
+  AccessEntry,
+  AccessPlan,
+  SpecPlan,
+  SpecApplyReport,
   UserInfo,
   Cluster,
   PingResult,
+  HealthCheckOptions,
+  UpstreamStatus,
+  UpstreamReport,
+  HostOutput,
+  HostSessionsReport,
+  StopResult,
+  ConnectTunnelRequest,
+  ConnectSession,
 } from '../types/devtunnel';
 
 // API Error class for better error handling
 export class ApiError extends Error {
-  constructor(message: string, public command?: string) {
+  constructor(
+    message: string,
+    public command?: string,
+    public code?: ErrorCode,
+    public hint?: string
+  ) {
     super(message);
     this.name = 'ApiError';
   }
 }
 
+// Carry the backend's errorCode/hint along with the message
+function responseError(response: CommandResponse<unknown>, fallback: string, command?: string): ApiError {
+  return new ApiError(response.error || fallback, command, response.errorCode, response.hint);
+}
+
 // Common wrapper for Tauri command invocations
 async function invokeCommand<T>(
   command: string,
@@ -32,10 +72,7 @@ async function invokeCommand<T>(
   const response = await invoke<CommandResponse<T>>(command, args);
 
   if (!response.success || response.data === undefined || response.data === null) {
-    throw new ApiError(
-      response.error || errorMessage || `Command '${command}' failed`,
-      command
-    );
+    throw responseError(response, errorMessage || `Command '${command}' failed`, command);
   }
 
   return response.data;
@@ -43,8 +80,8 @@ async function invokeCommand<T>(
 
 // Authentication API
 export const authApi = {
-  login: async (provider: 'microsoft' | 'github', useDeviceCode = false): Promise<string> => {
-    return invokeCommand<string>('login_devtunnel', { provider, useDeviceCode }, 'Login failed');
+  login: async (provider: 'microsoft' | 'github', useDeviceCode = false, opId?: string): Promise<string> => {
+    return invokeCommand<string>('login_devtunnel', { provider, useDeviceCode, opId }, 'Login failed');
   },
 
   logout: async (): Promise<string> => {
@@ -62,27 +99,28 @@ export const tunnelApi = {
     const response = await invoke<CommandResponse<string>>('create_tunnel', { req });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to create tunnel');
+      throw responseError(response, 'Failed to create tunnel');
     }
 
     return response.data;
   },
 
-  list: async (req?: ListTunnelsRequest): Promise<TunnelListItem[]> => {
-    const response = await invoke<CommandResponse<TunnelListItem[]>>('list_tunnels', { req });
+  // forceRefresh bypasses the backend's cache of CLI results
+  list: async (req?: ListTunnelsRequest, opId?: string, forceRefresh?: boolean): Promise<TunnelListItem[]> => {
+    const response = await invoke<CommandResponse<TunnelListItem[]>>('list_tunnels', { req, opId, forceRefresh });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to list tunnels');
+      throw responseError(response, 'Failed to list tunnels');
     }
 
     return response.data;
   },
 
-  show: async (tunnelId?: string): Promise<string> => {
-    const response = await invoke<CommandResponse<string>>('show_tunnel', { tunnelId });
+  show: async (tunnelId?: string, opId?: string, forceRefresh?: boolean): Promise<Tunnel> => {
+    const response = await invoke<CommandResponse<Tunnel>>('show_tunnel', { tunnelId, opId, forceRefresh });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to show tunnel');
+      throw responseError(response, 'Failed to show tunnel');
     }
 
     return response.data;
@@ -92,7 +130,7 @@ export const tunnelApi = {
     const response = await invoke<CommandResponse<string>>('update_tunnel', { req });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to update tunnel');
+      throw responseError(response, 'Failed to update tunnel');
     }
 
     return response.data;
@@ -102,7 +140,7 @@ export const tunnelApi = {
     const response = await invoke<CommandResponse<string>>('delete_tunnel', { tunnelId });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to delete tunnel');
+      throw responseError(response, 'Failed to delete tunnel');
     }
 
     return response.data;
@@ -112,7 +150,7 @@ export const tunnelApi = {
     const response = await invoke<CommandResponse<string>>('delete_all_tunnels');
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to delete all tunnels');
+      throw responseError(response, 'Failed to delete all tunnels');
     }
 
     return response.data;
@@ -122,17 +160,17 @@ export const tunnelApi = {
     const response = await invoke<CommandResponse<string>>('host_tunnel', { req });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to host tunnel');
+      throw responseError(response, 'Failed to host tunnel');
     }
 
     return response.data;
   },
 
-  stop: async (tunnelId: string): Promise<string> => {
-    const response = await invoke<CommandResponse<string>>('stop_tunnel', { tunnelId });
+  stop: async (tunnelId: string, gracePeriodMs?: number): Promise<StopResult> => {
+    const response = await invoke<CommandResponse<StopResult>>('stop_tunnel', { tunnelId, gracePeriodMs });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to stop tunnel');
+      throw responseError(response, 'Failed to stop tunnel');
     }
 
     return response.data;
@@ -142,7 +180,7 @@ export const tunnelApi = {
     const response = await invoke<CommandResponse<string>>('restart_tunnel', { req });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to restart tunnel');
+      throw responseError(response, 'Failed to restart tunnel');
     }
 
     return response.data;
@@ -152,30 +190,82 @@ export const tunnelApi = {
     const response = await invoke<CommandResponse<string>>('get_tunnel_start_time', { tunnelId });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to get tunnel start time');
+      throw responseError(response, 'Failed to get tunnel start time');
+    }
+
+    return response.data;
+  },
+
+  checkUpstream: async (ports: number[], probe?: HealthCheckOptions): Promise<UpstreamStatus[]> => {
+    return invokeCommand<UpstreamStatus[]>('check_upstream', { ports, probe }, 'Failed to check local services');
+  },
+
+  getUpstreamStatus: async (): Promise<UpstreamReport[]> => {
+    return invokeCommand<UpstreamReport[]>('get_upstream_status', undefined, 'Failed to get local service status');
+  },
+
+  getHostOutput: async (tunnelId: string, since?: number): Promise<HostOutput> => {
+    const response = await invoke<CommandResponse<HostOutput>>('get_host_output', { tunnelId, since });
+
+    if (!response.success || !response.data) {
+      throw responseError(response, 'Failed to get host output');
     }
 
     return response.data;
   },
 };
 
+// Hosted Session API
+export const sessionApi = {
+  list: async (): Promise<HostSessionsReport> => {
+    return invokeCommand<HostSessionsReport>('get_host_sessions', undefined, 'Failed to get host sessions');
+  },
+
+  restore: async (tunnelIds?: string[]): Promise<string[]> => {
+    return invokeCommand<string[]>('restore_host_sessions', { tunnelIds }, 'Failed to restore host sessions');
+  },
+
+  forget: async (tunnelId: string): Promise<string> => {
+    return invokeCommand<string>('forget_host_session', { tunnelId }, 'Failed to forget host session');
+  },
+
+  setRestoreOnStartup: async (enabled: boolean): Promise<boolean> => {
+    return invokeCommand<boolean>('set_restore_sessions_on_startup', { enabled }, 'Failed to update session settings');
+  },
+};
+
+// Connect API: forward remote tunnels to localhost
+export const connectApi = {
+  connect: async (req: ConnectTunnelRequest): Promise<ConnectSession> => {
+    return invokeCommand<ConnectSession>('connect_tunnel', { req }, 'Failed to connect to tunnel');
+  },
+
+  list: async (): Promise<ConnectSession[]> => {
+    return invokeCommand<ConnectSession[]>('list_connections', undefined, 'Failed to list connections');
+  },
+
+  stop: async (tunnelId: string, gracePeriodMs?: number): Promise<StopResult> => {
+    return invokeCommand<StopResult>('stop_connection', { tunnelId, gracePeriodMs }, 'Failed to disconnect');
+  },
+};
+
 // Port Management API
 export const portApi = {
   create: async (req: CreatePortRequest): Promise<string> => {
     const response = await invoke<CommandResponse<string>>('create_port', { req });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to create port');
+      throw responseError(response, 'Failed to create port');
     }
 
     return response.data;
   },
 
-  list: async (tunnelId: string): Promise<Port[]> => {
-    const response = await invoke<CommandResponse<Port[]>>('list_ports', { tunnelId });
+  list: async (tunnelId: string, forceRefresh?: boolean): Promise<Port[]> => {
+    const response = await invoke<CommandResponse<Port[]>>('list_ports', { tunnelId, forceRefresh });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to list ports');
+      throw responseError(response, 'Failed to list ports');
     }
 
     return response.data;
@@ -185,7 +275,7 @@ export const portApi = {
     const response = await invoke<CommandResponse<Port>>('show_port', { tunnelId, portNumber });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to show port');
+      throw responseError(response, 'Failed to show port');
     }
 
     return response.data;
@@ -195,7 +285,7 @@ export const portApi = {
     const response = await invoke<CommandResponse<string>>('update_port', { req });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to update port');
+      throw responseError(response, 'Failed to update port');
     }
 
     return response.data;
@@ -205,17 +295,22 @@ export const portApi = {
     const response = await invoke<CommandResponse<string>>('delete_port', { tunnelId, port });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to delete port');
+      throw responseError(response, 'Failed to delete port');
     }
 
     return response.data;
   },
 
-  ping: async (url: string): Promise<PingResult> => {
-    const response = await invoke<CommandResponse<PingResult>>('ping_port', { url });
+  // Listening local services, minus the tunnel's existing ports
+  discover: async (tunnelId?: string, probe?: boolean): Promise<DiscoveredPort[]> => {
+    return invokeCommand<DiscoveredPort[]>('discover_ports', { tunnelId, probe }, 'Failed to discover local ports');
+  },
+
+  ping: async (url: string, check?: HealthCheckOptions): Promise<PingResult> => {
+    const response = await invoke<CommandResponse<PingResult>>('ping_port', { url, check });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to ping port');
+      throw responseError(response, 'Failed to ping port');
     }
 
     return response.data;
@@ -228,7 +323,7 @@ export const accessApi = {
     const response = await invoke<CommandResponse<string>>('create_access', { req });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to create access');
+      throw responseError(response, 'Failed to create access');
     }
 
     return response.data;
@@ -238,7 +333,27 @@ export const accessApi = {
     const response = await invoke<CommandResponse<string>>('list_access', { tunnelId });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to list access');
+      throw responseError(response, 'Failed to list access');
+    }
+
+    return response.data;
+  },
+
+  listEntries: async (tunnelId: string, forceRefresh?: boolean): Promise<AccessEntry[]> => {
+    const response = await invoke<CommandResponse<AccessEntry[]>>('list_access_entries', { tunnelId, forceRefresh });
+
+    if (!response.success || !response.data) {
+      throw responseError(response, 'Failed to list access');
+    }
+
+    return response.data;
+  },
+
+  delete: async (req: DeleteAccessRequest): Promise<string> => {
+    const response = await invoke<CommandResponse<string>>('delete_access', { req });
+
+    if (!response.success || !response.data) {
+      throw responseError(response, 'Failed to delete access');
     }
 
     return response.data;
@@ -248,7 +363,46 @@ export const accessApi = {
     const response = await invoke<CommandResponse<string>>('reset_access', { tunnelId });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to reset access');
+      throw responseError(response, 'Failed to reset access');
+    }
+
+    return response.data;
+  },
+
+  issueToken: async (tunnelId: string, scopes: TokenScope[], port?: number): Promise<TunnelToken> => {
+    return invokeCommand<TunnelToken>('issue_token', { tunnelId, scopes, port }, 'Failed to issue token');
+  },
+
+  // Computes the create/delete/reset calls needed to reach `desired` without applying them
+  diff: async (tunnelId: string, desired: AccessControlEntry[]): Promise<AccessPlan> => {
+    const response = await invoke<CommandResponse<AccessPlan>>('diff_access', { tunnelId, desired });
+
+    if (!response.success || !response.data) {
+      throw responseError(response, 'Failed to compare access');
+    }
+
+    return response.data;
+  },
+};
+
+// Spec File API
+export const specApi = {
+  plan: async (path: string, opId?: string): Promise<SpecPlan> => {
+    const response = await invoke<CommandResponse<SpecPlan>>('plan_spec', { path, opId });
+
+    if (!response.success || !response.data) {
+      throw responseError(response, 'Failed to plan spec');
+    }
+
+    return response.data;
+  },
+
+  // Resolves with the per-step report even when a step fails
+  apply: async (path: string, opId?: string): Promise<SpecApplyReport> => {
+    const response = await invoke<CommandResponse<SpecApplyReport>>('apply_spec', { path, opId });
+
+    if (!response.success || !response.data) {
+      throw responseError(response, 'Failed to apply spec');
     }
 
     return response.data;
@@ -257,17 +411,110 @@ export const accessApi = {
 
 // Cluster API
 export const clusterApi = {
-  list: async (ping = false): Promise<Cluster[]> => {
-    const response = await invoke<CommandResponse<Cluster[]>>('list_clusters', { ping });
+  list: async (ping = false, opId?: string): Promise<Cluster[]> => {
+    const response = await invoke<CommandResponse<Cluster[]>>('list_clusters', { ping, opId });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to list clusters');
+      throw responseError(response, 'Failed to list clusters');
     }
 
     return response.data;
   },
 };
 
+// Operation API
+// Pass an id from newOperationId() to a cancellable call, then cancel() it to
+// abort; the pending call rejects with an ApiError whose code is 'cancelled'.
+// Only login, tunnel list/show/enrich, cluster list and spec plan/apply take an
+// opId; create, update, delete and port listing run to completion, and hosting
+// is ended with tunnelApi.stop() instead.
+export const newOperationId = (): string => crypto.randomUUID();
+
+export const operationApi = {
+  cancel: async (opId: string): Promise<boolean> => {
+    const response = await invoke<CommandResponse<boolean>>('cancel_operation', { opId });
+    return response.success && response.data === true;
+  },
+
+  getTimeouts: async (): Promise<CliTimeouts> => {
+    return invokeCommand<CliTimeouts>('get_cli_timeouts', undefined, 'Failed to get CLI timeouts');
+  },
+
+  setTimeouts: async (timeouts: CliTimeouts): Promise<CliTimeouts> => {
+    return invokeCommand<CliTimeouts>('set_cli_timeouts', { timeouts }, 'Failed to save CLI timeouts');
+  },
+
+  getLimits: async (): Promise<CliLimits> => {
+    return invokeCommand<CliLimits>('get_cli_limits', undefined, 'Failed to get CLI limits');
+  },
+
+  setLimits: async (limits: CliLimits): Promise<CliLimits> => {
+    return invokeCommand<CliLimits>('set_cli_limits', { limits }, 'Failed to save CLI limits');
+  },
+};
+
+// Background Refresh API
+export const pollerApi = {
+  getSettings: async (): Promise<PollerSettings> => {
+    return invokeCommand<PollerSettings>('get_poller_settings', undefined, 'Failed to get refresh settings');
+  },
+
+  setSettings: async (settings: PollerSettings): Promise<PollerSettings> => {
+    return invokeCommand<PollerSettings>('set_poller_settings', { settings }, 'Failed to save refresh settings');
+  },
+
+  setPaused: async (paused: boolean): Promise<boolean> => {
+    return invokeCommand<boolean>('set_poller_paused', { paused }, 'Failed to pause background refresh');
+  },
+};
+
+// Port Monitoring API
+export const monitorApi = {
+  getSettings: async (): Promise<MonitorSettings> => {
+    return invokeCommand<MonitorSettings>('get_monitor_settings', undefined, 'Failed to get monitor settings');
+  },
+
+  setSettings: async (settings: MonitorSettings): Promise<MonitorSettings> => {
+    return invokeCommand<MonitorSettings>('set_monitor_settings', { settings }, 'Failed to save monitor settings');
+  },
+
+  health: async (windowHours?: number): Promise<PortHealth[]> => {
+    return invokeCommand<PortHealth[]>('get_port_health', { windowHours }, 'Failed to load port health');
+  },
+
+  history: async (tunnelId: string, portNumber: number, since?: string): Promise<HealthSample[]> => {
+    return invokeCommand<HealthSample[]>('get_port_health_history', { tunnelId, portNumber, since }, 'Failed to load health history');
+  },
+};
+
+// Logs API
+export const logApi = {
+  get: async (filter?: LogFilter): Promise<LogEntry[]> => {
+    return invokeCommand<LogEntry[]>('get_logs', { filter }, 'Failed to load logs');
+  },
+
+  getFileSettings: async (): Promise<LogFileSettings> => {
+    return invokeCommand<LogFileSettings>('get_log_file_settings', undefined, 'Failed to get log file settings');
+  },
+
+  setFileSettings: async (settings: LogFileSettings): Promise<LogFileSettings> => {
+    return invokeCommand<LogFileSettings>('set_log_file_settings', { settings }, 'Failed to save log file settings');
+  },
+
+  getRedactionSettings: async (): Promise<RedactionSettings> => {
+    return invokeCommand<RedactionSettings>('get_redaction_settings', undefined, 'Failed to get redaction settings');
+  },
+
+  setRedactionSettings: async (settings: RedactionSettings): Promise<RedactionSettings> => {
+    return invokeCommand<RedactionSettings>('set_redaction_settings', { settings }, 'Failed to save redaction settings');
+  },
+
+  // Returns the path of the zip archive
+  export: async (range?: LogRange, tunnelId?: string): Promise<string> => {
+    return invokeCommand<string>('export_logs', { range, tunnelId }, 'Failed to export logs');
+  },
+};
+
 // System API
 export interface DevTunnelInfo {
   installed: boolean;
@@ -280,7 +527,7 @@ export const systemApi = {
     const response = await invoke<CommandResponse<DevTunnelInfo>>('check_devtunnel_installation');
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to check DevTunnel installation');
+      throw responseError(response, 'Failed to check DevTunnel installation');
     }
 
     return response.data;
@@ -290,7 +537,7 @@ export const systemApi = {
     const response = await invoke<CommandResponse<string>>('open_url', { url });
 
     if (!response.success || !response.data) {
-      throw new Error(response.error || 'Failed to open URL');
+      throw responseError(response, 'Failed to open URL');
     }
 
     return response.data;
@@ -1,12 +1,12 @@
 import { create } from 'zustand';
-import type { TunnelListItem, UserInfo } from '../types/devtunnel';
+import type { Tunnel, TunnelListItem, UserInfo } from '../types/devtunnel';
 import type { DevTunnelInfo } from '../lib/api';
 
 // Cache configuration
 const CACHE_EXPIRY_MS = 5 * 60 * 1000; // 5 minutes
 
 interface TunnelDetailsCache {
-  data: string;
+  data: Tunnel;
   timestamp: number;
 }
 
@@ -41,8 +41,8 @@ interface TunnelStore {
   reset: () => void;
 
   // Cache actions
-  getTunnelDetails: (tunnelId: string) => string | null;
-  setTunnelDetails: (tunnelId: string, data: string) => void;
+  getTunnelDetails: (tunnelId: string) => Tunnel | null;
+  setTunnelDetails: (tunnelId: string, data: Tunnel) => void;
   invalidateTunnelDetails: (tunnelId: string) => void;
   clearAllCache: () => void;
   isTunnelListCacheValid: () => boolean;
@@ -164,7 +164,7 @@ export const useTunnelStore = create<TunnelStore>((set, get) => ({
     return cached.data;
   },
 
-  setTunnelDetails: (tunnelId: string, data: string) => {
+  setTunnelDetails: (tunnelId: string, data: Tunnel) => {
     set((state) => {
       const newCache = new Map(state.tunnelDetailsCache);
       newCache.set(tunnelId, {
@@ -21,6 +21,34 @@ export interface PingResult {
   statusCode?: number;
   responseTimeMs: number;
   error?: string;
+  timings: PingTimings;
+  remoteAddress?: string;
+  // Only set when the check has a body expectation
+  bodyMatched?: boolean;
+}
+
+// Each phase on its own; missing when skipped (IP address, plain http) or not reached
+export interface PingTimings {
+  dnsMs?: number;
+  connectMs?: number;
+  tlsMs?: number;
+  firstByteMs?: number;
+}
+
+// Without options any HTTP response counts as reachable
+export interface HealthCheckOptions {
+  method?: string;
+  // Replaces the URL's path and query, e.g. "/healthz"
+  path?: string;
+  headers?: Record<string, string>;
+  // Sent as "X-Tunnel-Authorization: tunnel <token>"
+  tunnelToken?: string;
+  // Codes or ranges, e.g. ["200-299", "304", "2xx"]
+  expectedStatus?: string[];
+  bodyContains?: string;
+  bodyRegex?: string;
+  verifyTls?: boolean;
+  timeoutMs?: number;
 }
 
 export interface Tunnel {
@@ -34,6 +62,9 @@ export interface Tunnel {
   createdAt?: string;
   expiresAt?: string;
   allowAnonymous?: boolean;
+  hostConnections?: number;
+  clientConnections?: number;
+  accessControl: string[];
 }
 
 export interface TunnelListItem {
@@ -43,6 +74,8 @@ export interface TunnelListItem {
   ports: number[];
   status: TunnelStatus;
   expiresAt?: string;
+  // Set when fetching this tunnel's details failed; ports/tags may be missing
+  detailsError?: ItemError;
 }
 
 export interface CreateTunnelRequest {
@@ -53,12 +86,31 @@ export interface CreateTunnelRequest {
   expiration?: string;
 }
 
+export type RestartMode = 'never' | 'on-failure' | 'always';
+
+export interface RestartPolicy {
+  mode: RestartMode;
+  maxRetries?: number;
+  initialBackoffMs?: number;
+  maxBackoffMs?: number;
+}
+
 export interface HostTunnelRequest {
   tunnelId?: string;
   ports: number[];
   protocol?: Protocol;
   allowAnonymous?: boolean;
   expiration?: string;
+  restartPolicy?: RestartPolicy;
+  upstream?: UpstreamOptions;
+}
+
+// Ports are always checked for a local listener before hosting
+export interface UpstreamOptions {
+  // Sent to http://localhost:<port>
+  probe?: HealthCheckOptions;
+  // Wait this long for every port to be ready; hosting fails with 'timeout' otherwise
+  waitMs?: number;
 }
 
 export interface CreatePortRequest {
@@ -84,6 +136,7 @@ export interface AccessControlEntry {
   userId?: string;
   tenantId?: string;
   organizationId?: string;
+  deny?: boolean;
 }
 
 export interface CreateAccessRequest {
@@ -91,6 +144,66 @@ export interface CreateAccessRequest {
   entry: AccessControlEntry;
 }
 
+export interface DeleteAccessRequest {
+  tunnelId: string;
+  entry: AccessControlEntry;
+}
+
+export type AccessSubjectType = 'anonymous' | 'organization' | 'tenant' | 'repository' | 'user' | 'unknown';
+
+// Parsed `access list` entry
+export interface AccessEntry {
+  type: AccessSubjectType;
+  subject?: string;
+  provider?: string;
+  isDeny: boolean;
+  isInherited: boolean;
+  scopes: string[];
+  ports: number[]; // Empty for tunnel-level entries
+  expiration?: string;
+}
+
+export type AccessChange =
+  | { action: 'create'; entry: AccessControlEntry }
+  | { action: 'delete'; entry: AccessControlEntry }
+  | { action: 'reset' };
+
+export interface AccessPlan {
+  tunnelId: string;
+  current: AccessEntry[];
+  changes: AccessChange[];
+  commands: string[];
+}
+
+// Declarative spec files (TOML or YAML)
+
+export type SpecAction =
+  | { action: 'createTunnel'; req: CreateTunnelRequest }
+  | { action: 'updateTunnel'; req: UpdateTunnelRequest }
+  | { action: 'createPort'; req: CreatePortRequest }
+  | { action: 'updatePort'; req: UpdatePortRequest }
+  | { action: 'deletePort'; tunnelId: string; portNumber: number }
+  | { action: 'createAccess'; req: CreateAccessRequest }
+  | { action: 'deleteAccess'; req: DeleteAccessRequest }
+  | { action: 'resetAccess'; tunnelId: string };
+
+export type SpecStep = SpecAction & { summary: string };
+
+export interface SpecPlan {
+  steps: SpecStep[];
+}
+
+export interface SpecStepResult {
+  step: SpecStep;
+  status: 'applied' | 'failed' | 'skipped';
+  error?: string;
+}
+
+export interface SpecApplyReport {
+  results: SpecStepResult[];
+  success: boolean;
+}
+
 export interface UserInfo {
   userId: string;
   userName?: string;
@@ -106,17 +219,257 @@ export interface Cluster {
   latency?: number;
 }
 
+// Scopes accepted by issue_token
+export type TokenScope = 'connect' | 'host' | 'manage' | 'manage:ports' | 'inspect';
+
+// Everything but `token` is decoded from the JWT claims
 export interface TunnelToken {
   token: string;
-  scopes: AccessScope[];
+  tunnelId: string;
+  clusterId?: string;
+  scopes: TokenScope[];
+  port?: number;
+  issuedAt?: string;
   expiresAt?: string;
+  expiringSoon: boolean;
 }
 
+// Payload of the 'token-expiring' event
+export interface TokenExpiringEvent {
+  tunnelId: string;
+  scopes: TokenScope[];
+  expiresAt: string;
+}
+
+export type LogLevel = 'DEBUG' | 'INFO' | 'WARN' | 'ERROR';
+
 export interface LogEntry {
+  id: number;
   timestamp: string;
-  level: 'info' | 'warning' | 'error' | 'debug';
+  level: LogLevel;
   message: string;
   source?: string;
+  tunnelId?: string;
+}
+
+export interface LogFilter {
+  level?: LogLevel;
+  source?: string;
+  tunnelId?: string;
+  search?: string;
+  sinceId?: number;
+  limit?: number;
+}
+
+export interface LogFileSettings {
+  maxFileBytes: number;
+  maxFiles: number;
+  retentionDays: number;
+}
+
+// Extra regexes masked in logs and host output, on top of the built-in rules
+export interface RedactionSettings {
+  patterns: string[];
+}
+
+// RFC 3339 bounds for export_logs; either may be left open
+export interface LogRange {
+  since?: string;
+  until?: string;
+}
+
+export type OutputStream = 'stdout' | 'stderr';
+
+export interface HostOutputLine {
+  seq: number;
+  stream: OutputStream;
+  line: string;
+  timestamp: string;
+}
+
+export interface HostOutput {
+  tunnelId: string;
+  lines: HostOutputLine[];
+  nextSeq: number;
+  running: boolean;
+  pid?: number;
+  exitStatus?: string;
+}
+
+export type HostSessionState = 'running' | 'adopted' | 'stopped';
+
+export interface HostSessionInfo {
+  tunnelId: string;
+  pid: number;
+  startedAt: string;
+  program: string;
+  args: string[];
+  request: HostTunnelRequest;
+  state: HostSessionState;
+}
+
+export interface HostSessionsReport {
+  restoreOnStartup: boolean;
+  sessions: HostSessionInfo[];
+}
+
+export type StopMethod = 'graceful' | 'forced' | 'already-exited' | 'not-running';
+
+export interface StopResult {
+  tunnelId: string;
+  method: StopMethod;
+  pid?: number;
+  elapsedMs: number;
+}
+
+export interface ConnectTunnelRequest {
+  tunnelId: string;
+  accessToken?: string;
+  // Connect even if some of the tunnel's ports are taken locally
+  allowPortRemap?: boolean;
+}
+
+// "Forwarding from 127.0.0.1:3000 to host port 3000." reported by `devtunnel connect`
+export interface PortMapping {
+  localAddress: string;
+  localPort: number;
+  remotePort: number;
+}
+
+export type ConnectState = 'starting' | 'connected' | 'exited';
+
+// A `devtunnel connect` process; also the payload of the 'connect-status' event
+export interface ConnectSession {
+  tunnelId: string;
+  pid?: number;
+  startedAt: string;
+  state: ConnectState;
+  mappings: PortMapping[];
+  exitStatus?: string;
+}
+
+// Payload of the 'host-exited' event
+export interface HostExitedEvent {
+  tunnelId: string;
+  pid?: number;
+  exitStatus: string;
+  success: boolean;
+  requested: boolean;
+  willRestart: boolean;
+}
+
+// Payload of the 'host-restarted' event
+export interface HostRestartedEvent {
+  tunnelId: string;
+  pid?: number;
+  attempt: number;
+  delayMs: number;
+}
+
+// A port health-checked on a schedule; intervalMs is at least 5000
+export interface PortMonitor {
+  tunnelId: string;
+  portNumber: number;
+  url: string;
+  intervalMs: number;
+  check?: HealthCheckOptions;
+}
+
+export interface MonitorSettings {
+  monitors: PortMonitor[];
+}
+
+export interface HealthSample {
+  timestamp: string;
+  up: boolean;
+  latencyMs: number;
+  statusCode?: number;
+  error?: string;
+}
+
+// Stats over the requested window; missing until the port has been checked
+export interface PortHealth {
+  tunnelId: string;
+  portNumber: number;
+  url: string;
+  up?: boolean;
+  lastCheckedAt?: string;
+  lastError?: string;
+  samples: number;
+  uptimePercent?: number;
+  latencyP50Ms?: number;
+  latencyP95Ms?: number;
+  latencyP99Ms?: number;
+}
+
+// Payload of the 'port-health-changed' event
+export interface PortHealthChangedEvent {
+  tunnelId: string;
+  portNumber: number;
+  up: boolean;
+  sample: HealthSample;
+}
+
+export interface UpstreamStatus {
+  portNumber: number;
+  listening: boolean;
+  ready: boolean;
+  statusCode?: number;
+  error?: string;
+}
+
+// Payload of the 'upstream-changed' event
+export interface UpstreamReport {
+  tunnelId: string;
+  ready: boolean;
+  ports: UpstreamStatus[];
+  checkedAt: string;
+}
+
+// A local listening service suggested as a tunnel port
+export interface DiscoveredPort {
+  portNumber: number;
+  addresses: string[];
+  // Only known for processes of the current user
+  pid?: number;
+  process?: string;
+  // Missing when the port answered neither HTTPS nor HTTP
+  protocol?: Protocol;
+}
+
+// Background refresh; intervalMs 0 turns it off
+export interface PollerSettings {
+  intervalMs: number;
+}
+
+// Payload of the 'tunnel-added' event
+export interface TunnelAddedEvent {
+  tunnel: TunnelListItem;
+}
+
+// Payload of the 'tunnel-removed' event
+export interface TunnelRemovedEvent {
+  tunnelId: string;
+}
+
+export interface FieldChange {
+  field: string;
+  before: unknown;
+  after: unknown;
+}
+
+// Payload of the 'tunnel-updated' event
+export interface TunnelUpdatedEvent {
+  tunnelId: string;
+  changes: FieldChange[];
+}
+
+// Payload of the 'port-changed' event; updated holds the new values
+export interface PortChangedEvent {
+  tunnelId: string;
+  added: Port[];
+  removed: number[];
+  updated: Port[];
 }
 
 export interface TunnelStats {
@@ -131,10 +484,43 @@ export interface TunnelStats {
 
 // Request/Response types for Tauri commands
 
+export type ErrorCode =
+  | 'authRequired'
+  | 'notFound'
+  | 'conflict'
+  | 'quotaExceeded'
+  | 'invalidArgument'
+  | 'binaryMissing'
+  | 'timeout'
+  | 'network'
+  | 'cancelled'
+  | 'unknown';
+
+// Per-operation limits in ms, keyed by backend operation name
+export interface CliTimeouts {
+  defaultMs: number;
+  operations: Record<string, number>;
+}
+
+// Parallelism and throttling retries for devtunnel CLI calls
+export interface CliLimits {
+  maxParallel: number;
+  maxRetries: number;
+  retryBaseMs: number;
+}
+
+// Failure of one item in an otherwise successful batch
+export interface ItemError {
+  error: string;
+  errorCode: ErrorCode;
+}
+
 export interface CommandResponse<T> {
   success: boolean;
   data?: T;
   error?: string;
+  errorCode?: ErrorCode;
+  hint?: string;
 }
 
 export interface ListTunnelsRequest {